@@ -0,0 +1,176 @@
+use bevy::math::Vec3;
+
+use crate::collision::{sweep_aabb, Aabb};
+use crate::world::VoxelWorld;
+
+/// Distance probed below the feet to decide whether the walker is grounded.
+const GROUND_PROBE: f32 = 0.05;
+
+/// Tuning for the first-person walking controller. Distances are in voxels.
+#[derive(Clone, Copy, Debug)]
+pub struct WalkerSettings {
+    pub half_width: f32,
+    pub height: f32,
+    pub crouch_height: f32,
+    pub eye_offset: f32,
+    pub walk_speed: f32,
+    pub crouch_speed: f32,
+    pub jump_speed: f32,
+    pub gravity: f32,
+    pub terminal_speed: f32,
+    /// Tallest ledge climbed automatically while walking into it.
+    pub step_height: f32,
+}
+
+impl Default for WalkerSettings {
+    fn default() -> Self {
+        Self {
+            half_width: 0.3,
+            height: 1.8,
+            crouch_height: 1.2,
+            eye_offset: 0.15,
+            walk_speed: 6.0,
+            crouch_speed: 2.5,
+            jump_speed: 8.0,
+            gravity: 24.0,
+            terminal_speed: 50.0,
+            step_height: 1.0,
+        }
+    }
+}
+
+/// Per-frame intent for the walker.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WalkInput {
+    /// Desired horizontal direction; the Y component is ignored.
+    pub movement: Vec3,
+    pub jump: bool,
+    pub crouch: bool,
+}
+
+/// Physical state of a walker. `position` is the centre of its feet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WalkerState {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub grounded: bool,
+    pub crouching: bool,
+}
+
+impl WalkerState {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            ..Self::default()
+        }
+    }
+
+    pub fn height(&self, settings: &WalkerSettings) -> f32 {
+        if self.crouching {
+            settings.crouch_height
+        } else {
+            settings.height
+        }
+    }
+
+    /// Camera height above the feet.
+    pub fn eye_height(&self, settings: &WalkerSettings) -> f32 {
+        self.height(settings) - settings.eye_offset
+    }
+
+    pub fn bounds(&self, settings: &WalkerSettings) -> Aabb {
+        body_bounds(self.position, self.height(settings), settings)
+    }
+}
+
+fn body_bounds(feet: Vec3, height: f32, settings: &WalkerSettings) -> Aabb {
+    let half = Vec3::new(settings.half_width, 0.0, settings.half_width);
+    Aabb::new(feet - half, feet + half + Vec3::Y * height)
+}
+
+/// Returns true if the body rests on a solid voxel.
+pub fn is_grounded(world: &VoxelWorld, bounds: Aabb) -> bool {
+    sweep_aabb(world, bounds, Vec3::NEG_Y * GROUND_PROBE)
+        .blocked
+        .y
+}
+
+/// Advances the walker by `dt` seconds: gravity, jumping, crouching,
+/// collision with sliding, and automatic step-up onto low ledges.
+pub fn step_walker(
+    world: &VoxelWorld,
+    state: &mut WalkerState,
+    input: WalkInput,
+    settings: &WalkerSettings,
+    dt: f32,
+) {
+    // Only stand back up when there is room above the crouched body.
+    if input.crouch {
+        state.crouching = true;
+    } else if state.crouching
+        && !body_bounds(state.position, settings.height, settings).intersects_solid(world)
+    {
+        state.crouching = false;
+    }
+
+    let mut wish = Vec3::new(input.movement.x, 0.0, input.movement.z);
+    if wish.length_squared() > 1.0 {
+        wish = wish.normalize();
+    }
+    let speed = if state.crouching {
+        settings.crouch_speed
+    } else {
+        settings.walk_speed
+    };
+    state.velocity.x = wish.x * speed;
+    state.velocity.z = wish.z * speed;
+
+    if state.grounded && input.jump && !state.crouching {
+        state.velocity.y = settings.jump_speed;
+        state.grounded = false;
+    }
+    state.velocity.y = (state.velocity.y - settings.gravity * dt).max(-settings.terminal_speed);
+
+    let bounds = state.bounds(settings);
+    let motion = state.velocity * dt;
+    let swept = sweep_aabb(world, bounds, motion);
+    let mut applied = swept.motion;
+
+    let horizontally_blocked = swept.blocked.x || swept.blocked.z;
+    if state.grounded && horizontally_blocked && state.velocity.y <= 0.0 {
+        if let Some(stepped) = try_step_up(world, bounds, motion, settings.step_height) {
+            let gained = horizontal_length(stepped) - horizontal_length(applied);
+            if gained > 1e-4 {
+                applied = stepped;
+            }
+        }
+    }
+
+    state.position += applied;
+
+    if swept.blocked.y && state.velocity.y > 0.0 {
+        // Bumped a ceiling.
+        state.velocity.y = 0.0;
+    }
+    state.grounded = state.velocity.y <= 0.0 && is_grounded(world, state.bounds(settings));
+    if state.grounded {
+        state.velocity.y = 0.0;
+    }
+}
+
+/// Retries a blocked horizontal move from `step_height` higher, then settles back down.
+fn try_step_up(world: &VoxelWorld, bounds: Aabb, motion: Vec3, step_height: f32) -> Option<Vec3> {
+    let lift = sweep_aabb(world, bounds, Vec3::Y * step_height);
+    if lift.blocked.y {
+        return None;
+    }
+    let raised = bounds.translated(lift.motion);
+    let across = sweep_aabb(world, raised, Vec3::new(motion.x, 0.0, motion.z));
+    let moved = raised.translated(across.motion);
+    let settle = sweep_aabb(world, moved, Vec3::NEG_Y * (step_height + motion.y.abs()));
+    Some(lift.motion + across.motion + settle.motion)
+}
+
+fn horizontal_length(v: Vec3) -> f32 {
+    Vec3::new(v.x, 0.0, v.z).length()
+}
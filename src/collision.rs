@@ -0,0 +1,206 @@
+use bevy::math::{BVec3, IVec3, Vec3};
+
+use crate::world::VoxelWorld;
+
+/// Gap kept between a resolved box and the voxel face it touches, so
+/// floating point drift never leaves the box overlapping a solid voxel.
+const SKIN: f32 = 1e-4;
+
+/// Axis-aligned box in world space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center(center: Vec3, half_extents: Vec3) -> Self {
+        Self {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn translated(&self, offset: Vec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// Returns true if any solid voxel overlaps the box volume.
+    pub fn intersects_solid(&self, world: &VoxelWorld) -> bool {
+        let lo = (self.min + SKIN).floor().as_ivec3();
+        let hi = (self.max - SKIN).floor().as_ivec3();
+        for z in lo.z..=hi.z {
+            for y in lo.y..=hi.y {
+                for x in lo.x..=hi.x {
+                    if world.is_solid(IVec3::new(x, y, z)) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Outcome of sweeping a box through the voxel world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepResult {
+    /// Displacement that was actually applied.
+    pub motion: Vec3,
+    /// Axes on which the requested motion was cut short by a voxel.
+    pub blocked: BVec3,
+}
+
+/// Moves `aabb` by `motion`, resolving collisions one axis at a time.
+///
+/// The vertical axis is resolved first, then X, then Z. Blocking one axis
+/// keeps the remaining components, so a box pushed diagonally into a wall
+/// slides along it. Voxels the box already overlaps are ignored, which lets
+/// a box that starts embedded in terrain move out of it.
+pub fn sweep_aabb(world: &VoxelWorld, aabb: Aabb, motion: Vec3) -> SweepResult {
+    let mut current = aabb;
+    let mut applied = Vec3::ZERO;
+    let mut blocked = BVec3::FALSE;
+
+    for axis in [1, 0, 2] {
+        let requested = motion[axis];
+        let allowed = clip_axis(world, &current, axis, requested);
+        if allowed != requested {
+            blocked.set(axis, true);
+        }
+        let mut step = Vec3::ZERO;
+        step[axis] = allowed;
+        current = current.translated(step);
+        applied[axis] = allowed;
+    }
+
+    SweepResult {
+        motion: applied,
+        blocked,
+    }
+}
+
+/// Largest displacement along `axis` (up to `delta`) before the box hits a solid voxel.
+fn clip_axis(world: &VoxelWorld, aabb: &Aabb, axis: usize, delta: f32) -> f32 {
+    if delta == 0.0 {
+        return 0.0;
+    }
+
+    // Cross-section of voxels the box covers on the two other axes.
+    let (u, v) = match axis {
+        0 => (1, 2),
+        1 => (0, 2),
+        _ => (0, 1),
+    };
+    let u_range = (aabb.min[u] + SKIN).floor() as i32..=(aabb.max[u] - SKIN).floor() as i32;
+    let v_range = (aabb.min[v] + SKIN).floor() as i32..=(aabb.max[v] - SKIN).floor() as i32;
+
+    let layer_is_solid = |layer: i32| {
+        for a in u_range.clone() {
+            for b in v_range.clone() {
+                let mut cell = IVec3::ZERO;
+                cell[axis] = layer;
+                cell[u] = a;
+                cell[v] = b;
+                if world.is_solid(cell) {
+                    return true;
+                }
+            }
+        }
+        false
+    };
+
+    if delta > 0.0 {
+        let first = (aabb.max[axis] - SKIN).floor() as i32 + 1;
+        let last = (aabb.max[axis] + delta).ceil() as i32 - 1;
+        for layer in first..=last {
+            if layer_is_solid(layer) {
+                return (layer as f32 - aabb.max[axis] - SKIN).clamp(0.0, delta);
+            }
+        }
+    } else {
+        let first = (aabb.min[axis] + SKIN).floor() as i32 - 1;
+        let last = (aabb.min[axis] + delta).floor() as i32;
+        for layer in (last..=first).rev() {
+            if layer_is_solid(layer) {
+                return ((layer + 1) as f32 - aabb.min[axis] + SKIN).clamp(delta, 0.0);
+            }
+        }
+    }
+    delta
+}
+
+/// The first voxel a ray enters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    pub cell: IVec3,
+    /// Side of `cell` the ray came in through; zero when the ray starts
+    /// inside it.
+    pub normal: IVec3,
+    pub distance: f32,
+}
+
+/// Walks the cells along a ray from `origin` and returns the first one that
+/// holds a block other than air or water, within `max_distance`.
+pub fn raycast(
+    world: &VoxelWorld,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<RayHit> {
+    let direction = direction.try_normalize()?;
+    let mut cell = origin.floor().as_ivec3();
+    let step = direction.signum().as_ivec3();
+    // Distance along the ray between crossings of each axis' cell borders,
+    // and to the next crossing.
+    let delta = direction.abs().recip();
+    let mut next = Vec3::ZERO;
+    for axis in 0..3 {
+        let border = if direction[axis] > 0.0 {
+            cell[axis] as f32 + 1.0
+        } else {
+            cell[axis] as f32
+        };
+        next[axis] = if direction[axis] == 0.0 {
+            f32::INFINITY
+        } else {
+            (border - origin[axis]) / direction[axis]
+        };
+    }
+    let mut normal = IVec3::ZERO;
+    let mut distance = 0.0;
+    while distance <= max_distance {
+        let voxel = world.get(cell);
+        if !voxel.is_empty() && !voxel.is_water() {
+            return Some(RayHit {
+                cell,
+                normal,
+                distance,
+            });
+        }
+        let axis = if next.x < next.y && next.x < next.z {
+            0
+        } else if next.y < next.z {
+            1
+        } else {
+            2
+        };
+        distance = next[axis];
+        next[axis] += delta[axis];
+        cell[axis] += step[axis];
+        normal = IVec3::ZERO;
+        normal[axis] = -step[axis];
+    }
+    None
+}
@@ -0,0 +1,429 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+
+use crate::input::{Action, InputMap};
+
+/// Lines kept in the console's output log.
+const LOG_LINES: usize = 200;
+/// Output lines shown while the console is open.
+const VISIBLE_LINES: usize = 14;
+/// How deep `exec` may nest scripts, so a script that runs itself stops.
+pub const MAX_EXEC_DEPTH: usize = 8;
+
+/// Runs a command with its arguments (the words after the command name)
+/// and returns the text to print, or an error message.
+pub type CommandHandler = fn(&mut World, &[&str]) -> Result<String, String>;
+
+/// A console command as registered with [`CommandRegistry`].
+#[derive(Clone, Copy)]
+pub struct ConsoleCommand {
+    pub name: &'static str,
+    /// Argument synopsis shown by `help`, like `x y z`.
+    pub usage: &'static str,
+    pub help: &'static str,
+    pub run: CommandHandler,
+}
+
+/// Every command the console knows, by name.
+#[derive(Resource, Default)]
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, ConsoleCommand>,
+}
+
+impl CommandRegistry {
+    /// Adds `command`, replacing any earlier command of the same name.
+    pub fn register(&mut self, command: ConsoleCommand) {
+        self.commands.insert(command.name, command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.commands.get(name)
+    }
+
+    /// Registered commands in name order.
+    pub fn commands(&self) -> impl Iterator<Item = &ConsoleCommand> {
+        self.commands.values()
+    }
+
+    /// Command names starting with the first word of `input`, while only
+    /// that word has been typed.
+    pub fn completions(&self, input: &str) -> Vec<&'static str> {
+        if input.contains(char::is_whitespace) {
+            return Vec::new();
+        }
+        self.commands
+            .keys()
+            .copied()
+            .filter(|name| name.starts_with(input))
+            .collect()
+    }
+
+    /// `input` completed as far as every candidate agrees, with a trailing
+    /// space once a single command is left.
+    pub fn complete(&self, input: &str) -> String {
+        let candidates = self.completions(input);
+        match candidates[..] {
+            [] => input.to_string(),
+            [only] => format!("{only} "),
+            _ => {
+                let first = candidates[0];
+                let shared = candidates.iter().fold(first.len(), |len, name| {
+                    first
+                        .bytes()
+                        .zip(name.bytes())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                first[..shared].to_string()
+            }
+        }
+    }
+}
+
+/// Registering console commands from any plugin.
+pub trait ConsoleAppExt {
+    fn register_console_command(&mut self, command: ConsoleCommand) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn register_console_command(&mut self, command: ConsoleCommand) -> &mut Self {
+        self.init_resource::<CommandRegistry>();
+        self.world_mut()
+            .resource_mut::<CommandRegistry>()
+            .register(command);
+        self
+    }
+}
+
+/// Console state: the line being typed, history, output and the commands
+/// waiting to run.
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    log: VecDeque<String>,
+    history: Vec<String>,
+    /// Position while browsing history; `None` when editing a new line.
+    browsing: Option<usize>,
+    /// Lines waiting to run, with how many scripts deep they were queued.
+    pending: VecDeque<(String, usize)>,
+    /// Script depth of the line being run.
+    depth: usize,
+}
+
+impl Console {
+    /// Runs `line` next frame and remembers it in the history.
+    pub fn submit(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+        self.browsing = None;
+        self.queue(line);
+    }
+
+    /// Runs `line` next frame without touching the history.
+    pub fn queue(&mut self, line: &str) {
+        self.pending.push_back((line.to_string(), 0));
+    }
+
+    /// Appends output, dropping the oldest lines past the log limit.
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.log.len() == LOG_LINES {
+                self.log.pop_front();
+            }
+            self.log.push_back(line.to_string());
+        }
+    }
+
+    pub fn log(&self) -> impl Iterator<Item = &str> {
+        self.log.iter().map(String::as_str)
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Replaces the input with the previous history entry.
+    pub fn history_back(&mut self) {
+        let index = match self.browsing {
+            Some(index) => index.saturating_sub(1),
+            None => match self.history.len().checked_sub(1) {
+                Some(last) => last,
+                None => return,
+            },
+        };
+        self.browsing = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    /// Replaces the input with the next history entry, or clears it after
+    /// the newest one.
+    pub fn history_forward(&mut self) {
+        let Some(index) = self.browsing else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.browsing = Some(index + 1);
+            self.input = self.history[index + 1].clone();
+        } else {
+            self.browsing = None;
+            self.input.clear();
+        }
+    }
+}
+
+/// Commands in a script: one per line, skipping blank lines and `#`
+/// comments.
+pub fn script_lines(source: &str) -> Vec<&str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Runs one command line against `world`.
+pub fn execute(world: &mut World, line: &str) -> Result<String, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&name, args)) = words.split_first() else {
+        return Ok(String::new());
+    };
+    let command = world
+        .get_resource::<CommandRegistry>()
+        .and_then(|registry| registry.get(name).copied())
+        .ok_or_else(|| format!("unknown command {name:?}; try help"))?;
+    (command.run)(world, args)
+}
+
+/// Parses the startup script flag, `--script FILE`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsoleScript(pub Option<PathBuf>);
+
+impl ConsoleScript {
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut script = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--script" {
+                let path = args.next().ok_or("--script expects a file")?;
+                script.0 = Some(path.into());
+            }
+        }
+        Ok(script)
+    }
+}
+
+/// The drop-down console: the `toggle_console` key (backtick unless
+/// rebound) opens it, Enter runs the line, Up and
+/// Down walk the history and Tab completes command names. While it is
+/// open it takes all keyboard input. Commands from the startup script run
+/// once the world is set up.
+pub struct ConsolePlugin {
+    pub script: ConsoleScript,
+}
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        let mut console = Console::default();
+        if let Some(path) = &self.script.0 {
+            match std::fs::read_to_string(path) {
+                Ok(source) => script_lines(&source)
+                    .into_iter()
+                    .for_each(|line| console.queue(line)),
+                Err(err) => console.print(&format!("cannot read {}: {err}", path.display())),
+            }
+        }
+        app.insert_resource(console)
+            .init_resource::<CommandRegistry>()
+            .register_console_command(ConsoleCommand {
+                name: "help",
+                usage: "",
+                help: "list every command",
+                run: help,
+            })
+            .register_console_command(ConsoleCommand {
+                name: "exec",
+                usage: "file",
+                help: "run the commands in a script file",
+                run: exec,
+            })
+            .add_systems(Startup, spawn_console)
+            .add_systems(PreUpdate, console_input.after(InputSystem))
+            .add_systems(Update, (run_console_commands, update_console_text));
+    }
+}
+
+fn help(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let registry = world.resource::<CommandRegistry>();
+    Ok(registry
+        .commands()
+        .map(|command| format!("{} {} - {}", command.name, command.usage, command.help))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// The `exec` command: queues the lines of a script to run next frame, one
+/// level deeper than the line that ran it.
+pub fn exec(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [path] = args else {
+        return Err("usage: exec file".into());
+    };
+    let depth = world.resource::<Console>().depth + 1;
+    if depth > MAX_EXEC_DEPTH {
+        return Err(format!(
+            "{path}: scripts nested more than {MAX_EXEC_DEPTH} deep"
+        ));
+    }
+    let source = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    let lines = script_lines(&source);
+    let mut console = world.resource_mut::<Console>();
+    for &line in &lines {
+        console.pending.push_back((line.to_string(), depth));
+    }
+    Ok(format!("queued {} commands from {path}", lines.len()))
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::srgba(0.05, 0.05, 0.08, 0.85).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            ConsoleRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::srgb(0.85, 0.9, 0.85),
+                        ..default()
+                    },
+                ),
+                ConsoleText,
+            ));
+        });
+}
+
+/// Edits the console line from keyboard events and keeps every other
+/// system from seeing keys while the console is open.
+fn console_input(
+    mut events: EventReader<KeyboardInput>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut console: ResMut<Console>,
+    registry: Res<CommandRegistry>,
+    input_map: Option<Res<InputMap>>,
+) {
+    let toggle = match &input_map {
+        Some(map) => map.keys(Action::ToggleConsole),
+        None => Action::ToggleConsole.default_keys(),
+    };
+    let was_open = console.open;
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if toggle.contains(&event.key_code) {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                console.submit(&line);
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Escape => console.open = false,
+            Key::ArrowUp => console.history_back(),
+            Key::ArrowDown => console.history_forward(),
+            Key::Tab => {
+                let candidates = registry.completions(&console.input);
+                if candidates.len() > 1 {
+                    console.print(&candidates.join("  "));
+                }
+                console.input = registry.complete(&console.input);
+            }
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => {}
+        }
+    }
+    if console.open || was_open {
+        keys.reset_all();
+    }
+}
+
+/// Runs the command lines queued before this frame with full world access
+/// and logs the results. Lines they queue wait for the next frame.
+pub fn run_console_commands(world: &mut World) {
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for (line, depth) in pending {
+        world.resource_mut::<Console>().depth = depth;
+        let result = execute(world, &line);
+        let mut console = world.resource_mut::<Console>();
+        console.depth = 0;
+        console.print(&format!("> {line}"));
+        match result {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => console.print(&output),
+            Err(err) => console.print(&format!("error: {err}")),
+        }
+    }
+}
+
+fn update_console_text(
+    console: Res<Console>,
+    mut roots: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut visibility in &mut roots {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let skip = console.log.len().saturating_sub(VISIBLE_LINES);
+    let mut lines: Vec<&str> = console.log().skip(skip).collect();
+    let prompt = format!("] {}_", console.input);
+    lines.push(&prompt);
+    for mut text in &mut texts {
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,322 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+/// Full daylight illuminance of the sun, in lux.
+const SUN_ILLUMINANCE: f32 = 30_000.0;
+/// Illuminance of a high full moon, in lux.
+const MOON_ILLUMINANCE: f32 = 600.0;
+
+/// Clock driving the sun, moon, sky and anything that reacts to nightfall.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TimeOfDay {
+    /// Hour of the day in `0.0..24.0`.
+    pub hours: f32,
+    /// Real seconds for one full 24 hour cycle.
+    pub day_length: f32,
+    /// Observer latitude in degrees; tilts the sun's path across the sky.
+    pub latitude: f32,
+    /// Solar declination in degrees (0 at the equinoxes, ±23.4 at the solstices).
+    pub declination: f32,
+    pub paused: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self {
+            hours: 9.0,
+            day_length: 600.0,
+            latitude: 40.0,
+            declination: 10.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// Builds a clock from command-line flags:
+    /// `--time HH[:MM]`, `--day-length SECONDS`, `--latitude DEGREES`, `--freeze-time`.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut time = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value =
+                |name: &str| args.next().ok_or_else(|| format!("{name} expects a value"));
+            match arg.as_str() {
+                "--time" => {
+                    let raw = value("--time")?;
+                    time.hours =
+                        parse_clock(&raw).ok_or_else(|| format!("invalid time {raw:?}"))?;
+                }
+                "--day-length" => {
+                    let raw = value("--day-length")?;
+                    time.day_length = raw
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|v| *v > 0.0)
+                        .ok_or_else(|| format!("invalid day length {raw:?}"))?;
+                }
+                "--latitude" => {
+                    let raw = value("--latitude")?;
+                    time.latitude = raw
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|v| (-90.0..=90.0).contains(v))
+                        .ok_or_else(|| format!("invalid latitude {raw:?}"))?;
+                }
+                "--freeze-time" => time.paused = true,
+                _ => {}
+            }
+        }
+        Ok(time)
+    }
+
+    pub fn set_hours(&mut self, hours: f32) {
+        self.hours = hours.rem_euclid(24.0);
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        if !self.paused {
+            self.set_hours(self.hours + seconds / self.day_length * 24.0);
+        }
+    }
+
+    /// Unit vector pointing from the ground towards the sun.
+    pub fn sun_direction(&self) -> Vec3 {
+        celestial_direction(self.hour_angle(), self.declination, self.latitude)
+    }
+
+    /// Unit vector towards the moon, which rides roughly opposite the sun.
+    pub fn moon_direction(&self) -> Vec3 {
+        celestial_direction(self.hour_angle() + PI, -self.declination, self.latitude)
+    }
+
+    /// Sun angle above the horizon in radians (negative at night).
+    pub fn sun_elevation(&self) -> f32 {
+        self.sun_direction().y.clamp(-1.0, 1.0).asin()
+    }
+
+    /// 0 at night, 1 in full daylight, ramping through twilight.
+    pub fn daylight(&self) -> f32 {
+        smoothstep(-0.1, 0.2, self.sun_elevation())
+    }
+
+    pub fn sun_illuminance(&self) -> f32 {
+        SUN_ILLUMINANCE * smoothstep(-0.02, 0.35, self.sun_elevation())
+    }
+
+    pub fn moon_illuminance(&self) -> f32 {
+        let moon_elevation = self.moon_direction().y.clamp(-1.0, 1.0).asin();
+        MOON_ILLUMINANCE * smoothstep(0.0, 0.3, moon_elevation) * (1.0 - self.daylight())
+    }
+
+    /// Background sky color: deep blue at night, warm at the horizon, blue by day.
+    pub fn sky_color(&self) -> Color {
+        let night = Vec3::new(0.01, 0.012, 0.035);
+        let dusk = Vec3::new(0.75, 0.4, 0.28);
+        let day = Vec3::new(0.42, 0.62, 0.92);
+        let elevation = self.sun_elevation();
+        let rgb = if elevation < 0.0 {
+            night.lerp(dusk, smoothstep(-0.25, 0.0, elevation))
+        } else {
+            dusk.lerp(day, smoothstep(0.0, 0.3, elevation))
+        };
+        Color::srgb(rgb.x, rgb.y, rgb.z)
+    }
+
+    /// Sun tint: reddened near the horizon, white overhead.
+    pub fn sun_color(&self) -> Color {
+        let t = smoothstep(0.0, 0.4, self.sun_elevation());
+        let rgb = Vec3::new(1.0, 0.55, 0.35).lerp(Vec3::ONE, t);
+        Color::srgb(rgb.x, rgb.y, rgb.z)
+    }
+
+    pub fn ambient_brightness(&self) -> f32 {
+        40.0 + 360.0 * self.daylight()
+    }
+
+    fn hour_angle(&self) -> f32 {
+        (self.hours - 12.0) / 24.0 * TAU
+    }
+}
+
+/// Parses `HH`, `HH.H` or `HH:MM` into fractional hours.
+pub fn parse_clock(raw: &str) -> Option<f32> {
+    let hours = match raw.split_once(':') {
+        Some((h, m)) => {
+            let h: f32 = h.parse().ok()?;
+            let m: f32 = m.parse().ok()?;
+            if !(0.0..60.0).contains(&m) {
+                return None;
+            }
+            h + m / 60.0
+        }
+        None => raw.parse().ok()?,
+    };
+    (0.0..24.0).contains(&hours).then_some(hours)
+}
+
+/// Direction to a body at the given hour angle and declination, seen from
+/// `latitude`. World axes: +X east, -Z north, +Y up.
+fn celestial_direction(hour_angle: f32, declination_deg: f32, latitude_deg: f32) -> Vec3 {
+    let dec = declination_deg.to_radians();
+    let lat = latitude_deg.clamp(-90.0, 90.0).to_radians();
+    let east = -dec.cos() * hour_angle.sin();
+    let north = lat.cos() * dec.sin() - lat.sin() * dec.cos() * hour_angle.cos();
+    let up = lat.sin() * dec.sin() + lat.cos() * dec.cos() * hour_angle.cos();
+    Vec3::new(east, up, -north).normalize()
+}
+
+/// Hermite ramp from 0 at `edge0` to 1 at `edge1`.
+pub(crate) fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Atmospheric modifiers applied on top of the clock, set by the weather.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SkyConditions {
+    /// Multiplier on sun and moon illuminance.
+    pub light_factor: f32,
+    /// 0 for a clear sky, 1 for a fully grey one.
+    pub haze: f32,
+}
+
+impl Default for SkyConditions {
+    fn default() -> Self {
+        Self {
+            light_factor: 1.0,
+            haze: 0.0,
+        }
+    }
+}
+
+impl SkyConditions {
+    /// Blends `color` towards a grey of the same brightness.
+    pub fn apply_haze(&self, color: Color) -> Color {
+        let rgb = color.to_linear();
+        let luma = 0.2126 * rgb.red + 0.7152 * rgb.green + 0.0722 * rgb.blue;
+        let grey = Vec3::splat(luma * 1.1);
+        let mixed = Vec3::new(rgb.red, rgb.green, rgb.blue).lerp(grey, self.haze.clamp(0.0, 1.0));
+        Color::linear_rgb(mixed.x, mixed.y, mixed.z)
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+#[derive(Component)]
+struct Moon;
+
+/// Light whose intensity follows the time of day (headlights, glowing props).
+#[derive(Component, Clone, Copy)]
+pub struct NightLight {
+    pub day_intensity: f32,
+    pub night_intensity: f32,
+}
+
+impl NightLight {
+    fn intensity(&self, time: &TimeOfDay) -> f32 {
+        let t = time.daylight();
+        self.night_intensity + (self.day_intensity - self.night_intensity) * t
+    }
+}
+
+/// Sun, moon, sky color and ambient light driven by [`TimeOfDay`].
+pub struct DayNightPlugin {
+    pub initial: TimeOfDay,
+}
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.initial)
+            .init_resource::<SkyConditions>()
+            .insert_resource(ClearColor(self.initial.sky_color()))
+            .add_systems(Startup, spawn_sky_lights)
+            .add_systems(
+                Update,
+                (
+                    advance_time_of_day,
+                    update_sky.after(advance_time_of_day),
+                    update_night_lights.after(advance_time_of_day),
+                ),
+            );
+    }
+}
+
+fn spawn_sky_lights(mut commands: Commands) {
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                illuminance: SUN_ILLUMINANCE,
+                ..default()
+            },
+            ..default()
+        },
+        Sun,
+    ));
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                color: Color::srgb(0.6, 0.7, 1.0),
+                shadows_enabled: false,
+                illuminance: 0.0,
+                ..default()
+            },
+            ..default()
+        },
+        Moon,
+    ));
+}
+
+fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    time_of_day.advance(time.delta_seconds());
+}
+
+fn light_facing(direction_to_light: Vec3) -> Transform {
+    // Directional lights shine along their forward axis.
+    let up = if direction_to_light.y.abs() > 0.999 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    Transform::IDENTITY.looking_to(-direction_to_light, up)
+}
+
+#[allow(clippy::type_complexity)]
+fn update_sky(
+    time_of_day: Res<TimeOfDay>,
+    conditions: Res<SkyConditions>,
+    mut clear_color: ResMut<ClearColor>,
+    mut ambient: ResMut<AmbientLight>,
+    mut suns: Query<(&mut Transform, &mut DirectionalLight), (With<Sun>, Without<Moon>)>,
+    mut moons: Query<(&mut Transform, &mut DirectionalLight), (With<Moon>, Without<Sun>)>,
+) {
+    for (mut transform, mut light) in &mut suns {
+        *transform = light_facing(time_of_day.sun_direction());
+        light.illuminance = time_of_day.sun_illuminance() * conditions.light_factor;
+        light.color = time_of_day.sun_color();
+    }
+    for (mut transform, mut light) in &mut moons {
+        *transform = light_facing(time_of_day.moon_direction());
+        light.illuminance = time_of_day.moon_illuminance() * conditions.light_factor;
+    }
+    let sky = conditions.apply_haze(time_of_day.sky_color());
+    clear_color.0 = sky;
+    ambient.color = sky;
+    ambient.brightness = time_of_day.ambient_brightness();
+}
+
+fn update_night_lights(
+    time_of_day: Res<TimeOfDay>,
+    mut spots: Query<(&NightLight, &mut SpotLight)>,
+    mut points: Query<(&NightLight, &mut PointLight)>,
+) {
+    for (night, mut light) in &mut spots {
+        light.intensity = night.intensity(&time_of_day);
+    }
+    for (night, mut light) in &mut points {
+        light.intensity = night.intensity(&time_of_day);
+    }
+}
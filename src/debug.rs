@@ -0,0 +1,80 @@
+use bevy::math::{IVec3, Vec3};
+
+use crate::visibility::CullingStats;
+use crate::voxel::Voxel;
+use crate::world::chunk_coord;
+
+/// Everything the debug overlay reports for one frame.
+#[derive(Clone, Debug, Default)]
+pub struct DebugInfo {
+    /// Smoothed frames per second and frame time in milliseconds, once
+    /// enough frames have been measured.
+    pub fps: Option<f64>,
+    pub frame_time: Option<f64>,
+    pub camera: String,
+    pub position: Vec3,
+    pub forward: Vec3,
+    /// The voxel under the crosshair and its cell.
+    pub target: Option<(IVec3, Voxel)>,
+    pub loaded_chunks: usize,
+    pub meshed_chunks: usize,
+    pub culling: CullingStats,
+    pub vertices: usize,
+    pub triangles: usize,
+}
+
+impl DebugInfo {
+    /// The overlay text, one entry per line.
+    pub fn lines(&self) -> Vec<String> {
+        let fps = match (self.fps, self.frame_time) {
+            (Some(fps), Some(frame_time)) => format!("{fps:.0} fps ({frame_time:.1} ms)"),
+            _ => "-- fps".to_string(),
+        };
+        let p = self.position;
+        let chunk = chunk_coord(p.floor().as_ivec3());
+        let pitch = self.forward.normalize_or_zero().y.asin().to_degrees();
+        let target = match self.target {
+            Some((cell, voxel)) => format!("{voxel} at {} {} {}", cell.x, cell.y, cell.z),
+            None => "none".to_string(),
+        };
+        let culling = self.culling;
+        vec![
+            fps,
+            format!("Camera: {}", self.camera),
+            format!(
+                "XYZ: {:.1} / {:.1} / {:.1}  Chunk: {} {} {}",
+                p.x, p.y, p.z, chunk.x, chunk.y, chunk.z
+            ),
+            format!("Facing: {} ({pitch:.0}° pitch)", compass(self.forward)),
+            format!("Target: {target}"),
+            format!(
+                "Chunks: {} loaded, {} meshed, {} drawn",
+                self.loaded_chunks, self.meshed_chunks, culling.drawn
+            ),
+            format!(
+                "Culled: {} occluded, {} outside view, {} buried",
+                culling.occluded, culling.outside_view, culling.buried
+            ),
+            format!(
+                "Mesh: {} vertices, {} triangles",
+                self.vertices, self.triangles
+            ),
+        ]
+    }
+}
+
+/// The horizontal direction `forward` points closest to, named like block
+/// facings: east is +x and south is +z.
+pub fn compass(forward: Vec3) -> &'static str {
+    if forward.x.abs() >= forward.z.abs() {
+        if forward.x >= 0.0 {
+            "east (+x)"
+        } else {
+            "west (-x)"
+        }
+    } else if forward.z > 0.0 {
+        "south (+z)"
+    } else {
+        "north (-z)"
+    }
+}
@@ -0,0 +1,131 @@
+use bevy::math::{IVec3, Vec3};
+
+use crate::collision::{sweep_aabb, Aabb};
+use crate::voxel::Voxel;
+use crate::world::VoxelWorld;
+
+const DEBRIS_HALF_EXTENT: f32 = 0.2;
+const DEBRIS_GRAVITY: f32 = 20.0;
+/// Seconds before a debris piece disappears.
+pub const DEBRIS_LIFETIME: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Explosion {
+    pub center: Vec3,
+    pub radius: f32,
+    /// Force at the centre; it falls off linearly to zero at `radius`.
+    pub power: f32,
+}
+
+impl Explosion {
+    /// Force left at `cell`, with a little per-voxel jitter so craters have
+    /// ragged edges. The jitter is a hash of the position, so the same blast
+    /// always carves the same crater.
+    pub fn force_at(&self, cell: IVec3) -> f32 {
+        let distance = (cell.as_vec3() + Vec3::splat(0.5)).distance(self.center);
+        if distance > self.radius {
+            return 0.0;
+        }
+        let falloff = 1.0 - distance / self.radius;
+        self.power * falloff * (0.85 + 0.3 * jitter(cell))
+    }
+}
+
+/// Voxels destroyed by `explosion`: every non-empty voxel within the radius
+/// whose remaining force exceeds its resistance. Sorted by position.
+pub fn blast_cells(world: &VoxelWorld, explosion: &Explosion) -> Vec<(IVec3, Voxel)> {
+    let min = (explosion.center - Vec3::splat(explosion.radius))
+        .floor()
+        .as_ivec3();
+    let max = (explosion.center + Vec3::splat(explosion.radius))
+        .ceil()
+        .as_ivec3();
+    let mut cells = Vec::new();
+    for y in min.y..=max.y {
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                let cell = IVec3::new(x, y, z);
+                let voxel = world.get(cell);
+                if voxel.is_empty() {
+                    continue;
+                }
+                if explosion.force_at(cell) > voxel.resistance() {
+                    cells.push((cell, voxel));
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// A chunk of a destroyed voxel flying away from an explosion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Debris {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub voxel: Voxel,
+    pub age: f32,
+}
+
+/// Launches up to `max` debris pieces from the destroyed cells, spread
+/// evenly over them, flying outward and upward from the blast centre.
+pub fn spawn_debris(
+    explosion: &Explosion,
+    destroyed: &[(IVec3, Voxel)],
+    max: usize,
+) -> Vec<Debris> {
+    if destroyed.is_empty() || max == 0 {
+        return Vec::new();
+    }
+    let stride = destroyed.len().div_ceil(max);
+    destroyed
+        .iter()
+        .step_by(stride)
+        .map(|&(cell, voxel)| {
+            let position = cell.as_vec3() + Vec3::splat(0.5);
+            let outward = (position - explosion.center).normalize_or(Vec3::Y);
+            let speed = 6.0 + 6.0 * jitter(cell);
+            Debris {
+                position,
+                velocity: outward * speed + Vec3::Y * (4.0 + 4.0 * jitter(cell + IVec3::Y)),
+                voxel,
+                age: 0.0,
+            }
+        })
+        .collect()
+}
+
+/// Moves a debris piece one step under gravity, bouncing off voxels with
+/// heavy damping. Returns false once the piece has expired.
+pub fn step_debris(world: &VoxelWorld, debris: &mut Debris, dt: f32) -> bool {
+    debris.age += dt;
+    if debris.age >= DEBRIS_LIFETIME {
+        return false;
+    }
+    debris.velocity.y -= DEBRIS_GRAVITY * dt;
+    let bounds = Aabb::from_center(debris.position, Vec3::splat(DEBRIS_HALF_EXTENT));
+    let sweep = sweep_aabb(world, bounds, debris.velocity * dt);
+    debris.position += sweep.motion;
+    for axis in 0..3 {
+        if sweep.blocked.test(axis) {
+            debris.velocity[axis] *= -0.3;
+            // Ground contact scrubs horizontal speed.
+            if axis == 1 {
+                debris.velocity.x *= 0.6;
+                debris.velocity.z *= 0.6;
+            }
+        }
+    }
+    true
+}
+
+/// Position hash to `0..1`.
+fn jitter(cell: IVec3) -> f32 {
+    let mut h = (cell.x as u32).wrapping_mul(0x8da6_b343)
+        ^ (cell.y as u32).wrapping_mul(0xd816_3841)
+        ^ (cell.z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^= h >> 15;
+    (h & 0xffff) as f32 / 65_535.0
+}
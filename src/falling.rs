@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+use bevy::math::{IVec2, IVec3, Vec3};
+
+use crate::voxel::Voxel;
+use crate::world::VoxelWorld;
+
+/// A column of gravity-affected voxels that lost its support and is falling
+/// as one body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FallingBody {
+    pub id: u32,
+    /// World `(x, z)` of the column.
+    pub column: IVec2,
+    /// World y of the bottom face of the lowest voxel.
+    pub bottom: f32,
+    /// Downward speed in voxels per second.
+    pub speed: f32,
+    /// Voxels from bottom to top.
+    pub voxels: Vec<Voxel>,
+}
+
+impl FallingBody {
+    /// Minimum corner of the lowest voxel.
+    pub fn origin(&self) -> Vec3 {
+        Vec3::new(self.column.x as f32, self.bottom, self.column.y as f32)
+    }
+}
+
+/// Falling sand and gravel. Edited cells are checked for unsupported
+/// gravity blocks, which detach as [`FallingBody`]s and re-solidify where
+/// they land. Bodies are stepped in a fixed order so a run is reproducible
+/// for a given sequence of time steps.
+#[derive(Clone, Debug)]
+pub struct FallingSim {
+    bodies: Vec<FallingBody>,
+    pending: HashSet<IVec3>,
+    next_id: u32,
+    pub gravity: f32,
+    pub terminal_speed: f32,
+    /// Lowest voxel y; anything resting on this layer counts as supported.
+    pub min_y: i32,
+}
+
+impl Default for FallingSim {
+    fn default() -> Self {
+        Self {
+            bodies: Vec::new(),
+            pending: HashSet::new(),
+            next_id: 0,
+            gravity: 20.0,
+            terminal_speed: 30.0,
+            min_y: 0,
+        }
+    }
+}
+
+impl FallingSim {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the voxel at `pos` changed, so it and the voxel above it
+    /// are checked for support.
+    pub fn notify(&mut self, pos: IVec3) {
+        self.pending.insert(pos);
+    }
+
+    pub fn bodies(&self) -> &[FallingBody] {
+        &self.bodies
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.bodies.is_empty() && self.pending.is_empty()
+    }
+
+    fn is_supported(&self, world: &VoxelWorld, pos: IVec3) -> bool {
+        pos.y <= self.min_y || world.get(pos - IVec3::Y).is_solid()
+    }
+
+    /// Turns unsupported gravity columns near pending edits into falling
+    /// bodies. Returns the cells they vacated; the caller must clear them.
+    pub fn detach(&mut self, world: &VoxelWorld) -> Vec<IVec3> {
+        let mut pending: Vec<IVec3> = self.pending.drain().collect();
+        pending.sort_by_key(|pos| (pos.y, pos.z, pos.x));
+
+        let mut vacated = Vec::new();
+        let mut taken = HashSet::new();
+        for pos in pending {
+            for start in [pos, pos + IVec3::Y] {
+                if taken.contains(&start)
+                    || !world.get(start).falls()
+                    || self.is_supported(world, start)
+                {
+                    continue;
+                }
+                let mut voxels = Vec::new();
+                let mut cell = start;
+                while world.get(cell).falls() {
+                    voxels.push(world.get(cell));
+                    taken.insert(cell);
+                    vacated.push(cell);
+                    cell += IVec3::Y;
+                }
+                self.bodies.push(FallingBody {
+                    id: self.next_id,
+                    column: IVec2::new(start.x, start.z),
+                    bottom: start.y as f32,
+                    speed: 0.0,
+                    voxels,
+                });
+                self.next_id = self.next_id.wrapping_add(1);
+            }
+        }
+        vacated
+    }
+
+    /// Advances every body by `dt` seconds and returns the voxels of bodies
+    /// that landed, placed from the cell they came to rest in upwards. A
+    /// voxel meeting a solid cell, such as an overhang placed into the body
+    /// while it fell, is stacked on the first free cell above it instead.
+    pub fn step(&mut self, world: &VoxelWorld, dt: f32) -> Vec<(IVec3, Voxel)> {
+        self.bodies
+            .sort_by(|a, b| a.bottom.total_cmp(&b.bottom).then(a.id.cmp(&b.id)));
+
+        let mut landed_cells: HashSet<IVec3> = HashSet::new();
+        let mut writes = Vec::new();
+        let mut still_falling = Vec::new();
+        for mut body in std::mem::take(&mut self.bodies) {
+            body.speed = (body.speed + self.gravity * dt).min(self.terminal_speed);
+            let target = body.bottom - body.speed * dt;
+
+            // Scan the cells the bottom passes through this step.
+            let start = body.bottom.floor() as i32;
+            let end = target.floor() as i32;
+            let mut rest = None;
+            for y in (end..start).rev() {
+                let cell = IVec3::new(body.column.x, y, body.column.y);
+                if y < self.min_y || world.get(cell).is_solid() || landed_cells.contains(&cell) {
+                    rest = Some(y + 1);
+                    break;
+                }
+            }
+
+            match rest {
+                Some(y) => {
+                    let mut cell = IVec3::new(body.column.x, y, body.column.y);
+                    for &voxel in &body.voxels {
+                        while world.get(cell).is_solid() || landed_cells.contains(&cell) {
+                            cell += IVec3::Y;
+                        }
+                        landed_cells.insert(cell);
+                        writes.push((cell, voxel));
+                        cell += IVec3::Y;
+                    }
+                }
+                None => {
+                    body.bottom = target;
+                    still_falling.push(body);
+                }
+            }
+        }
+        self.bodies = still_falling;
+        writes
+    }
+
+    /// Runs detach and step directly against `world` with a fixed `dt`
+    /// until nothing is falling or `max_steps` runs out. Returns the number
+    /// of steps run.
+    pub fn settle(&mut self, world: &mut VoxelWorld, dt: f32, max_steps: usize) -> usize {
+        for step in 0..max_steps {
+            for cell in self.detach(world) {
+                world.set(cell, Voxel::AIR);
+                self.notify(cell);
+            }
+            if self.bodies.is_empty() && self.pending.is_empty() {
+                return step;
+            }
+            for (cell, voxel) in self.step(world, dt) {
+                world.set(cell, voxel);
+                self.notify(cell);
+            }
+        }
+        max_steps
+    }
+}
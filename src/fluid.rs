@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use bevy::math::IVec3;
+
+use crate::voxel::Voxel;
+use crate::world::VoxelWorld;
+
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+const NEIGHBOURS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// Cellular-automaton water flow.
+///
+/// Only cells next to recent changes are re-evaluated. Each tick reads the
+/// world as it was at the start of the tick, so the outcome does not depend
+/// on evaluation order.
+#[derive(Clone, Default)]
+pub struct FluidSim {
+    active: HashSet<IVec3>,
+    /// Voxel region water may flow in (min inclusive, max exclusive).
+    bounds: Option<(IVec3, IVec3)>,
+}
+
+impl FluidSim {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A simulation that never writes outside `min..max`.
+    pub fn with_bounds(min: IVec3, max: IVec3) -> Self {
+        Self {
+            active: HashSet::new(),
+            bounds: Some((min, max)),
+        }
+    }
+
+    /// Schedules `pos` and its neighbours for the next tick.
+    pub fn wake(&mut self, pos: IVec3) {
+        self.active.insert(pos);
+        self.active
+            .extend(NEIGHBOURS.iter().map(|&offset| pos + offset));
+    }
+
+    /// Schedules the cells around the inclusive box `min..=max` after it
+    /// changed as a whole. Cells inside the box only flow once their
+    /// surroundings change, so just its surface and the layer around it
+    /// are woken.
+    pub fn wake_box(&mut self, min: IVec3, max: IVec3) {
+        for z in min.z..=max.z {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let pos = IVec3::new(x, y, z);
+                    if pos.cmpeq(min).any() || pos.cmpeq(max).any() {
+                        self.wake(pos);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Runs one tick and returns the voxel writes it produces, sorted by
+    /// position. The caller applies them and wakes the written cells (as
+    /// `VoxelTerrain::set_voxel` does) so flow continues next tick.
+    pub fn step(&mut self, world: &VoxelWorld) -> Vec<(IVec3, Voxel)> {
+        let mut cells: Vec<IVec3> = self
+            .active
+            .drain()
+            .filter(|&cell| match self.bounds {
+                Some((min, max)) => cell.cmpge(min).all() && cell.cmplt(max).all(),
+                None => true,
+            })
+            .collect();
+        cells.sort_by_key(|cell| (cell.y, cell.z, cell.x));
+        cells
+            .into_iter()
+            .filter_map(|cell| {
+                let next = next_state(world, cell);
+                (next != world.get(cell)).then_some((cell, next))
+            })
+            .collect()
+    }
+
+    /// Applies ticks directly to `world` until it settles or `max_ticks`
+    /// runs out. Returns the number of ticks run.
+    pub fn settle(&mut self, world: &mut VoxelWorld, max_ticks: usize) -> usize {
+        for tick in 0..max_ticks {
+            if self.is_idle() {
+                return tick;
+            }
+            for (pos, voxel) in self.step(world) {
+                world.set(pos, voxel);
+                self.wake(pos);
+            }
+        }
+        max_ticks
+    }
+}
+
+/// What `cell` becomes on the next tick.
+///
+/// Sources persist. Water below any water falls at full height. Two
+/// neighbouring sources over solid ground or a source form a new source.
+/// Otherwise a cell takes one level more than its lowest-level horizontal
+/// neighbour that cannot fall, drying out past [`Voxel::MAX_WATER_LEVEL`].
+pub fn next_state(world: &VoxelWorld, cell: IVec3) -> Voxel {
+    let current = world.get(cell);
+    if !(current.is_empty() || current.is_water()) || current == Voxel::WATER {
+        return current;
+    }
+    if world.get(cell + IVec3::Y).is_water() {
+        return Voxel::water(0, true);
+    }
+
+    let below = world.get(cell - IVec3::Y);
+    let sources = HORIZONTAL
+        .iter()
+        .filter(|&&offset| world.get(cell + offset) == Voxel::WATER)
+        .count();
+    if sources >= 2 && (below.is_solid() || below == Voxel::WATER) {
+        return Voxel::WATER;
+    }
+
+    HORIZONTAL
+        .iter()
+        .filter_map(|&offset| {
+            let neighbour = cell + offset;
+            let voxel = world.get(neighbour);
+            let level = voxel.water_level()?;
+            if !spreads_sideways(world, neighbour) {
+                return None;
+            }
+            // Falling water spreads like a source where it lands.
+            let level = if voxel.is_falling_water() { 0 } else { level };
+            Some(level + 1)
+        })
+        .filter(|&level| level <= Voxel::MAX_WATER_LEVEL)
+        .min()
+        .map_or(Voxel::AIR, |level| Voxel::water(level, false))
+}
+
+/// Water spreads sideways only once it cannot flow further down: it rests on
+/// a solid block or on a still source.
+fn spreads_sideways(world: &VoxelWorld, pos: IVec3) -> bool {
+    let below = world.get(pos - IVec3::Y);
+    !below.is_empty() && (!below.is_water() || below == Voxel::WATER)
+}
@@ -0,0 +1,368 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+/// Binding file read when `--bindings` is not given, if it exists.
+pub const DEFAULT_BINDINGS_PATH: &str = "bindings.cfg";
+
+/// Something the player can do, independent of the keys bound to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    MoveUp,
+    MoveDown,
+    Jump,
+    Crouch,
+    ToggleCollision,
+    ToggleWalk,
+    CycleCamera,
+    VehicleForward,
+    VehicleBack,
+    VehicleLevel,
+    PlaceBlock,
+    Detonate,
+    CycleWeather,
+    Screenshot,
+    ToggleMap,
+    ExportMap,
+    MapPanLeft,
+    MapPanRight,
+    MapPanUp,
+    MapPanDown,
+    MapZoomIn,
+    MapZoomOut,
+    DebugOverlay,
+    ToggleConsole,
+    Quit,
+}
+
+/// Every action with its name in binding files and its default keys.
+const ACTIONS: [(Action, &str, &[KeyCode]); 31] = [
+    (Action::MoveForward, "move_forward", &[KeyCode::KeyW]),
+    (Action::MoveBack, "move_back", &[KeyCode::KeyS]),
+    (Action::StrafeLeft, "strafe_left", &[KeyCode::KeyA]),
+    (Action::StrafeRight, "strafe_right", &[KeyCode::KeyD]),
+    (Action::TurnLeft, "turn_left", &[KeyCode::KeyQ]),
+    (Action::TurnRight, "turn_right", &[KeyCode::KeyE]),
+    (Action::MoveUp, "move_up", &[KeyCode::KeyX]),
+    (Action::MoveDown, "move_down", &[KeyCode::KeyZ]),
+    (Action::Jump, "jump", &[KeyCode::Space]),
+    (Action::Crouch, "crouch", &[KeyCode::ShiftLeft]),
+    (
+        Action::ToggleCollision,
+        "toggle_collision",
+        &[KeyCode::KeyC],
+    ),
+    (Action::ToggleWalk, "toggle_walk", &[KeyCode::KeyV]),
+    (Action::CycleCamera, "cycle_camera", &[KeyCode::Tab]),
+    (Action::VehicleForward, "vehicle_forward", &[KeyCode::KeyK]),
+    (Action::VehicleBack, "vehicle_back", &[KeyCode::KeyJ]),
+    (Action::VehicleLevel, "vehicle_level", &[KeyCode::Period]),
+    (Action::PlaceBlock, "place_block", &[KeyCode::KeyL]),
+    (Action::Detonate, "detonate", &[KeyCode::KeyB]),
+    (Action::CycleWeather, "cycle_weather", &[KeyCode::KeyO]),
+    (Action::Screenshot, "screenshot", &[KeyCode::KeyP]),
+    (Action::ToggleMap, "toggle_map", &[KeyCode::KeyM]),
+    (Action::ExportMap, "export_map", &[KeyCode::KeyN]),
+    (Action::MapPanLeft, "map_pan_left", &[KeyCode::ArrowLeft]),
+    (Action::MapPanRight, "map_pan_right", &[KeyCode::ArrowRight]),
+    (Action::MapPanUp, "map_pan_up", &[KeyCode::ArrowUp]),
+    (Action::MapPanDown, "map_pan_down", &[KeyCode::ArrowDown]),
+    (Action::MapZoomIn, "map_zoom_in", &[KeyCode::Equal]),
+    (Action::MapZoomOut, "map_zoom_out", &[KeyCode::Minus]),
+    (Action::DebugOverlay, "debug_overlay", &[KeyCode::F3]),
+    (
+        Action::ToggleConsole,
+        "toggle_console",
+        &[KeyCode::Backquote],
+    ),
+    (Action::Quit, "quit", &[KeyCode::Backspace]),
+];
+
+/// Key names accepted in binding files.
+const KEY_NAMES: [(&str, KeyCode); 91] = [
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("F1", KeyCode::F1),
+    ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3),
+    ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5),
+    ("F6", KeyCode::F6),
+    ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9),
+    ("F10", KeyCode::F10),
+    ("F11", KeyCode::F11),
+    ("F12", KeyCode::F12),
+    ("Space", KeyCode::Space),
+    ("Tab", KeyCode::Tab),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("ArrowUp", KeyCode::ArrowUp),
+    ("ArrowDown", KeyCode::ArrowDown),
+    ("ArrowLeft", KeyCode::ArrowLeft),
+    ("ArrowRight", KeyCode::ArrowRight),
+    ("ShiftLeft", KeyCode::ShiftLeft),
+    ("ShiftRight", KeyCode::ShiftRight),
+    ("ControlLeft", KeyCode::ControlLeft),
+    ("ControlRight", KeyCode::ControlRight),
+    ("AltLeft", KeyCode::AltLeft),
+    ("AltRight", KeyCode::AltRight),
+    ("Backquote", KeyCode::Backquote),
+    ("Minus", KeyCode::Minus),
+    ("Equal", KeyCode::Equal),
+    ("BracketLeft", KeyCode::BracketLeft),
+    ("BracketRight", KeyCode::BracketRight),
+    ("Backslash", KeyCode::Backslash),
+    ("Semicolon", KeyCode::Semicolon),
+    ("Quote", KeyCode::Quote),
+    ("Comma", KeyCode::Comma),
+    ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash),
+    ("CapsLock", KeyCode::CapsLock),
+    ("Numpad0", KeyCode::Numpad0),
+    ("Numpad1", KeyCode::Numpad1),
+    ("Numpad2", KeyCode::Numpad2),
+    ("Numpad3", KeyCode::Numpad3),
+    ("Numpad4", KeyCode::Numpad4),
+    ("Numpad5", KeyCode::Numpad5),
+    ("Numpad6", KeyCode::Numpad6),
+    ("Numpad7", KeyCode::Numpad7),
+    ("Numpad8", KeyCode::Numpad8),
+    ("Numpad9", KeyCode::Numpad9),
+];
+
+impl Action {
+    pub fn all() -> impl Iterator<Item = Action> {
+        ACTIONS.iter().map(|&(action, _, _)| action)
+    }
+
+    /// Name used in binding files, like `move_forward`.
+    pub fn name(self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|&&(action, _, _)| action == self)
+            .map_or("", |&(_, name, _)| name)
+    }
+
+    pub fn parse(name: &str) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .find(|&&(_, candidate, _)| candidate == name)
+            .map(|&(action, _, _)| action)
+    }
+
+    pub fn default_keys(self) -> &'static [KeyCode] {
+        ACTIONS
+            .iter()
+            .find(|&&(action, _, _)| action == self)
+            .map_or(&[], |&(_, _, keys)| keys)
+    }
+}
+
+/// Name of `key` in binding files, like `W`, `F3` or `ArrowUp`.
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|&&(_, candidate)| candidate == key)
+        .map(|&(name, _)| name)
+}
+
+/// Reads a key name; the `Key` and `Digit` prefixes of the key codes are
+/// accepted too, so `KeyW` and `W` are the same key.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let short = name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .filter(|rest| rest.len() == 1)
+        .unwrap_or(name);
+    KEY_NAMES
+        .iter()
+        .find(|&&(candidate, _)| candidate.eq_ignore_ascii_case(short))
+        .map(|&(_, key)| key)
+}
+
+/// Keys bound to each action. Any of an action's keys triggers it.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::all()
+                .map(|action| (action, action.default_keys().to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl InputMap {
+    /// Loads bindings from `--bindings FILE`, or from
+    /// [`DEFAULT_BINDINGS_PATH`] when that exists, or uses the defaults.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut path = None;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--bindings" {
+                path = Some(args.next().ok_or("--bindings expects a file")?);
+            }
+        }
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_BINDINGS_PATH).exists() => DEFAULT_BINDINGS_PATH.into(),
+            None => return Ok(Self::default()),
+        };
+        let source = std::fs::read_to_string(&path).map_err(|err| format!("{path}: {err}"))?;
+        Self::parse(&source).map_err(|err| format!("{path}: {err}"))
+    }
+
+    /// Reads a binding file: one `action = key, key` per line, with `#`
+    /// comments. Listed actions replace their default keys, and an empty
+    /// list unbinds the action. A key bound to two actions is an error.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut map = Self::default();
+        for (index, raw) in source.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, keys) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {line_no}: expected action = keys"))?;
+            let action = Action::parse(name.trim())
+                .ok_or_else(|| format!("line {line_no}: unknown action {:?}", name.trim()))?;
+            let keys = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(|key| {
+                    parse_key(key).ok_or_else(|| format!("line {line_no}: unknown key {key:?}"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            map.bind(action, keys);
+        }
+        match map.conflicts().first() {
+            None => Ok(map),
+            Some((key, actions)) => {
+                let names: Vec<&str> = actions.iter().map(|action| action.name()).collect();
+                Err(format!(
+                    "{} is bound to {}",
+                    key_name(*key).unwrap_or("a key"),
+                    names.join(" and ")
+                ))
+            }
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces the keys of `action`, dropping repeats.
+    pub fn bind(&mut self, action: Action, mut keys: Vec<KeyCode>) {
+        let mut seen = Vec::new();
+        keys.retain(|key| {
+            let first = !seen.contains(key);
+            seen.push(*key);
+            first
+        });
+        self.bindings.insert(action, keys);
+    }
+
+    /// Keys bound to more than one action, with those actions.
+    pub fn conflicts(&self) -> Vec<(KeyCode, Vec<Action>)> {
+        let mut users: Vec<(KeyCode, Vec<Action>)> = Vec::new();
+        for (&action, keys) in &self.bindings {
+            for &key in keys {
+                match users.iter_mut().find(|(candidate, _)| *candidate == key) {
+                    Some((_, actions)) => actions.push(action),
+                    None => users.push((key, vec![action])),
+                }
+            }
+        }
+        users.retain(|(_, actions)| actions.len() > 1);
+        users
+    }
+
+    /// The bindings as a binding file would list them.
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<&str> = self
+            .keys(action)
+            .iter()
+            .filter_map(|&key| key_name(key))
+            .collect();
+        format!("{} = {}", action.name(), keys.join(", "))
+    }
+}
+
+/// Keyboard state read through the [`InputMap`].
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    map: Res<'w, InputMap>,
+}
+
+impl Actions<'_> {
+    /// Whether any key of `action` is held.
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keys.any_pressed(self.map.keys(action).iter().copied())
+    }
+
+    /// Whether any key of `action` went down this frame.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.keys
+            .any_just_pressed(self.map.keys(action).iter().copied())
+    }
+}
@@ -1,21 +1,89 @@
+pub mod character;
+pub mod collision;
+pub mod console;
+pub mod daynight;
+pub mod debug;
+pub mod explosion;
+pub mod falling;
+pub mod fluid;
+pub mod input;
+pub mod lighting;
+pub mod lod;
 pub mod meshing;
+pub mod minimap;
+pub mod pathfinding;
+pub mod structure;
+pub mod visibility;
 pub mod voxel;
+pub mod weather;
 pub mod world;
+pub mod worldgen;
 
 use bevy::app::AppExit;
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::system::SystemState;
+use bevy::math::Affine3A;
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::primitives::Frustum;
 use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
 use bevy::render::view::screenshot::ScreenshotManager;
 
+use std::collections::{HashMap, HashSet};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::meshing::{mesh_chunk, SurfaceMesh};
-use crate::voxel::Voxel;
-use crate::world::{Chunk, CHUNK_SIZE};
+use crate::character::{step_walker, WalkInput, WalkerSettings, WalkerState};
+use crate::collision::{raycast, sweep_aabb, Aabb};
+use crate::console::{ConsoleAppExt, ConsoleCommand, ConsolePlugin, ConsoleScript};
+use crate::daynight::{parse_clock, DayNightPlugin, NightLight, TimeOfDay};
+use crate::debug::DebugInfo;
+use crate::explosion::{blast_cells, spawn_debris, step_debris, Debris, Explosion};
+use crate::falling::FallingSim;
+use crate::fluid::FluidSim;
+use crate::input::{Action, Actions, InputMap};
+use crate::lighting::LightField;
+use crate::lod::{chunk_distance, LodLevel, LodSettings};
+use crate::meshing::{mesh_lod_layers, ChunkNeighborhood, MeshStyle, SurfaceMesh};
+use crate::minimap::{draw_marker, rgba_image, MapView, WorldMap};
+use crate::structure::{Marker, Placement, StructureTemplate};
+use crate::visibility::{visible_chunks, CullingStats, FaceConnections};
+use crate::voxel::{BlockMaterial, Voxel};
+use crate::weather::{
+    is_sheltered, next_random, snow_layer_cell, Weather, WeatherKind, WeatherPlugin,
+};
+use crate::world::{chunk_coord, chunks_near, Chunk, HeightMap, VoxelWorld, CHUNK_SIZE};
+use crate::worldgen::{base_height, TerrainGenerator, VILLAGE_REGION};
 
 const WORLD_DIM: u32 = 9; // 9x9x9 chunks
 const PROJECT_NAME: &str = "dexter-platz";
+const FLY_RIG_HALF_EXTENTS: Vec3 = Vec3::new(0.5, 0.8, 0.5);
+/// Rain and snow particles kept around the active camera at full intensity.
+const PRECIPITATION_POOL: usize = 800;
+const PRECIPITATION_RADIUS: f32 = 24.0;
+/// Fixed-schedule rate; water advances once every `FLUID_TICK_EVERY` ticks.
+const FIXED_TICK_HZ: f64 = 20.0;
+const FLUID_TICK_EVERY: u32 = 4;
+/// Debris pieces thrown by a single explosion.
+const MAX_DEBRIS_PER_BLAST: usize = 48;
+const SPAWN_CLEARING_RADIUS: f32 = 22.0;
+/// Built-in structure templates, stamped near the world centre.
+const RUIN_TEMPLATE: &str = include_str!("../assets/structures/ruin.txt");
+const FOUNTAIN_TEMPLATE: &str = include_str!("../assets/structures/fountain.txt");
+/// Minimap and full map image sizes in pixels.
+const MINIMAP_SIZE: u32 = 160;
+const FULL_MAP_SIZE: u32 = 512;
+/// Map tiles redrawn per frame, so big edits and new chunks fill in
+/// gradually.
+const MAP_TILES_PER_FRAME: usize = 16;
+/// `(x, z)` offsets of the ruin and fountain anchors from the world centre.
+const RUIN_OFFSET: IVec2 = IVec2::new(0, 18);
+const FOUNTAIN_OFFSET: IVec2 = IVec2::new(-14, 21);
+/// Largest box the `fill` command writes at once.
+const MAX_FILL_VOLUME: i32 = 65_536;
+/// Where `save` and `load` keep the world without a path argument.
+const DEFAULT_SAVE_PATH: &str = "saves/world.bin";
 
 #[derive(Default, Resource)]
 struct HudState {
@@ -24,34 +92,355 @@ struct HudState {
     dirty: bool,
 }
 
+/// Whether the F3 debug overlay is shown, and its text for this frame.
+#[derive(Default, Resource)]
+struct DebugOverlay {
+    visible: bool,
+    lines: Vec<String>,
+}
+
+/// Reach of the debug overlay's targeted voxel.
+const DEBUG_TARGET_REACH: f32 = 64.0;
+
 #[derive(Default, Resource)]
 struct CameraRegistry {
     cameras: Vec<Entity>,
+    /// Display names, parallel to `cameras`.
+    names: Vec<&'static str>,
     active: usize,
 }
 
+impl CameraRegistry {
+    fn register(&mut self, camera: Entity, name: &'static str) {
+        self.cameras.push(camera);
+        self.names.push(name);
+    }
+
+    fn active_name(&self) -> &'static str {
+        self.names
+            .get(self.active.min(self.names.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or("none")
+    }
+
+    fn active_camera(&self) -> Option<Entity> {
+        self.cameras
+            .get(self.active.min(self.cameras.len().saturating_sub(1)))
+            .copied()
+    }
+
+    fn index_of(&self, camera: Entity) -> Option<usize> {
+        self.cameras.iter().position(|&entity| entity == camera)
+    }
+
+    /// Deactivates the current camera and activates the one at `index`.
+    fn activate(&mut self, index: usize, cameras: &mut Query<&mut Camera>) {
+        if index >= self.cameras.len() {
+            return;
+        }
+        if let Some(current) = self.active_camera() {
+            if let Ok(mut camera) = cameras.get_mut(current) {
+                camera.is_active = false;
+            }
+        }
+        self.active = index;
+        if let Ok(mut camera) = cameras.get_mut(self.cameras[index]) {
+            camera.is_active = true;
+        }
+    }
+
+    /// Activates the camera after the current one, wrapping around.
+    fn activate_next(&mut self, cameras: &mut Query<&mut Camera>) {
+        let count = self.cameras.len();
+        if count < 2 {
+            return;
+        }
+        let current = self.active.min(count - 1);
+        self.activate((current + 1) % count, cameras);
+    }
+}
+
 #[derive(Resource, Clone, Copy, Default)]
 struct WorldCenter(Vec3);
 
+/// Block the place key puts down; the `give` command changes it.
+#[derive(Resource)]
+struct HeldBlock(Voxel);
+
+impl Default for HeldBlock {
+    fn default() -> Self {
+        Self(Voxel::LAMP)
+    }
+}
+
 #[derive(Resource, Default)]
-struct VehicleAutoMotion {
-    direction: f32,
+struct VoxelTerrain {
+    world: VoxelWorld,
+    light: LightField,
+    heights: HeightMap,
+    fluids: FluidSim,
+    falling: FallingSim,
+    /// Chunks whose meshes are stale.
+    dirty: HashSet<IVec3>,
+    /// Chunk columns whose map tiles are stale.
+    unmapped: HashSet<IVec2>,
+}
+
+impl VoxelTerrain {
+    /// Lights `world` and builds its heightmap. Every chunk starts dirty and
+    /// unmapped, so the first frames mesh and map the whole world.
+    fn new(world: VoxelWorld) -> Self {
+        let max_chunk = IVec3::splat(WORLD_DIM as i32 - 1);
+        let dirty: HashSet<IVec3> = world.chunks().map(|(coord, _)| coord).collect();
+        Self {
+            light: LightField::compute(&world, IVec3::ZERO, max_chunk),
+            heights: HeightMap::build(&world),
+            falling: FallingSim::new(),
+            fluids: FluidSim::with_bounds(
+                IVec3::ZERO,
+                max_chunk * CHUNK_SIZE as i32 + CHUNK_SIZE as i32,
+            ),
+            unmapped: dirty
+                .iter()
+                .map(|coord| IVec2::new(coord.x, coord.z))
+                .collect(),
+            dirty,
+            world,
+        }
+    }
+
+    /// Writes a voxel, re-lights its surroundings, wakes nearby water and
+    /// falling blocks, and queues affected chunks for remeshing.
+    fn set_voxel(&mut self, pos: IVec3, voxel: Voxel) -> Voxel {
+        let previous = self.world.set(pos, voxel);
+        if previous != voxel {
+            let relit = self.light.update(&self.world, pos, previous);
+            self.heights.update(&self.world, pos);
+            self.fluids.wake(pos);
+            self.falling.notify(pos);
+            self.dirty.extend(relit);
+            self.dirty.extend(chunks_near(pos));
+            // Map pixels are shaded by the column to their north-west.
+            for cell in [pos, pos + IVec3::new(1, 0, 1)] {
+                let coord = chunk_coord(cell);
+                self.unmapped.insert(IVec2::new(coord.x, coord.z));
+            }
+        }
+        previous
+    }
+
+    /// Sets every voxel in the inclusive box `min..=max` to `voxel`, then
+    /// re-lights, refreshes heights, wakes water and falling blocks, and
+    /// queues chunks and map tiles once for the whole box. Returns the number
+    /// of voxels that changed.
+    fn fill_box(&mut self, min: IVec3, max: IVec3, voxel: Voxel) -> usize {
+        let mut changes = Vec::new();
+        for z in min.z..=max.z {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let pos = IVec3::new(x, y, z);
+                    let previous = self.world.set(pos, voxel);
+                    if previous != voxel {
+                        changes.push((pos, previous));
+                    }
+                }
+            }
+        }
+        if changes.is_empty() {
+            return 0;
+        }
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                // Falling blocks may lose support along the bottom of the box
+                // or above its top.
+                self.falling.notify(IVec3::new(x, min.y, z));
+                self.falling.notify(IVec3::new(x, max.y, z));
+            }
+        }
+        self.fluids.wake_box(min, max);
+        self.finish_edit(&changes, min, max);
+        changes.len()
+    }
+
+    /// Relights `changes` (cells with their previous voxels) in one pass,
+    /// refreshes the height columns of the inclusive box `min..=max` that
+    /// holds them, and queues the chunks and map tiles around it.
+    fn finish_edit(&mut self, changes: &[(IVec3, Voxel)], min: IVec3, max: IVec3) {
+        let relit = self.light.update_many(&self.world, changes);
+        self.dirty.extend(relit);
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                self.heights.update_span(&self.world, x, z, min.y, max.y);
+            }
+        }
+        let (low, high) = (chunk_coord(min - IVec3::ONE), chunk_coord(max + IVec3::ONE));
+        for z in low.z..=high.z {
+            for y in low.y..=high.y {
+                for x in low.x..=high.x {
+                    self.dirty.insert(IVec3::new(x, y, z));
+                }
+            }
+        }
+        // Map pixels are shaded by the column to their north-west.
+        let first = chunk_coord(min);
+        for z in first.z..=high.z {
+            for x in first.x..=high.x {
+                self.unmapped.insert(IVec2::new(x, z));
+            }
+        }
+    }
+
+    /// Removes every voxel the explosion overpowers and returns them. The
+    /// crater is relit, and its chunks queued for remeshing, in one batch.
+    fn explode(&mut self, explosion: &Explosion) -> Vec<(IVec3, Voxel)> {
+        let destroyed = blast_cells(&self.world, explosion);
+        let Some(&(first, _)) = destroyed.first() else {
+            return destroyed;
+        };
+        let (mut min, mut max) = (first, first);
+        for &(cell, _) in &destroyed {
+            self.world.set(cell, Voxel::AIR);
+            self.fluids.wake(cell);
+            self.falling.notify(cell);
+            min = min.min(cell);
+            max = max.max(cell);
+        }
+        self.finish_edit(&destroyed, min, max);
+        destroyed
+    }
+}
+
+#[derive(Resource)]
+struct TerrainMaterials {
+    ground: Handle<StandardMaterial>,
+    dirt: Handle<StandardMaterial>,
+    stone: Handle<StandardMaterial>,
+    snow: Handle<StandardMaterial>,
+    sand: Handle<StandardMaterial>,
+    gravel: Handle<StandardMaterial>,
+    water: Handle<StandardMaterial>,
+    wood: Handle<StandardMaterial>,
+    leaves: Handle<StandardMaterial>,
+    plant: Handle<StandardMaterial>,
+    coal: Handle<StandardMaterial>,
+    iron: Handle<StandardMaterial>,
+    crystal: Handle<StandardMaterial>,
+    brick: Handle<StandardMaterial>,
+    glass: Handle<StandardMaterial>,
+}
+
+impl TerrainMaterials {
+    fn for_layer(&self, coord: IVec3, material: BlockMaterial) -> Handle<StandardMaterial> {
+        match material {
+            // Air is never meshed, so it never asks for a material.
+            BlockMaterial::Air | BlockMaterial::Terrain => self.for_chunk(coord),
+            BlockMaterial::Snow => self.snow.clone(),
+            BlockMaterial::Sand => self.sand.clone(),
+            BlockMaterial::Gravel => self.gravel.clone(),
+            BlockMaterial::Water => self.water.clone(),
+            BlockMaterial::Wood => self.wood.clone(),
+            BlockMaterial::Leaves => self.leaves.clone(),
+            BlockMaterial::Plant => self.plant.clone(),
+            BlockMaterial::Coal => self.coal.clone(),
+            BlockMaterial::Iron => self.iron.clone(),
+            BlockMaterial::Crystal => self.crystal.clone(),
+            BlockMaterial::Brick => self.brick.clone(),
+            BlockMaterial::Glass => self.glass.clone(),
+        }
+    }
+
+    /// Picks a chunk's material from the terrain height at its centre.
+    fn for_chunk(&self, coord: IVec3) -> Handle<StandardMaterial> {
+        let sample_x = (coord.x as f32 + 0.5) * CHUNK_SIZE as f32;
+        let sample_z = (coord.z as f32 + 0.5) * CHUNK_SIZE as f32;
+        let sample_height = base_height(sample_x, sample_z);
+        if sample_height > 28.0 {
+            self.stone.clone()
+        } else if sample_height > 18.0 {
+            self.dirt.clone()
+        } else {
+            self.ground.clone()
+        }
+    }
+}
+
+/// Rendered chunk entities by chunk coordinate, one per block material.
+#[derive(Resource, Default)]
+struct ChunkEntities(HashMap<(IVec3, BlockMaterial), Entity>);
+
+/// Level of detail each chunk is meshed at; chunks not listed are at full
+/// resolution.
+#[derive(Resource, Default)]
+struct ChunkLods(HashMap<IVec3, LodLevel>);
+
+impl ChunkLods {
+    fn get(&self, coord: IVec3) -> LodLevel {
+        self.0.get(&coord).copied().unwrap_or_default()
+    }
+
+    /// Whether any side neighbour is meshed at another level, so the chunk
+    /// needs skirts over the cracks between them.
+    fn needs_skirts(&self, coord: IVec3) -> bool {
+        let lod = self.get(coord);
+        [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z]
+            .iter()
+            .any(|&side| {
+                self.0
+                    .get(&(coord + side))
+                    .is_some_and(|&other| other != lod)
+            })
+    }
+}
+
+/// Chunk visibility: which sides of each chunk see each other and how many
+/// chunks the last frame drew.
+#[derive(Resource, Default)]
+struct ChunkCulling {
+    /// Side connections of every chunk in the world; chunks not listed are
+    /// all air.
+    connections: HashMap<IVec3, FaceConnections>,
+    stats: CullingStats,
+}
+
+/// The top-down world map and the minimap and full map views of it.
+#[derive(Resource)]
+struct MapDisplay {
+    map: WorldMap,
+    minimap: Handle<Image>,
+    full: Handle<Image>,
+    /// Full screen map node, shown while the map is open.
+    full_node: Entity,
+    open: bool,
+    view: MapView,
+}
+
+#[derive(Resource)]
+struct PrecipitationAssets {
+    rain_mesh: Handle<Mesh>,
+    snow_mesh: Handle<Mesh>,
+    rain_material: Handle<StandardMaterial>,
+    snow_material: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Default)]
+struct CollisionSettings {
+    enabled: bool,
 }
 
-fn height_at(world_x: f32, world_z: f32) -> f32 {
-    let coarse = (world_x * 0.05).sin() + (world_z * 0.05).cos();
-    let medium = ((world_x + world_z) * 0.02).sin();
-    let detail = (world_x * 0.14).cos() * (world_z * 0.14).sin();
-    let height = coarse * 4.5 + medium * 7.5 + detail * 2.0 + 14.0;
-    height.max(0.0)
+#[derive(Resource, Default)]
+struct VehicleAutoMotion {
+    direction: f32,
 }
 
 #[derive(Component)]
 struct FlyCamera;
 
+/// First-person walking rig. The rig transform sits at the walker's feet.
 #[derive(Component)]
-struct AnimatedLight {
-    speed: f32,
+struct Walker {
+    state: WalkerState,
+    settings: WalkerSettings,
+    camera: Entity,
 }
 
 #[derive(Component)]
@@ -65,6 +454,24 @@ struct RotatingSpotlight {
     speed: f32,
 }
 
+/// Prop that blows up when detonated (B).
+#[derive(Component)]
+struct Explosive {
+    radius: f32,
+    power: f32,
+}
+
+#[derive(Component)]
+struct DebrisPiece(Debris);
+
+/// One pooled rain drop or snow flake.
+#[derive(Component, Default)]
+struct Precipitation {
+    slot: usize,
+    falling: bool,
+    snow: bool,
+}
+
 #[derive(Component)]
 struct FountainSplash {
     base_height: f32,
@@ -73,51 +480,114 @@ struct FountainSplash {
 }
 
 pub fn run() {
-    App::new()
-        .add_plugins(DefaultPlugins.build())
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let parsed = (|| {
+        Ok::<_, String>((
+            TimeOfDay::from_args(args.clone())?,
+            Weather::from_args(args.clone())?,
+            MeshStyle::from_args(args.clone())?,
+            ConsoleScript::from_args(args.clone())?,
+            InputMap::from_args(args.clone())?,
+            TerrainGenerator::from_args(args)?,
+        ))
+    })();
+    let (time_of_day, weather, mesh_style, script, input_map, mut generator) = parsed
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(2);
+        });
+    // Keep the spawn point, the ruin and the fountain clear of trees.
+    let half_world = (WORLD_DIM * CHUNK_SIZE as u32) as f32 / 2.0;
+    generator.clearings.push((
+        Vec2::new(half_world, half_world + 12.0),
+        SPAWN_CLEARING_RADIUS,
+    ));
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.build())
+        .insert_resource(Time::<Fixed>::from_hz(FIXED_TICK_HZ))
+        .insert_resource(generator)
+        .insert_resource(mesh_style)
+        .insert_resource(input_map)
+        .add_plugins(DayNightPlugin {
+            initial: time_of_day,
+        })
+        .add_plugins(WeatherPlugin { initial: weather })
+        .add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(ConsolePlugin { script })
         .init_resource::<CameraRegistry>()
         .init_resource::<HudState>()
+        .init_resource::<DebugOverlay>()
         .init_resource::<WorldCenter>()
+        .init_resource::<HeldBlock>()
         .init_resource::<VehicleAutoMotion>()
+        .init_resource::<VoxelTerrain>()
+        .init_resource::<ChunkEntities>()
+        .init_resource::<ChunkLods>()
+        .init_resource::<ChunkCulling>()
+        .init_resource::<LodSettings>()
+        .init_resource::<CollisionSettings>()
         .add_systems(Startup, setup)
+        .add_systems(FixedUpdate, (tick_fluids, tick_falling_blocks))
         .add_systems(
             Update,
             (
-                camera_controls,
+                toggle_collision,
+                toggle_walk_mode,
+                camera_controls
+                    .after(toggle_collision)
+                    .after(toggle_walk_mode),
+                update_walker_eye.after(camera_controls),
                 vehicle_controls,
                 screenshot_capture,
-                animate_light,
-                cycle_cameras,
+                cycle_cameras.after(toggle_walk_mode),
                 animate_fountain,
                 rotate_spotlights,
-                update_hud,
+                place_held_block,
+                cycle_weather,
+                update_precipitation.after(camera_controls),
+                accumulate_snow,
+                sync_falling_blocks,
+                detonate_explosives,
+                update_debris,
+                update_chunk_lods.after(update_walker_eye),
+                remesh_dirty_chunks
+                    .after(update_chunk_lods)
+                    .after(place_held_block)
+                    .after(accumulate_snow)
+                    .after(detonate_explosives),
+                cull_chunks.after(remesh_dirty_chunks),
             ),
         )
-        .run();
+        .add_systems(
+            Update,
+            (
+                toggle_debug_overlay,
+                update_debug_overlay
+                    .after(toggle_debug_overlay)
+                    .after(cull_chunks),
+                update_hud.after(update_debug_overlay),
+                update_world_map,
+                map_controls,
+                draw_maps.after(update_world_map).after(map_controls),
+            ),
+        );
+    register_commands(&mut app);
+    app.run();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
     asset_server: Res<AssetServer>,
     mut camera_registry: ResMut<CameraRegistry>,
     mut hud_state: ResMut<HudState>,
     mut auto_motion: ResMut<VehicleAutoMotion>,
+    generator: Res<TerrainGenerator>,
 ) {
-    // Lighting
-    commands
-        .spawn(DirectionalLightBundle {
-            transform: Transform::from_xyz(50.0, 100.0, 50.0).looking_at(Vec3::ZERO, Vec3::Y),
-            directional_light: DirectionalLight {
-                shadows_enabled: true,
-                illuminance: 30_000.0,
-                ..default()
-            },
-            ..default()
-        })
-        .insert(AnimatedLight { speed: 0.25 });
-
     // Camera
     let world_size = Vec3::new(
         (WORLD_DIM * CHUNK_SIZE as u32) as f32,
@@ -162,7 +632,7 @@ fn setup(
     });
     if let Some(entity) = fly_camera_entity {
         camera_registry.active = camera_registry.cameras.len();
-        camera_registry.cameras.push(entity);
+        camera_registry.register(entity, "Fly");
     }
 
     let ground_texture = asset_server.load("textures/ground.png");
@@ -191,46 +661,236 @@ fn setup(
         ..default()
     });
 
+    let snow_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.95, 0.97, 1.0),
+        perceptual_roughness: 0.9,
+        reflectance: 0.1,
+        ..default()
+    });
+
+    let water_material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.2, 0.45, 0.7, 0.7),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.1,
+        reflectance: 0.3,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+
+    let sand_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.86, 0.78, 0.55),
+        base_color_texture: Some(ground_texture.clone()),
+        perceptual_roughness: 0.95,
+        reflectance: 0.02,
+        ..default()
+    });
+
+    let gravel_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.52, 0.5, 0.48),
+        base_color_texture: Some(stone_texture.clone()),
+        perceptual_roughness: 0.95,
+        reflectance: 0.03,
+        ..default()
+    });
+
+    let wood_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.42, 0.3, 0.18),
+        base_color_texture: Some(dirt_texture.clone()),
+        perceptual_roughness: 0.9,
+        reflectance: 0.02,
+        ..default()
+    });
+
+    // Cutout materials: faces are drawn from both sides and pixels below
+    // the mask threshold are dropped, so the leaves behind show through.
+    let leaves_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.25, 0.5, 0.2),
+        base_color_texture: Some(images.add(cutout_texture(0.7, 0x1eaf))),
+        alpha_mode: AlphaMode::Mask(0.5),
+        perceptual_roughness: 0.8,
+        reflectance: 0.03,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+
+    let plant_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.45, 0.7, 0.3),
+        base_color_texture: Some(images.add(cutout_texture(0.4, 0x9a55))),
+        alpha_mode: AlphaMode::Mask(0.5),
+        perceptual_roughness: 0.8,
+        reflectance: 0.02,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+
+    let glass_material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.8, 0.92, 0.95, 0.25),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.05,
+        reflectance: 0.5,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    });
+
+    let coal_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.2, 0.2, 0.22),
+        base_color_texture: Some(stone_texture.clone()),
+        perceptual_roughness: 0.8,
+        reflectance: 0.05,
+        ..default()
+    });
+
+    let iron_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.72, 0.55, 0.45),
+        base_color_texture: Some(stone_texture.clone()),
+        perceptual_roughness: 0.5,
+        metallic: 0.4,
+        ..default()
+    });
+
+    let crystal_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.5, 0.85, 1.0),
+        emissive: LinearRgba::rgb(0.6, 1.6, 2.4),
+        perceptual_roughness: 0.2,
+        reflectance: 0.5,
+        ..default()
+    });
+
+    let brick_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.55, 0.35, 0.3),
+        base_color_texture: Some(stone_texture.clone()),
+        perceptual_roughness: 0.9,
+        reflectance: 0.03,
+        ..default()
+    });
+
+    commands.insert_resource(TerrainMaterials {
+        ground: ground_material,
+        dirt: dirt_material,
+        stone: stone_material,
+        snow: snow_material,
+        sand: sand_material,
+        gravel: gravel_material,
+        water: water_material,
+        wood: wood_material,
+        leaves: leaves_material,
+        plant: plant_material,
+        coal: coal_material,
+        iron: iron_material,
+        crystal: crystal_material,
+        brick: brick_material,
+        glass: glass_material,
+    });
+
+    // Precipitation pool; particles stay hidden until it rains or snows.
+    let precipitation = PrecipitationAssets {
+        rain_mesh: meshes.add(Cuboid::new(0.03, 0.5, 0.03)),
+        snow_mesh: meshes.add(Cuboid::new(0.1, 0.1, 0.1)),
+        rain_material: materials.add(StandardMaterial {
+            base_color: Color::srgba(0.7, 0.8, 1.0, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        snow_material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            ..default()
+        }),
+    };
+    for slot in 0..PRECIPITATION_POOL {
+        commands.spawn((
+            PbrBundle {
+                mesh: precipitation.rain_mesh.clone(),
+                material: precipitation.rain_material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Precipitation { slot, ..default() },
+        ));
+    }
+    commands.insert_resource(precipitation);
+
+    let world_regions = (WORLD_DIM * CHUNK_SIZE as u32).div_ceil(VILLAGE_REGION as u32) as i32;
+    let villages: Vec<_> = (0..world_regions)
+        .flat_map(|rz| (0..world_regions).map(move |rx| IVec2::new(rx, rz)))
+        .filter_map(|region| generator.village_in_region(region))
+        .collect();
+    let mut world = VoxelWorld::new();
     for cz in 0..WORLD_DIM {
-        for cy in 0..WORLD_DIM {
-            for cx in 0..WORLD_DIM {
+        for cx in 0..WORLD_DIM {
+            let decorations = generator.column_decorations(IVec2::new(cx as i32, cz as i32));
+            for cy in 0..WORLD_DIM {
+                let coord = IVec3::new(cx as i32, cy as i32, cz as i32);
                 let mut chunk = Chunk::new();
-                populate_chunk_heightfield(cx, cy, cz, &mut chunk);
-
-                let smesh = mesh_chunk(&chunk);
-                if smesh.indices.is_empty() {
-                    continue;
+                generator.populate_chunk(coord, &mut chunk);
+                generator.populate_underground(coord, &mut chunk);
+                generator.decorate_chunk(coord, &mut chunk, &decorations);
+                generator.build_villages(coord, &mut chunk, &villages);
+                chunk.compact();
+                if !chunk.is_empty() {
+                    world.insert_chunk(coord, chunk);
                 }
-
-                let bevy_mesh = surface_to_bevy_mesh(&smesh);
-                let mesh_handle = meshes.add(bevy_mesh);
-
-                let tx = Vec3::new(
-                    (cx * CHUNK_SIZE as u32) as f32,
-                    (cy * CHUNK_SIZE as u32) as f32,
-                    (cz * CHUNK_SIZE as u32) as f32,
-                );
-
-                let sample_x = tx.x + CHUNK_SIZE as f32 * 0.5;
-                let sample_z = tx.z + CHUNK_SIZE as f32 * 0.5;
-                let sample_height = height_at(sample_x, sample_z);
-                let material_handle = if sample_height > 28.0 {
-                    stone_material.clone()
-                } else if sample_height > 18.0 {
-                    dirt_material.clone()
-                } else {
-                    ground_material.clone()
-                };
-
-                commands.spawn(PbrBundle {
-                    mesh: mesh_handle,
-                    material: material_handle,
-                    transform: Transform::from_translation(tx),
-                    ..default()
-                });
             }
         }
     }
+    // The ruin and fountain are stamped before lighting and the heightmap are
+    // computed, so both see them as ordinary voxels.
+    let ruin = StructureTemplate::parse(RUIN_TEMPLATE).expect("built-in ruin template");
+    let fountain = StructureTemplate::parse(FOUNTAIN_TEMPLATE).expect("built-in fountain template");
+    let (cx, cz) = (center.x as i32, center.z as i32);
+    let mut markers = ruin.stamp(
+        &mut world,
+        &Placement::at(generator.surface_cell(cx + RUIN_OFFSET.x, cz + RUIN_OFFSET.y)),
+    );
+    markers.extend(fountain.stamp(
+        &mut world,
+        &Placement::at(generator.surface_cell(cx + FOUNTAIN_OFFSET.x, cz + FOUNTAIN_OFFSET.y)),
+    ));
+
+    // Walking rig, dropped onto the terrain near the fly camera's target.
+    let walker_settings = WalkerSettings::default();
+    let walker_start = Vec3::new(
+        center.x + 6.0,
+        generator
+            .surface_or_water(center.x + 6.0, center.z + 6.0)
+            .floor()
+            + 2.0,
+        center.z + 6.0,
+    );
+    let walker_state = WalkerState::new(walker_start);
+    let walker_camera = commands
+        .spawn(Camera3dBundle {
+            camera: Camera {
+                is_active: false,
+                ..default()
+            },
+            transform: Transform::from_translation(
+                Vec3::Y * walker_state.eye_height(&walker_settings),
+            ),
+            ..default()
+        })
+        .id();
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_translation(walker_start),
+                ..default()
+            },
+            Walker {
+                state: walker_state,
+                settings: walker_settings,
+                camera: walker_camera,
+            },
+        ))
+        .add_child(walker_camera);
+    camera_registry.register(walker_camera, "Walker");
+
+    let terrain = VoxelTerrain::new(world);
 
     // Ground vehicle rig
     let vehicle_mesh = meshes.add(Mesh::from(Cuboid::new(2.4, 1.2, 4.0)));
@@ -242,7 +902,7 @@ fn setup(
         ..default()
     });
     let vehicle_pos = Vec3::new(center.x + 40.0, 0.0, center.z + 20.0);
-    let vehicle_height = height_at(vehicle_pos.x, vehicle_pos.z);
+    let vehicle_height = generator.surface_or_water(vehicle_pos.x, vehicle_pos.z);
     let vehicle_translation = Vec3::new(vehicle_pos.x, vehicle_height + 1.2, vehicle_pos.z);
     let mut vehicle_rig = commands.spawn((
         SpatialBundle {
@@ -270,19 +930,26 @@ fn setup(
                 },
             ))
             .with_children(|pivot| {
-                pivot.spawn(SpotLightBundle {
-                    spot_light: SpotLight {
-                        color: Color::srgb(1.0, 0.95, 0.8),
-                        intensity: 6_000.0,
-                        outer_angle: 0.7,
-                        inner_angle: 0.4,
-                        shadows_enabled: true,
+                pivot.spawn((
+                    SpotLightBundle {
+                        spot_light: SpotLight {
+                            color: Color::srgb(1.0, 0.95, 0.8),
+                            intensity: 6_000.0,
+                            outer_angle: 0.7,
+                            inner_angle: 0.4,
+                            shadows_enabled: true,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(Vec3::new(0.0, 0.3, 1.8))
+                            .looking_at(Vec3::new(0.0, -0.2, 0.0), Vec3::Y),
                         ..default()
                     },
-                    transform: Transform::from_translation(Vec3::new(0.0, 0.3, 1.8))
-                        .looking_at(Vec3::new(0.0, -0.2, 0.0), Vec3::Y),
-                    ..default()
-                });
+                    // Headlights are barely noticeable by day and full strength at night.
+                    NightLight {
+                        day_intensity: 1_500.0,
+                        night_intensity: 6_000.0,
+                    },
+                ));
             });
         let camera = parent
             .spawn(Camera3dBundle {
@@ -297,12 +964,11 @@ fn setup(
         vehicle_camera_entity = Some(camera);
     });
     if let Some(entity) = vehicle_camera_entity {
-        camera_registry.cameras.push(entity);
+        camera_registry.register(entity, "Vehicle");
     }
 
-    let ruin_origin = build_ruin(&mut commands, &mut meshes, &mut materials, center);
-
-    build_fountain(&mut commands, &mut meshes, &mut materials, ruin_origin);
+    spawn_structure_markers(&mut commands, &mut meshes, &mut materials, &markers);
+    commands.insert_resource(terrain);
 
     commands.spawn(Camera2dBundle {
         camera: Camera {
@@ -352,13 +1018,56 @@ fn setup(
     hud_state.message = "Press P to capture screenshot".into();
     hud_state.dirty = true;
 
+    // Minimap in the top-right corner and the full map, hidden until opened.
+    let blank = |size: u32| rgba_image(vec![0; (size * size * 4) as usize], size, size);
+    let minimap = images.add(blank(MINIMAP_SIZE));
+    let full = images.add(blank(FULL_MAP_SIZE));
+    commands.spawn(ImageBundle {
+        image: UiImage::new(minimap.clone()),
+        style: Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            width: Val::Px(MINIMAP_SIZE as f32),
+            height: Val::Px(MINIMAP_SIZE as f32),
+            ..default()
+        },
+        ..default()
+    });
+    let full_node = commands
+        .spawn(ImageBundle {
+            image: UiImage::new(full.clone()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                align_self: AlignSelf::Center,
+                justify_self: JustifySelf::Center,
+                width: Val::VMin(90.0),
+                height: Val::VMin(90.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .id();
+    commands.insert_resource(MapDisplay {
+        map: WorldMap::new(),
+        minimap,
+        full,
+        full_node,
+        open: false,
+        view: MapView {
+            center: Vec2::new(center.x, center.z),
+            cells_per_pixel: 1.0,
+        },
+    });
+
     // Highlight the world center with a white voxel-sized cube.
     let cube_mesh = meshes.add(Mesh::from(Cuboid::default()));
     let cube_material = materials.add(StandardMaterial {
         base_color: Color::srgb(0.95, 0.95, 0.95),
         ..default()
     });
-    let ground_height = height_at(center.x, center.z);
+    let ground_height = generator.surface_or_water(center.x, center.z);
     let cube_translation = Vec3::new(center.x - 0.5, ground_height + 10.5, center.z - 0.5);
     commands.spawn(PbrBundle {
         mesh: cube_mesh,
@@ -368,26 +1077,31 @@ fn setup(
     });
 }
 
-fn populate_chunk_heightfield(cx: u32, cy: u32, cz: u32, chunk: &mut Chunk) {
-    let chunk_size = CHUNK_SIZE as u32;
-    let world_y_base = (cy * chunk_size) as i32;
-    let max_world_height = (WORLD_DIM * chunk_size - 1) as i32;
-
-    for z in 0..chunk_size {
-        let world_z = (cz * chunk_size + z) as f32;
-        for x in 0..chunk_size {
-            let world_x = (cx * chunk_size + x) as f32;
-            let target_height = height_at(world_x, world_z).floor() as i32;
-            let clamped_height = target_height.clamp(0, max_world_height);
-
-            for y in 0..chunk_size {
-                let world_y = world_y_base + y as i32;
-                if world_y <= clamped_height {
-                    chunk.set(x, y, z, Voxel(1));
-                }
-            }
-        }
+/// A 16×16 white texture with about `coverage` of its pixels solid and the
+/// rest fully transparent, for cutout materials.
+fn cutout_texture(coverage: f32, seed: u32) -> Image {
+    let mut state = seed.max(1);
+    let mut data = Vec::with_capacity(16 * 16 * 4);
+    for _ in 0..16 * 16 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let solid = (state % 1000) as f32 / 1000.0 < coverage;
+        data.extend_from_slice(&[255, 255, 255, if solid { 255 } else { 0 }]);
     }
+    let mut image = Image::new(
+        Extent3d {
+            width: 16,
+            height: 16,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    image
 }
 
 fn surface_to_bevy_mesh(s: &SurfaceMesh) -> Mesh {
@@ -398,35 +1112,570 @@ fn surface_to_bevy_mesh(s: &SurfaceMesh) -> Mesh {
     m.insert_attribute(Mesh::ATTRIBUTE_POSITION, s.positions.clone());
     m.insert_attribute(Mesh::ATTRIBUTE_NORMAL, s.normals.clone());
     m.insert_attribute(Mesh::ATTRIBUTE_UV_0, s.uvs.clone());
+    if !s.colors.is_empty() {
+        // Ambient occlusion darkens the baked light color per vertex.
+        let colors: Vec<[f32; 4]> = s
+            .colors
+            .iter()
+            .zip(s.ao.iter().chain(std::iter::repeat(&1.0)))
+            .map(|([r, g, b, a], ao)| [r * ao, g * ao, b * ao, *a])
+            .collect();
+        m.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
     m.insert_indices(Indices::U32(s.indices.clone()));
     m
 }
 
-fn camera_controls(
-    keys: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
-    mut exit: EventWriter<AppExit>,
-    mut query: Query<&mut Transform, With<FlyCamera>>,
+/// Moves chunks between levels of detail as the active camera travels.
+/// Side neighbours of a chunk that switched are remeshed too, since their
+/// skirts depend on it.
+fn update_chunk_lods(
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    settings: Res<LodSettings>,
+    mut lods: ResMut<ChunkLods>,
+    mut terrain: ResMut<VoxelTerrain>,
 ) {
-    if keys.just_pressed(KeyCode::Backspace) {
-        exit.send(AppExit::Success);
+    let Some(camera) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+        .map(|transform| transform.translation())
+    else {
+        return;
+    };
+    let coords: Vec<IVec3> = terrain.world.chunks().map(|(coord, _)| coord).collect();
+    for coord in coords {
+        let lod = settings.select(lods.get(coord), chunk_distance(coord, camera));
+        if lods.0.insert(coord, lod).unwrap_or_default() == lod {
+            continue;
+        }
+        terrain.dirty.insert(coord);
+        for side in [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z] {
+            terrain.dirty.insert(coord + side);
+        }
     }
+}
 
-    let mut transform = match query.get_single_mut() {
-        Ok(t) => t,
-        Err(_) => return,
+/// Rebuilds the meshes of every dirty chunk, spawning or despawning chunk
+/// entities as chunks gain or lose visible faces.
+#[allow(clippy::too_many_arguments)]
+fn remesh_dirty_chunks(
+    mut commands: Commands,
+    mut terrain: ResMut<VoxelTerrain>,
+    lods: Res<ChunkLods>,
+    style: Res<MeshStyle>,
+    mut chunk_entities: ResMut<ChunkEntities>,
+    mut culling: ResMut<ChunkCulling>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Option<Res<TerrainMaterials>>,
+    mut handles: Query<&mut Handle<Mesh>>,
+) {
+    if terrain.dirty.is_empty() {
+        return;
+    }
+    let Some(materials) = materials else {
+        return;
     };
+    let dirty: Vec<IVec3> = terrain.dirty.drain().collect();
+    for coord in dirty {
+        match terrain.world.chunk(coord) {
+            Some(chunk) => culling
+                .connections
+                .insert(coord, FaceConnections::of(chunk)),
+            None => culling.connections.remove(&coord),
+        };
+        let neighborhood = ChunkNeighborhood::new(&terrain.world, coord, Some(&terrain.light));
+        let mut layers = if neighborhood.center().is_some() {
+            mesh_lod_layers(
+                &neighborhood,
+                lods.get(coord),
+                *style,
+                lods.needs_skirts(coord),
+            )
+        } else {
+            Vec::new()
+        };
 
-    let delta = time.delta_seconds();
-    let rotation_speed = std::f32::consts::PI; // half turn per second
-    let mut yaw_input: f32 = 0.0;
-    if keys.pressed(KeyCode::KeyQ) {
-        yaw_input += 1.0;
-    }
-    if keys.pressed(KeyCode::KeyE) {
-        yaw_input -= 1.0;
-    }
-    if yaw_input.abs() > f32::EPSILON {
+        for material in BlockMaterial::ALL {
+            let key = (coord, material);
+            let existing = chunk_entities.0.get(&key).copied();
+            let Some(index) = layers.iter().position(|(m, _)| *m == material) else {
+                if let Some(entity) = existing {
+                    commands.entity(entity).despawn();
+                    chunk_entities.0.remove(&key);
+                }
+                continue;
+            };
+            let (_, smesh) = layers.swap_remove(index);
+
+            let mesh_handle = meshes.add(surface_to_bevy_mesh(&smesh));
+            if let Some(mut handle) = existing.and_then(|entity| handles.get_mut(entity).ok()) {
+                *handle = mesh_handle;
+                continue;
+            }
+            let entity = commands
+                .spawn(PbrBundle {
+                    mesh: mesh_handle,
+                    material: materials.for_layer(coord, material),
+                    transform: Transform::from_translation((coord * CHUNK_SIZE as i32).as_vec3()),
+                    ..default()
+                })
+                .id();
+            chunk_entities.0.insert(key, entity);
+        }
+    }
+}
+
+/// Hides chunk entities the active camera cannot see: those outside its
+/// view and those only reachable through solid chunks.
+fn cull_chunks(
+    registry: Res<CameraRegistry>,
+    cameras: Query<(&GlobalTransform, &Frustum), With<Camera3d>>,
+    terrain: Res<VoxelTerrain>,
+    chunk_entities: Res<ChunkEntities>,
+    mut culling: ResMut<ChunkCulling>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    let Some((camera, frustum)) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+    else {
+        return;
+    };
+    let size = CHUNK_SIZE as i32;
+    let bounds = (IVec3::splat(-1), IVec3::splat(WORLD_DIM as i32));
+    let chunk_aabb =
+        bevy::render::primitives::Aabb::from_min_max(Vec3::ZERO, Vec3::splat(size as f32));
+    let in_view = |coord: IVec3| {
+        let transform = Affine3A::from_translation((coord * size).as_vec3());
+        frustum.intersects_obb(&chunk_aabb, &transform, true, true)
+    };
+    let visible = visible_chunks(
+        chunk_coord(camera.translation().floor().as_ivec3()),
+        bounds,
+        |coord| {
+            culling
+                .connections
+                .get(&coord)
+                .copied()
+                .unwrap_or(FaceConnections::ALL)
+        },
+        in_view,
+    );
+
+    let mut stats = CullingStats::default();
+    let meshed: HashSet<IVec3> = chunk_entities.0.keys().map(|(coord, _)| *coord).collect();
+    stats.buried = terrain
+        .world
+        .chunks()
+        .filter(|(coord, _)| !meshed.contains(coord))
+        .count();
+    for &coord in &meshed {
+        if visible.contains(&coord) {
+            stats.drawn += 1;
+        } else if in_view(coord) {
+            stats.occluded += 1;
+        } else {
+            stats.outside_view += 1;
+        }
+    }
+    for (&(coord, _), &entity) in &chunk_entities.0 {
+        let Ok(mut visibility) = visibilities.get_mut(entity) else {
+            continue;
+        };
+        let wanted = if visible.contains(&coord) {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    culling.stats = stats;
+}
+
+/// Advances the water simulation and applies its writes, which wakes the
+/// cells around them for the next tick.
+fn tick_fluids(mut terrain: ResMut<VoxelTerrain>, mut ticks: Local<u32>) {
+    *ticks = ticks.wrapping_add(1);
+    if !ticks.is_multiple_of(FLUID_TICK_EVERY) || terrain.fluids.is_idle() {
+        return;
+    }
+    let terrain = &mut *terrain;
+    for (pos, voxel) in terrain.fluids.step(&terrain.world) {
+        terrain.set_voxel(pos, voxel);
+    }
+}
+
+/// Detaches unsupported sand and gravel columns and lands falling ones.
+fn tick_falling_blocks(time: Res<Time>, mut terrain: ResMut<VoxelTerrain>) {
+    if terrain.falling.is_idle() {
+        return;
+    }
+    let terrain = &mut *terrain;
+    for cell in terrain.falling.detach(&terrain.world) {
+        terrain.set_voxel(cell, Voxel::AIR);
+    }
+    for (cell, voxel) in terrain.falling.step(&terrain.world, time.delta_seconds()) {
+        terrain.set_voxel(cell, voxel);
+    }
+}
+
+/// Mirrors falling bodies as entities: one stack of cubes per body.
+fn sync_falling_blocks(
+    mut commands: Commands,
+    terrain: Res<VoxelTerrain>,
+    materials: Option<Res<TerrainMaterials>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut entities: Local<HashMap<u32, Entity>>,
+    mut cube: Local<Option<Handle<Mesh>>>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let Some(materials) = materials else {
+        return;
+    };
+    let cube = cube
+        .get_or_insert_with(|| meshes.add(Cuboid::new(1.0, 1.0, 1.0)))
+        .clone();
+
+    let bodies = terrain.falling.bodies();
+    entities.retain(|id, entity| {
+        let alive = bodies.iter().any(|body| body.id == *id);
+        if !alive {
+            commands.entity(*entity).despawn_recursive();
+        }
+        alive
+    });
+    for body in bodies {
+        let translation = body.origin();
+        if let Some(mut transform) = entities
+            .get(&body.id)
+            .and_then(|&entity| transforms.get_mut(entity).ok())
+        {
+            transform.translation = translation;
+            continue;
+        }
+        if entities.contains_key(&body.id) {
+            continue;
+        }
+        let coord = chunk_coord(translation.floor().as_ivec3());
+        let entity = commands
+            .spawn(SpatialBundle::from_transform(Transform::from_translation(
+                translation,
+            )))
+            .with_children(|parent| {
+                for (i, voxel) in body.voxels.iter().enumerate() {
+                    parent.spawn(PbrBundle {
+                        mesh: cube.clone(),
+                        material: materials.for_layer(coord, voxel.material()),
+                        transform: Transform::from_xyz(0.5, i as f32 + 0.5, 0.5),
+                        ..default()
+                    });
+                }
+            })
+            .id();
+        entities.insert(body.id, entity);
+    }
+}
+
+/// Detonates every explosive prop (B): carves a crater in the terrain and
+/// throws debris.
+#[allow(clippy::too_many_arguments)]
+fn detonate_explosives(
+    mut commands: Commands,
+    actions: Actions,
+    mut terrain: ResMut<VoxelTerrain>,
+    materials: Option<Res<TerrainMaterials>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut cube: Local<Option<Handle<Mesh>>>,
+    mut hud_state: ResMut<HudState>,
+    explosives: Query<(Entity, &Explosive, &GlobalTransform)>,
+) {
+    if !actions.just_pressed(Action::Detonate) {
+        return;
+    }
+    let Some(materials) = materials else {
+        return;
+    };
+    let cube = cube
+        .get_or_insert_with(|| meshes.add(Cuboid::new(0.4, 0.4, 0.4)))
+        .clone();
+
+    let mut blasts = 0;
+    for (entity, explosive, transform) in &explosives {
+        let explosion = Explosion {
+            center: transform.translation(),
+            radius: explosive.radius,
+            power: explosive.power,
+        };
+        commands.entity(entity).despawn_recursive();
+        let destroyed = terrain.explode(&explosion);
+        for debris in spawn_debris(&explosion, &destroyed, MAX_DEBRIS_PER_BLAST) {
+            let coord = chunk_coord(debris.position.floor().as_ivec3());
+            commands.spawn((
+                PbrBundle {
+                    mesh: cube.clone(),
+                    material: materials.for_layer(coord, debris.voxel.material()),
+                    transform: Transform::from_translation(debris.position),
+                    ..default()
+                },
+                DebrisPiece(debris),
+            ));
+        }
+        blasts += 1;
+    }
+    hud_state.message = if blasts == 0 {
+        "Nothing left to detonate".into()
+    } else {
+        format!("Detonated {blasts} explosive(s)")
+    };
+    hud_state.dirty = true;
+}
+
+fn update_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    terrain: Res<VoxelTerrain>,
+    mut pieces: Query<(Entity, &mut DebrisPiece, &mut Transform)>,
+) {
+    let dt = time.delta_seconds();
+    for (entity, mut piece, mut transform) in &mut pieces {
+        if step_debris(&terrain.world, &mut piece.0, dt) {
+            transform.translation = piece.0.position;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Cycles the weather to the next state (O).
+fn cycle_weather(actions: Actions, mut weather: ResMut<Weather>, mut hud_state: ResMut<HudState>) {
+    if !actions.just_pressed(Action::CycleWeather) {
+        return;
+    }
+    let from = weather.next().unwrap_or(weather.current());
+    let index = WeatherKind::ALL
+        .iter()
+        .position(|&k| k == from)
+        .unwrap_or(0);
+    let kind = WeatherKind::ALL[(index + 1) % WeatherKind::ALL.len()];
+    weather.set(kind);
+    hud_state.message = format!("Weather: {}", kind.name());
+    hud_state.dirty = true;
+}
+
+/// Moves pooled rain and snow particles around the active camera. Particles
+/// vanish when they reach the top of a voxel column, so nothing falls under
+/// overhangs.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_precipitation(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    terrain: Res<VoxelTerrain>,
+    assets: Option<Res<PrecipitationAssets>>,
+    mut seed: Local<u32>,
+    mut particles: Query<(
+        &mut Precipitation,
+        &mut Transform,
+        &mut Visibility,
+        &mut Handle<Mesh>,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    let Some(assets) = assets else {
+        return;
+    };
+    let Some(camera) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+        .map(|transform| transform.translation())
+    else {
+        return;
+    };
+    *seed = (*seed).max(1);
+    let profile = weather.profile();
+    let intensity = (profile.rain + profile.snow).min(1.0);
+    let snowing = profile.snow > profile.rain;
+    let active = (intensity * PRECIPITATION_POOL as f32) as usize;
+    let dt = time.delta_seconds();
+    let elapsed = time.elapsed_seconds();
+
+    for (mut particle, mut transform, mut visibility, mut mesh, mut material) in &mut particles {
+        if particle.slot >= active {
+            particle.falling = false;
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        if particle.snow != snowing {
+            particle.snow = snowing;
+            particle.falling = false;
+            if snowing {
+                *mesh = assets.snow_mesh.clone();
+                *material = assets.snow_material.clone();
+            } else {
+                *mesh = assets.rain_mesh.clone();
+                *material = assets.rain_material.clone();
+            }
+        }
+
+        if particle.falling {
+            let pos = &mut transform.translation;
+            if snowing {
+                pos.y -= 1.8 * dt;
+                pos.x += (elapsed * 1.3 + particle.slot as f32).sin() * 0.6 * dt;
+            } else {
+                pos.y -= 16.0 * dt;
+            }
+            let far = pos.xz().distance(camera.xz()) > PRECIPITATION_RADIUS * 1.5;
+            if !far && pos.y > camera.y - 16.0 && !is_sheltered(&terrain.heights, *pos) {
+                continue;
+            }
+        }
+
+        let mut random = || (next_random(&mut seed) % 10_000) as f32 / 10_000.0;
+        let angle = random() * std::f32::consts::TAU;
+        let radius = random().sqrt() * PRECIPITATION_RADIUS;
+        let spawn = Vec3::new(
+            camera.x + angle.cos() * radius,
+            camera.y + 4.0 + random() * 16.0,
+            camera.z + angle.sin() * radius,
+        );
+        transform.translation = spawn;
+        particle.falling = !is_sheltered(&terrain.heights, spawn);
+        *visibility = if particle.falling {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Settles snow layers on exposed column tops around the active camera while
+/// it snows and snow cover is enabled.
+fn accumulate_snow(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut terrain: ResMut<VoxelTerrain>,
+    mut state: Local<(u32, f32)>,
+) {
+    let snow = weather.profile().snow;
+    if !weather.accumulate_snow || snow < 0.5 {
+        return;
+    }
+    let Some(camera) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+        .map(|transform| transform.translation())
+    else {
+        return;
+    };
+    let (seed, budget) = &mut *state;
+    *seed = (*seed).max(1);
+    // Attempted columns per second at full snowfall.
+    *budget += time.delta_seconds() * 40.0 * snow;
+    while *budget >= 1.0 {
+        *budget -= 1.0;
+        let dx = (next_random(seed) % 65) as i32 - 32;
+        let dz = (next_random(seed) % 65) as i32 - 32;
+        let x = camera.x.floor() as i32 + dx;
+        let z = camera.z.floor() as i32 + dz;
+        if let Some(cell) = snow_layer_cell(&terrain.world, &terrain.heights, x, z) {
+            terrain.set_voxel(cell, Voxel::SNOW);
+        }
+    }
+}
+
+/// Places the held block at the active camera's position, or removes it if
+/// it is already there.
+fn place_held_block(
+    actions: Actions,
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    held: Res<HeldBlock>,
+    mut terrain: ResMut<VoxelTerrain>,
+    mut hud_state: ResMut<HudState>,
+) {
+    if !actions.just_pressed(Action::PlaceBlock) {
+        return;
+    }
+    let Some(transform) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+    else {
+        return;
+    };
+    let pos = transform.translation().floor().as_ivec3();
+    let current = terrain.world.get(pos);
+    let block = held.0;
+    if current == block {
+        terrain.set_voxel(pos, Voxel::AIR);
+        hud_state.message = format!("Removed {block} at {pos}");
+    } else if current.is_empty() {
+        terrain.set_voxel(pos, block);
+        hud_state.message = format!("Placed {block} at {pos}");
+    } else {
+        hud_state.message = format!("Cannot place {block} inside terrain");
+    }
+    hud_state.dirty = true;
+}
+
+fn toggle_collision(
+    actions: Actions,
+    mut collision: ResMut<CollisionSettings>,
+    mut hud_state: ResMut<HudState>,
+) {
+    if !actions.just_pressed(Action::ToggleCollision) {
+        return;
+    }
+    collision.enabled = !collision.enabled;
+    let state = if collision.enabled { "on" } else { "off" };
+    hud_state.message = format!("Camera collision: {state}");
+    hud_state.dirty = true;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn camera_controls(
+    actions: Actions,
+    time: Res<Time>,
+    collision: Res<CollisionSettings>,
+    terrain: Res<VoxelTerrain>,
+    registry: Res<CameraRegistry>,
+    mut exit: EventWriter<AppExit>,
+    mut fly_rigs: Query<&mut Transform, (With<FlyCamera>, Without<Walker>)>,
+    mut walkers: Query<(&mut Transform, &mut Walker), Without<FlyCamera>>,
+) {
+    if actions.just_pressed(Action::Quit) {
+        exit.send(AppExit::Success);
+    }
+
+    // Movement drives the walker while its camera is active, the fly rig otherwise.
+    let active_camera = registry.active_camera();
+    let walker = walkers
+        .iter_mut()
+        .find(|(_, walker)| Some(walker.camera) == active_camera);
+    let (mut transform, mut walker) = match walker {
+        Some((transform, walker)) => (transform, Some(walker)),
+        None => match fly_rigs.get_single_mut() {
+            Ok(t) => (t, None),
+            Err(_) => return,
+        },
+    };
+
+    let delta = time.delta_seconds();
+    let rotation_speed = std::f32::consts::PI; // half turn per second
+    let mut yaw_input: f32 = 0.0;
+    if actions.pressed(Action::TurnLeft) {
+        yaw_input += 1.0;
+    }
+    if actions.pressed(Action::TurnRight) {
+        yaw_input -= 1.0;
+    }
+    if yaw_input.abs() > f32::EPSILON {
         transform.rotate_y(yaw_input * rotation_speed * delta);
     }
 
@@ -442,109 +1691,157 @@ fn camera_controls(
         right = right.normalize();
     }
 
-    // Forward/back movement with W/S
-    if keys.pressed(KeyCode::KeyW) {
+    // Forward/back movement
+    if actions.pressed(Action::MoveForward) {
         movement += forward;
     }
-    if keys.pressed(KeyCode::KeyS) {
+    if actions.pressed(Action::MoveBack) {
         movement -= forward;
     }
 
-    // Lateral movement with A/D (strafe left/right)
-    if keys.pressed(KeyCode::KeyA) {
+    // Lateral movement (strafe left/right)
+    if actions.pressed(Action::StrafeLeft) {
         movement -= right;
     }
-    if keys.pressed(KeyCode::KeyD) {
+    if actions.pressed(Action::StrafeRight) {
         movement += right;
     }
-    if keys.pressed(KeyCode::KeyX) {
+
+    if let Some(walker) = walker.as_mut() {
+        let input = WalkInput {
+            movement,
+            jump: actions.pressed(Action::Jump),
+            crouch: actions.pressed(Action::Crouch),
+        };
+        let settings = walker.settings;
+        step_walker(&terrain.world, &mut walker.state, input, &settings, delta);
+        transform.translation = walker.state.position;
+        return;
+    }
+
+    if actions.pressed(Action::MoveUp) {
         movement.y += 1.0;
     }
-    if keys.pressed(KeyCode::KeyZ) {
+    if actions.pressed(Action::MoveDown) {
         movement.y -= 1.0;
     }
 
     if movement != Vec3::ZERO {
         let speed = 50.0;
-        transform.translation += movement.normalize() * speed * delta;
-    }
-}
-
-fn animate_light(time: Res<Time>, mut lights: Query<(&AnimatedLight, &mut DirectionalLight)>) {
-    for (animated, mut light) in &mut lights {
-        let cycle = time.elapsed_seconds() * animated.speed;
-        let normalized = cycle.rem_euclid(3.0);
-
-        let (from, to, t) = if normalized < 1.0 {
-            (Vec3::new(0.2, 0.3, 1.0), Vec3::splat(1.0), normalized)
-        } else if normalized < 2.0 {
-            (
-                Vec3::splat(1.0),
-                Vec3::new(1.0, 0.25, 0.25),
-                normalized - 1.0,
-            )
+        let displacement = movement.normalize() * speed * delta;
+        if collision.enabled {
+            // Sliding along walls keeps the rig outside solid voxels.
+            let bounds = Aabb::from_center(transform.translation, FLY_RIG_HALF_EXTENTS);
+            let swept = sweep_aabb(&terrain.world, bounds, displacement);
+            transform.translation += swept.motion;
         } else {
-            (
-                Vec3::new(1.0, 0.25, 0.25),
-                Vec3::new(0.2, 0.3, 1.0),
-                normalized - 2.0,
-            )
-        };
-
-        let rgb = from.lerp(to, t);
-        light.color = Color::srgb(rgb.x, rgb.y, rgb.z);
+            transform.translation += displacement;
+        }
     }
 }
 
-fn cycle_cameras(
-    keys: Res<ButtonInput<KeyCode>>,
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn toggle_walk_mode(
+    actions: Actions,
+    terrain: Res<VoxelTerrain>,
     mut registry: ResMut<CameraRegistry>,
+    mut hud_state: ResMut<HudState>,
     mut cameras: Query<&mut Camera>,
+    camera_parents: Query<(Entity, &Parent), With<Camera3d>>,
+    mut fly_rigs: Query<(Entity, &mut Transform), (With<FlyCamera>, Without<Walker>)>,
+    mut walkers: Query<(&mut Transform, &mut Walker), Without<FlyCamera>>,
 ) {
-    if !keys.just_pressed(KeyCode::Tab) {
+    if !actions.just_pressed(Action::ToggleWalk) {
         return;
     }
-    let count = registry.cameras.len();
-    if count < 2 {
+    let Ok((fly_rig, mut fly_transform)) = fly_rigs.get_single_mut() else {
         return;
-    }
+    };
+    let Some((mut walker_transform, mut walker)) = walkers.iter_mut().next() else {
+        return;
+    };
 
-    let current = registry.active.min(count - 1);
-    if let Ok(mut camera) = cameras.get_mut(registry.cameras[current]) {
-        camera.is_active = false;
+    let walking = registry.active_camera() == Some(walker.camera);
+    if walking {
+        // Hand control back to the fly rig, hovering where the walker's eyes were.
+        let eye = walker.state.position + Vec3::Y * walker.state.eye_height(&walker.settings);
+        fly_transform.translation = eye;
+        fly_transform.rotation = walker_transform.rotation;
+        let fly_camera = camera_parents
+            .iter()
+            .find(|(_, parent)| parent.get() == fly_rig)
+            .map(|(camera, _)| camera);
+        if let Some(index) = fly_camera.and_then(|camera| registry.index_of(camera)) {
+            registry.activate(index, &mut cameras);
+        }
+        hud_state.message = "Flying".into();
+    } else {
+        // Drop the walker below the fly rig, lifting it out of any terrain it would start in.
+        let mut feet = fly_transform.translation;
+        let settings = walker.settings;
+        let mut state = WalkerState::new(feet);
+        while state.bounds(&settings).intersects_solid(&terrain.world) {
+            feet.y = feet.y.floor() + 1.0;
+            state.position = feet;
+        }
+        walker.state = state;
+        walker_transform.translation = feet;
+        let (yaw, _, _) = fly_transform.rotation.to_euler(EulerRot::YXZ);
+        walker_transform.rotation = Quat::from_rotation_y(yaw);
+        if let Some(index) = registry.index_of(walker.camera) {
+            registry.activate(index, &mut cameras);
+        }
+        hud_state.message = "Walking (Space: jump, Shift: crouch)".into();
     }
+    hud_state.dirty = true;
+}
 
-    registry.active = (current + 1) % count;
+fn update_walker_eye(
+    walkers: Query<&Walker, Changed<Walker>>,
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+) {
+    for walker in &walkers {
+        if let Ok(mut transform) = cameras.get_mut(walker.camera) {
+            transform.translation.y = walker.state.eye_height(&walker.settings);
+        }
+    }
+}
 
-    if let Ok(mut camera) = cameras.get_mut(registry.cameras[registry.active]) {
-        camera.is_active = true;
+fn cycle_cameras(
+    actions: Actions,
+    mut registry: ResMut<CameraRegistry>,
+    mut cameras: Query<&mut Camera>,
+) {
+    if actions.just_pressed(Action::CycleCamera) {
+        registry.activate_next(&mut cameras);
     }
 }
 
 fn screenshot_capture(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     registry: Res<CameraRegistry>,
     mut screenshot_manager: ResMut<ScreenshotManager>,
     mut hud_state: ResMut<HudState>,
 ) {
-    if !keys.just_pressed(KeyCode::KeyP) {
-        return;
-    }
-    if registry.cameras.is_empty() {
-        hud_state.message = "Screenshot failed: no registered cameras".into();
-        hud_state.dirty = true;
-        return;
-    }
-    if let Err(err) = std::fs::create_dir_all("screenshots") {
-        hud_state.message = format!("Screenshot failed: {err}");
-        hud_state.dirty = true;
+    if !actions.just_pressed(Action::Screenshot) {
         return;
     }
+    hud_state.message = match save_screenshot(&registry, &mut screenshot_manager) {
+        Ok(message) | Err(message) => message,
+    };
+    hud_state.dirty = true;
+}
 
-    let active_index = registry
-        .active
-        .min(registry.cameras.len().saturating_sub(1));
-    let camera_entity = registry.cameras[active_index];
+/// Saves the active camera's next frame to `screenshots/` and describes the
+/// outcome.
+fn save_screenshot(
+    registry: &CameraRegistry,
+    screenshot_manager: &mut ScreenshotManager,
+) -> Result<String, String> {
+    let Some(camera_entity) = registry.active_camera() else {
+        return Err("Screenshot failed: no registered cameras".into());
+    };
+    std::fs::create_dir_all("screenshots").map_err(|err| format!("Screenshot failed: {err}"))?;
 
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -559,215 +1856,260 @@ fn screenshot_capture(
                 .strip_prefix(&cwd)
                 .map(|p| p.display().to_string())
                 .unwrap_or_else(|_| path.display().to_string());
-            hud_state.message = format!("Saved screenshot: {display_path}");
-            hud_state.dirty = true;
             info!("Saved screenshot to {}", path.display());
+            Ok(format!("Saved screenshot: {display_path}"))
         }
         Err(err) => {
-            hud_state.message = format!("Screenshot failed: {err}");
-            hud_state.dirty = true;
             warn!("Failed to capture screenshot: {err}");
+            Err(format!("Screenshot failed: {err}"))
         }
     }
 }
 
-fn build_ruin(
-    commands: &mut Commands,
-    meshes: &mut Assets<Mesh>,
-    materials: &mut Assets<StandardMaterial>,
-    world_center: Vec3,
-) -> Vec3 {
-    let wall_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.45, 0.35, 0.28),
-        perceptual_roughness: 0.7,
-        reflectance: 0.05,
-        ..default()
-    });
-    let floor_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.25, 0.22, 0.2),
-        perceptual_roughness: 0.9,
-        reflectance: 0.02,
-        ..default()
-    });
-    let core_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.6, 0.5, 0.55),
-        emissive: Color::srgb(1.8, 1.6, 2.6).into(),
-        perceptual_roughness: 0.15,
-        reflectance: 0.1,
-        ..default()
-    });
-
-    let wall_mesh = meshes.add(Mesh::from(Cuboid::new(12.0, 6.0, 1.0)));
-    let pillar_mesh = meshes.add(Mesh::from(Cuboid::new(1.2, 4.0, 1.2)));
-    let floor_mesh = meshes.add(Mesh::from(Cuboid::new(12.0, 0.4, 12.0)));
-
-    let ruin_origin = Vec3::new(
-        world_center.x,
-        height_at(world_center.x, world_center.z) + 0.2,
-        world_center.z + 18.0,
-    );
-
-    // floor slab
-    commands.spawn(PbrBundle {
-        mesh: floor_mesh.clone(),
-        material: floor_material.clone(),
-        transform: Transform::from_translation(ruin_origin + Vec3::new(0.0, -0.2, 0.0)),
-        ..default()
-    });
-
-    // walls (U-shape ruin)
-    let wall_positions = [
-        Vec3::new(-6.0, 3.0, -6.0),
-        Vec3::new(-6.0, 3.0, 6.0),
-        Vec3::new(6.0, 3.0, -6.0),
-    ];
-    let wall_rotations = [
-        Quat::IDENTITY,
-        Quat::IDENTITY,
-        Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
-    ];
-
-    for (pos, rot) in wall_positions.into_iter().zip(wall_rotations) {
-        commands.spawn(PbrBundle {
-            mesh: wall_mesh.clone(),
-            material: wall_material.clone(),
-            transform: Transform::from_translation(ruin_origin + pos).with_rotation(rot),
-            ..default()
-        });
+/// Redraws a few stale map tiles each frame.
+fn update_world_map(mut terrain: ResMut<VoxelTerrain>, mut display: ResMut<MapDisplay>) {
+    let terrain = &mut *terrain;
+    let tiles: Vec<IVec2> = terrain
+        .unmapped
+        .iter()
+        .take(MAP_TILES_PER_FRAME)
+        .copied()
+        .collect();
+    for tile in tiles {
+        terrain.unmapped.remove(&tile);
+        display
+            .map
+            .update_tile(&terrain.world, &terrain.heights, tile);
     }
+}
 
-    // corner pillars
-    let pillar_offsets = [
-        Vec3::new(-5.5, 2.0, -5.5),
-        Vec3::new(-5.5, 2.0, 5.5),
-        Vec3::new(5.5, 2.0, -5.5),
-    ];
+/// A map marker: world column, heading and colour.
+type MapMarker = (Vec2, Vec2, [u8; 4]);
 
-    for offset in pillar_offsets {
-        commands.spawn(PbrBundle {
-            mesh: pillar_mesh.clone(),
-            material: wall_material.clone(),
-            transform: Transform::from_translation(ruin_origin + offset),
-            ..default()
-        });
+/// Opens, pans and zooms the full map, and saves the whole map as a PNG.
+fn map_controls(
+    actions: Actions,
+    time: Res<Time>,
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut display: ResMut<MapDisplay>,
+    mut visibilities: Query<&mut Visibility>,
+    mut hud_state: ResMut<HudState>,
+) {
+    if actions.just_pressed(Action::ToggleMap) {
+        display.open = !display.open;
+        if let Some(transform) = registry
+            .active_camera()
+            .and_then(|camera| cameras.get(camera).ok())
+        {
+            display.view.center = transform.translation().xz();
+        }
+        if let Ok(mut visibility) = visibilities.get_mut(display.full_node) {
+            *visibility = if display.open {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
     }
+    if actions.just_pressed(Action::ExportMap) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let path = std::path::Path::new("screenshots").join(format!("map-{}.png", now.as_secs()));
+        let saved = std::fs::create_dir_all("screenshots")
+            .map_err(|err| err.to_string())
+            .and_then(|()| display.map.save_png(&path));
+        hud_state.message = match saved {
+            Ok(()) => format!("Saved map: {}", path.display()),
+            Err(err) => format!("Map export failed: {err}"),
+        };
+        hud_state.dirty = true;
+    }
+    if !display.open {
+        return;
+    }
+    let mut pan = Vec2::ZERO;
+    for (action, direction) in [
+        (Action::MapPanLeft, Vec2::NEG_X),
+        (Action::MapPanRight, Vec2::X),
+        (Action::MapPanUp, Vec2::NEG_Y),
+        (Action::MapPanDown, Vec2::Y),
+    ] {
+        if actions.pressed(action) {
+            pan += direction;
+        }
+    }
+    let cells_per_pixel = display.view.cells_per_pixel;
+    display.view.center += pan * 300.0 * cells_per_pixel * time.delta_seconds();
+    if actions.just_pressed(Action::MapZoomIn) {
+        display.view.cells_per_pixel = (cells_per_pixel / 2.0).max(0.25);
+    }
+    if actions.just_pressed(Action::MapZoomOut) {
+        display.view.cells_per_pixel = (cells_per_pixel * 2.0).min(8.0);
+    }
+}
 
-    // debris block
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Mesh::from(Cuboid::new(2.0, 1.2, 1.5))),
-        material: core_material,
-        transform: Transform::from_translation(ruin_origin + Vec3::new(1.5, 0.6, 0.0)),
-        ..default()
-    });
-
-    ruin_origin
+/// Paints the minimap around the active camera and, while it is open, the
+/// full map, with the camera and vehicle marked on both. Views are only
+/// repainted when they or the map change.
+#[allow(clippy::type_complexity)]
+fn draw_maps(
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    vehicles: Query<&GlobalTransform, With<GroundVehicle>>,
+    display: Res<MapDisplay>,
+    mut images: ResMut<Assets<Image>>,
+    mut painted: Local<[Option<(u64, MapView, Vec<MapMarker>)>; 2]>,
+) {
+    let heading = |transform: &GlobalTransform| transform.forward().xz();
+    let mut markers: Vec<MapMarker> = Vec::new();
+    if let Some(camera) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+    {
+        markers.push((
+            camera.translation().xz(),
+            heading(camera),
+            [255, 255, 255, 255],
+        ));
+    }
+    for vehicle in &vehicles {
+        markers.push((
+            vehicle.translation().xz(),
+            heading(vehicle),
+            [255, 150, 40, 255],
+        ));
+    }
+    let Some(&(camera, _, _)) = markers.first() else {
+        return;
+    };
+    let minimap = MapView {
+        center: camera,
+        cells_per_pixel: 1.0,
+    };
+    let mut views = vec![(0, &display.minimap, minimap, MINIMAP_SIZE)];
+    if display.open {
+        views.push((1, &display.full, display.view, FULL_MAP_SIZE));
+    }
+    for (slot, handle, view, size) in views {
+        let key = (display.map.revision(), view, markers.clone());
+        if painted[slot].as_ref() == Some(&key) {
+            continue;
+        }
+        let mut pixels = display.map.render(view, size, size);
+        let half = Vec2::splat(size as f32 * 0.5);
+        for &(at, heading, color) in &markers {
+            let at = view.to_pixel(at, half);
+            draw_marker(&mut pixels, size, size, at, heading, color);
+        }
+        if let Some(image) = images.get_mut(handle) {
+            image.data = pixels;
+        }
+        painted[slot] = Some(key);
+    }
 }
 
-fn build_fountain(
+/// Spawns the entities a stamped structure asks for through its markers.
+fn spawn_structure_markers(
     commands: &mut Commands,
     meshes: &mut Assets<Mesh>,
     materials: &mut Assets<StandardMaterial>,
-    ruin_origin: Vec3,
+    markers: &[Marker],
 ) {
-    let basin_material = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.32, 0.3, 0.28),
-        perceptual_roughness: 0.8,
-        reflectance: 0.04,
-        ..default()
-    });
-    let water_material = materials.add(StandardMaterial {
-        base_color: Color::srgba(0.35, 0.6, 0.65, 0.8),
-        emissive: Color::srgb(0.2, 0.4, 0.5).into(),
-        alpha_mode: AlphaMode::Add,
-        perceptual_roughness: 0.05,
-        reflectance: 0.08,
-        ..default()
-    });
-    let spray_material = materials.add(StandardMaterial {
-        base_color: Color::srgba(0.6, 0.8, 1.0, 0.6),
-        emissive: Color::srgb(0.8, 0.9, 1.3).into(),
-        alpha_mode: AlphaMode::Add,
-        perceptual_roughness: 0.02,
-        reflectance: 0.1,
-        ..default()
-    });
-
-    let basin_mesh = meshes.add(Mesh::from(Cylinder {
-        radius: 3.0,
-        half_height: 0.3,
-        ..default()
-    }));
-    let water_mesh = meshes.add(Mesh::from(Cylinder {
-        radius: 2.6,
-        half_height: 0.1,
-        ..default()
-    }));
-    let spray_mesh = meshes.add(Mesh::from(Cylinder {
-        radius: 0.6,
-        half_height: 1.1,
-        ..default()
-    }));
-
-    let fountain_origin = ruin_origin + Vec3::new(-14.0, 0.0, 3.5);
-
-    commands.spawn(PbrBundle {
-        mesh: basin_mesh,
-        material: basin_material,
-        transform: Transform::from_translation(fountain_origin + Vec3::new(0.0, -0.1, 0.0)),
-        ..default()
-    });
-
-    commands.spawn(PbrBundle {
-        mesh: water_mesh.clone(),
-        material: water_material.clone(),
-        transform: Transform::from_translation(fountain_origin + Vec3::new(0.0, 0.2, 0.0)),
-        ..default()
-    });
-
-    commands
-        .spawn((
-            PbrBundle {
-                mesh: spray_mesh,
-                material: spray_material,
-                transform: Transform::from_translation(fountain_origin + Vec3::new(0.0, 1.2, 0.0)),
-                ..default()
-            },
-            FountainSplash {
-                base_height: fountain_origin.y + 1.2,
-                amplitude: 0.35,
-                speed: 2.5,
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(PointLightBundle {
-                point_light: PointLight {
-                    intensity: 2_800.0,
-                    color: Color::srgb(0.45, 0.7, 1.0),
-                    shadows_enabled: false,
-                    range: 12.0,
+    for marker in markers {
+        // Markers name a cell; props stand on the middle of its floor.
+        let base = marker.pos.as_vec3() + Vec3::new(0.5, 0.0, 0.5);
+        match marker.name.as_str() {
+            "explosive" => {
+                commands.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::from(Cuboid::new(2.0, 1.2, 1.5))),
+                        material: materials.add(StandardMaterial {
+                            base_color: Color::srgb(0.6, 0.5, 0.55),
+                            emissive: Color::srgb(1.8, 1.6, 2.6).into(),
+                            perceptual_roughness: 0.15,
+                            reflectance: 0.1,
+                            ..default()
+                        }),
+                        transform: Transform::from_translation(base + Vec3::Y * 0.6),
+                        ..default()
+                    },
+                    Explosive {
+                        radius: 5.0,
+                        power: 4.0,
+                    },
+                ));
+            }
+            "spray" => {
+                let spray_material = materials.add(StandardMaterial {
+                    base_color: Color::srgba(0.6, 0.8, 1.0, 0.6),
+                    emissive: Color::srgb(0.8, 0.9, 1.3).into(),
+                    alpha_mode: AlphaMode::Add,
+                    perceptual_roughness: 0.02,
+                    reflectance: 0.1,
                     ..default()
-                },
-                transform: Transform::from_translation(Vec3::new(0.0, 0.6, 0.0)),
-                ..default()
-            });
-        });
+                });
+                let base_height = base.y + 0.9;
+                commands
+                    .spawn((
+                        PbrBundle {
+                            mesh: meshes.add(Mesh::from(Cylinder::new(0.6, 2.2))),
+                            material: spray_material,
+                            transform: Transform::from_translation(Vec3::new(
+                                base.x,
+                                base_height,
+                                base.z,
+                            )),
+                            ..default()
+                        },
+                        FountainSplash {
+                            base_height,
+                            amplitude: 0.35,
+                            speed: 2.5,
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            PointLightBundle {
+                                point_light: PointLight {
+                                    intensity: 2_800.0,
+                                    color: Color::srgb(0.45, 0.7, 1.0),
+                                    shadows_enabled: false,
+                                    range: 12.0,
+                                    ..default()
+                                },
+                                transform: Transform::from_translation(Vec3::new(0.0, 0.6, 0.0)),
+                                ..default()
+                            },
+                            NightLight {
+                                day_intensity: 800.0,
+                                night_intensity: 2_800.0,
+                            },
+                        ));
+                    });
+            }
+            other => warn!("structure marker {other:?} has no spawner"),
+        }
+    }
 }
 
 fn vehicle_controls(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     time: Res<Time>,
     center: Res<WorldCenter>,
     mut auto_motion: ResMut<VehicleAutoMotion>,
+    generator: Res<TerrainGenerator>,
     mut vehicles: Query<&mut Transform, With<GroundVehicle>>,
 ) {
     let mut movement_input = 0.0f32;
-    if keys.pressed(KeyCode::KeyK) {
+    if actions.pressed(Action::VehicleForward) {
         movement_input += 1.0;
     }
-    if keys.pressed(KeyCode::KeyJ) {
+    if actions.pressed(Action::VehicleBack) {
         movement_input -= 1.0;
     }
-    let level_request = keys.just_pressed(KeyCode::Period);
+    let level_request = actions.just_pressed(Action::VehicleLevel);
 
     let delta = time.delta_seconds();
     let manual_speed = 35.0;
@@ -808,12 +2150,16 @@ fn vehicle_controls(
             transform.rotation = Quat::from_rotation_y(yaw);
         }
 
-        let ground_height = height_at(position.x, position.z);
+        let ground_height = generator.surface_or_water(position.x, position.z);
         transform.translation = Vec3::new(position.x, ground_height + 1.2, position.z);
     }
 }
 
-fn update_hud(mut hud_state: ResMut<HudState>, mut texts: Query<&mut Text, With<HudText>>) {
+fn update_hud(
+    mut hud_state: ResMut<HudState>,
+    overlay: Res<DebugOverlay>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
     if !hud_state.dirty {
         return;
     }
@@ -823,12 +2169,92 @@ fn update_hud(mut hud_state: ResMut<HudState>, mut texts: Query<&mut Text, With<
     if let Ok(mut text) = texts.get_mut(entity) {
         if text.sections.len() >= 2 {
             text.sections[0].value = format!("{PROJECT_NAME}\n");
-            text.sections[1].value = hud_state.message.clone();
+            text.sections[1].value = if overlay.visible {
+                let mut lines = overlay.lines.clone();
+                lines.push(hud_state.message.clone());
+                lines.join("\n")
+            } else {
+                hud_state.message.clone()
+            };
         }
     }
     hud_state.dirty = false;
 }
 
+fn toggle_debug_overlay(
+    actions: Actions,
+    mut overlay: ResMut<DebugOverlay>,
+    mut hud_state: ResMut<HudState>,
+) {
+    if actions.just_pressed(Action::DebugOverlay) {
+        overlay.visible = !overlay.visible;
+        hud_state.dirty = true;
+    }
+}
+
+/// Gathers the debug overlay's lines while it is shown.
+#[allow(clippy::too_many_arguments)]
+fn update_debug_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    registry: Res<CameraRegistry>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    terrain: Res<VoxelTerrain>,
+    chunk_entities: Res<ChunkEntities>,
+    culling: Res<ChunkCulling>,
+    meshes: Res<Assets<Mesh>>,
+    handles: Query<&Handle<Mesh>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut hud_state: ResMut<HudState>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let smoothed = |path| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+    };
+    let mut info = DebugInfo {
+        fps: smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+        frame_time: smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME),
+        camera: registry.active_name().to_string(),
+        loaded_chunks: terrain.world.chunk_count(),
+        meshed_chunks: chunk_entities
+            .0
+            .keys()
+            .map(|(coord, _)| *coord)
+            .collect::<HashSet<_>>()
+            .len(),
+        culling: culling.stats,
+        ..default()
+    };
+    if let Some(transform) = registry
+        .active_camera()
+        .and_then(|camera| cameras.get(camera).ok())
+    {
+        info.position = transform.translation();
+        info.forward = *transform.forward();
+        info.target = raycast(
+            &terrain.world,
+            info.position,
+            info.forward,
+            DEBUG_TARGET_REACH,
+        )
+        .map(|hit| (hit.cell, terrain.world.get(hit.cell)));
+    }
+    for mesh in chunk_entities
+        .0
+        .values()
+        .filter_map(|&entity| handles.get(entity).ok())
+        .filter_map(|handle| meshes.get(handle))
+    {
+        info.vertices += mesh.count_vertices();
+        info.triangles += mesh.indices().map_or(0, |indices| indices.len() / 3);
+    }
+    overlay.lines = info.lines();
+    hud_state.dirty = true;
+}
+
 fn rotate_spotlights(time: Res<Time>, mut query: Query<(&RotatingSpotlight, &mut Transform)>) {
     for (rotator, mut transform) in &mut query {
         let delta = rotator.speed * time.delta_seconds();
@@ -891,3 +2317,239 @@ fn move_inside_bounds(position: Vec3, center: Vec3, limit_chunks: f32) -> Vec3 {
         center.z + offset.z * chunk_size,
     )
 }
+
+fn register_commands(app: &mut App) {
+    let commands = [
+        ConsoleCommand {
+            name: "tp",
+            usage: "x y z",
+            help: "move the active camera's rig to a position",
+            run: teleport_command,
+        },
+        ConsoleCommand {
+            name: "time",
+            usage: "[set HH[:MM]]",
+            help: "show or set the time of day",
+            run: time_command,
+        },
+        ConsoleCommand {
+            name: "seed",
+            usage: "",
+            help: "show the terrain seed",
+            run: seed_command,
+        },
+        ConsoleCommand {
+            name: "give",
+            usage: "block",
+            help: "hold a block, like brick_stairs[facing=north], for the place key",
+            run: give_command,
+        },
+        ConsoleCommand {
+            name: "fill",
+            usage: "x1 y1 z1 x2 y2 z2 block",
+            help: "set every voxel in a box",
+            run: fill_command,
+        },
+        ConsoleCommand {
+            name: "screenshot",
+            usage: "",
+            help: "save a screenshot of the active camera",
+            run: screenshot_command,
+        },
+        ConsoleCommand {
+            name: "camera",
+            usage: "next|NAME",
+            help: "switch to the next camera or one by name",
+            run: camera_command,
+        },
+        ConsoleCommand {
+            name: "save",
+            usage: "[file]",
+            help: "write the world to a file (default saves/world.bin)",
+            run: save_command,
+        },
+        ConsoleCommand {
+            name: "load",
+            usage: "[file]",
+            help: "replace the world with a saved one",
+            run: load_command,
+        },
+        ConsoleCommand {
+            name: "bindings",
+            usage: "[action]",
+            help: "list the keys bound to every action, or to one",
+            run: bindings_command,
+        },
+    ];
+    for command in commands {
+        app.register_console_command(command);
+    }
+}
+
+/// Parses exactly `N` numbers, or fails with `usage`.
+fn parse_numbers<T: std::str::FromStr, const N: usize>(
+    args: &[&str],
+    usage: &str,
+) -> Result<[T; N], String> {
+    let numbers: Vec<T> = args
+        .iter()
+        .map(|arg| arg.parse().map_err(|_| format!("invalid number {arg:?}")))
+        .collect::<Result<_, _>>()?;
+    numbers.try_into().map_err(|_| format!("usage: {usage}"))
+}
+
+fn teleport_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [x, y, z] = parse_numbers::<f32, 3>(args, "tp x y z")?;
+    let target = Vec3::new(x, y, z);
+    let camera = world
+        .resource::<CameraRegistry>()
+        .active_camera()
+        .ok_or("no active camera")?;
+    // Cameras hang off their rigs; move the outermost one.
+    let mut rig = camera;
+    while let Some(parent) = world.get::<Parent>(rig) {
+        rig = parent.get();
+    }
+    if let Some(mut walker) = world.get_mut::<Walker>(rig) {
+        walker.state = WalkerState::new(target);
+    }
+    let mut transform = world
+        .get_mut::<Transform>(rig)
+        .ok_or("the active camera has no rig")?;
+    transform.translation = target;
+    Ok(format!("teleported to {x} {y} {z}"))
+}
+
+fn time_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut time = world.resource_mut::<TimeOfDay>();
+    match args {
+        [] => {}
+        ["set", raw] => {
+            let hours = parse_clock(raw).ok_or_else(|| format!("invalid time {raw:?}"))?;
+            time.set_hours(hours);
+        }
+        _ => return Err("usage: time [set HH[:MM]]".into()),
+    }
+    let minutes = (time.hours * 60.0).round() as u32 % (24 * 60);
+    Ok(format!("time is {:02}:{:02}", minutes / 60, minutes % 60))
+}
+
+fn seed_command(world: &mut World, _: &[&str]) -> Result<String, String> {
+    Ok(format!(
+        "seed {}",
+        world.resource::<TerrainGenerator>().seed
+    ))
+}
+
+fn bindings_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let map = world.get_resource::<InputMap>().ok_or("no input map")?;
+    match args {
+        [] => Ok(Action::all()
+            .map(|action| map.describe(action))
+            .collect::<Vec<_>>()
+            .join("\n")),
+        [name] => {
+            let action = Action::parse(name).ok_or_else(|| format!("unknown action {name:?}"))?;
+            Ok(map.describe(action))
+        }
+        _ => Err("usage: bindings [action]".into()),
+    }
+}
+
+fn give_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [name] = args else {
+        return Err("usage: give block".into());
+    };
+    let block = Voxel::from_name(name).ok_or_else(|| format!("unknown block {name:?}"))?;
+    world.resource_mut::<HeldBlock>().0 = block;
+    Ok(match world.get_resource::<InputMap>() {
+        Some(map) => format!("holding {block} ({})", map.describe(Action::PlaceBlock)),
+        None => format!("holding {block}"),
+    })
+}
+
+fn fill_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    const USAGE: &str = "fill x1 y1 z1 x2 y2 z2 block";
+    let Some((name, corners)) = args.split_last() else {
+        return Err(format!("usage: {USAGE}"));
+    };
+    let [x1, y1, z1, x2, y2, z2] = parse_numbers::<i32, 6>(corners, USAGE)?;
+    let block = Voxel::from_name(name).ok_or_else(|| format!("unknown block {name:?}"))?;
+    let (a, b) = (IVec3::new(x1, y1, z1), IVec3::new(x2, y2, z2));
+    let (min, max) = (a.min(b), a.max(b));
+    let size = max - min + IVec3::ONE;
+    let volume = size.x as i64 * size.y as i64 * size.z as i64;
+    if volume > MAX_FILL_VOLUME as i64 {
+        return Err(format!("{volume} voxels is more than {MAX_FILL_VOLUME}"));
+    }
+    let changed = world
+        .resource_mut::<VoxelTerrain>()
+        .fill_box(min, max, block);
+    Ok(format!(
+        "filled {volume} voxels with {block} ({changed} changed)"
+    ))
+}
+
+fn screenshot_command(world: &mut World, _: &[&str]) -> Result<String, String> {
+    world.resource_scope(|world, mut manager: Mut<ScreenshotManager>| {
+        save_screenshot(world.resource::<CameraRegistry>(), &mut manager)
+    })
+}
+
+fn camera_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut state = SystemState::<(ResMut<CameraRegistry>, Query<&mut Camera>)>::new(world);
+    let (mut registry, mut cameras) = state.get_mut(world);
+    if registry.cameras.is_empty() {
+        return Err("no registered cameras".into());
+    }
+    match args {
+        ["next"] => registry.activate_next(&mut cameras),
+        [name] => {
+            let index = registry
+                .names
+                .iter()
+                .position(|candidate| candidate.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("no camera named {name:?}"))?;
+            registry.activate(index, &mut cameras);
+        }
+        _ => return Err("usage: camera next|NAME".into()),
+    }
+    Ok(format!("camera {}", registry.active_name()))
+}
+
+fn save_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let path = std::path::Path::new(args.first().copied().unwrap_or(DEFAULT_SAVE_PATH));
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let file = std::fs::File::create(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let mut out = std::io::BufWriter::new(file);
+    let terrain = world.resource::<VoxelTerrain>();
+    terrain
+        .world
+        .write_to(&mut out)
+        .and_then(|()| std::io::Write::flush(&mut out))
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(format!(
+        "saved {} chunks to {}",
+        terrain.world.chunk_count(),
+        path.display()
+    ))
+}
+
+fn load_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let path = args.first().copied().unwrap_or(DEFAULT_SAVE_PATH);
+    let file = std::fs::File::open(path).map_err(|err| format!("{path}: {err}"))?;
+    let loaded = VoxelWorld::read_from(&mut std::io::BufReader::new(file))
+        .map_err(|err| format!("{path}: {err}"))?;
+    let count = loaded.chunk_count();
+    let mut terrain = world.resource_mut::<VoxelTerrain>();
+    // Chunks only in the old world must remesh and remap too, to drop them.
+    let old: Vec<IVec3> = terrain.world.chunks().map(|(coord, _)| coord).collect();
+    *terrain = VoxelTerrain::new(loaded);
+    terrain
+        .unmapped
+        .extend(old.iter().map(|coord| IVec2::new(coord.x, coord.z)));
+    terrain.dirty.extend(old);
+    Ok(format!("loaded {count} chunks from {path}"))
+}
@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::math::IVec3;
+
+use crate::voxel::Voxel;
+use crate::world::{chunk_coord, chunks_near, local_coord, Chunk, VoxelWorld, CHUNK_SIZE};
+
+pub const MAX_LIGHT: u8 = 15;
+
+const DIRECTIONS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// Sunlight and block light packed into one byte (sun in the high nibble).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightLevel(pub u8);
+
+impl LightLevel {
+    pub const DARK: LightLevel = LightLevel(0);
+    pub const FULL_SUN: LightLevel = LightLevel(MAX_LIGHT << 4);
+
+    pub fn new(sun: u8, block: u8) -> Self {
+        Self((sun.min(MAX_LIGHT) << 4) | block.min(MAX_LIGHT))
+    }
+
+    pub fn sun(self) -> u8 {
+        self.0 >> 4
+    }
+
+    pub fn block(self) -> u8 {
+        self.0 & 0x0F
+    }
+
+    /// The brighter of each channel.
+    pub fn brighter(self, other: LightLevel) -> Self {
+        Self::new(self.sun().max(other.sun()), self.block().max(other.block()))
+    }
+
+    fn channel(self, channel: Channel) -> u8 {
+        match channel {
+            Channel::Sun => self.sun(),
+            Channel::Block => self.block(),
+        }
+    }
+
+    fn with_channel(self, channel: Channel, value: u8) -> Self {
+        match channel {
+            Channel::Sun => Self::new(value, self.block()),
+            Channel::Block => Self::new(self.sun(), value),
+        }
+    }
+
+    /// Linear RGB multiplier used when baking light into vertex colors.
+    ///
+    /// Each level below full dims by a constant factor; block light is tinted warm.
+    pub fn color(self) -> [f32; 3] {
+        let curve = |level: u8| 0.8f32.powi((MAX_LIGHT - level) as i32);
+        let sun = curve(self.sun());
+        let block = if self.block() == 0 {
+            0.0
+        } else {
+            curve(self.block())
+        };
+        let floor = 0.02;
+        [
+            sun.max(block).max(floor),
+            sun.max(block * 0.85).max(floor),
+            sun.max(block * 0.6).max(floor),
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Channel {
+    Sun,
+    Block,
+}
+
+#[derive(Clone)]
+enum ChunkLight {
+    Uniform(LightLevel),
+    Dense(Vec<LightLevel>),
+}
+
+/// Per-voxel light for a box of chunks.
+///
+/// Positions above the box read as open sky; positions below or beside it
+/// read as dark and sunlit respectively, and are never written.
+#[derive(Clone, Default)]
+pub struct LightField {
+    min: IVec3,
+    max: IVec3,
+    chunks: HashMap<IVec3, ChunkLight>,
+}
+
+impl LightField {
+    /// Computes sunlight and block light from scratch for chunks `min_chunk..=max_chunk`.
+    pub fn compute(world: &VoxelWorld, min_chunk: IVec3, max_chunk: IVec3) -> Self {
+        let size = CHUNK_SIZE as i32;
+        let mut field = Self {
+            min: min_chunk * size,
+            max: (max_chunk + IVec3::ONE) * size - IVec3::ONE,
+            chunks: HashMap::new(),
+        };
+
+        let width = (field.max.x - field.min.x + 1) as usize;
+        let depth = (field.max.z - field.min.z + 1) as usize;
+        // Lowest sky-lit y of every column; everything from there up sees the sky.
+        let mut sky_floor = vec![field.max.y + 1; width * depth];
+
+        for cz in min_chunk.z..=max_chunk.z {
+            for cx in min_chunk.x..=max_chunk.x {
+                let mut open = vec![true; CHUNK_SIZE * CHUNK_SIZE];
+                let mut floors = vec![field.max.y + 1; CHUNK_SIZE * CHUNK_SIZE];
+                for cy in (min_chunk.y..=max_chunk.y).rev() {
+                    let coord = IVec3::new(cx, cy, cz);
+                    let light = sky_pass(world.chunk(coord), cy * size, &mut open, &mut floors);
+                    field.chunks.insert(coord, light);
+                }
+                for (column, floor) in floors.into_iter().enumerate() {
+                    let x = cx * size + (column % CHUNK_SIZE) as i32 - field.min.x;
+                    let z = cz * size + (column / CHUNK_SIZE) as i32 - field.min.z;
+                    sky_floor[x as usize + z as usize * width] = floor;
+                }
+            }
+        }
+
+        // Sky light spills sideways wherever a neighbouring column is shaded
+        // below the height at which this column is still open.
+        let mut sun_queue = VecDeque::new();
+        for z in field.min.z..=field.max.z {
+            for x in field.min.x..=field.max.x {
+                let floor =
+                    sky_floor[(x - field.min.x) as usize + (z - field.min.z) as usize * width];
+                for dir in [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z] {
+                    let nx = x + dir.x;
+                    let nz = z + dir.z;
+                    if nx < field.min.x || nx > field.max.x || nz < field.min.z || nz > field.max.z
+                    {
+                        continue;
+                    }
+                    let neighbour_floor = sky_floor
+                        [(nx - field.min.x) as usize + (nz - field.min.z) as usize * width];
+                    for y in floor..neighbour_floor {
+                        if !world.get(IVec3::new(nx, y, nz)).is_opaque() {
+                            sun_queue.push_back(IVec3::new(x, y, z));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut block_queue = VecDeque::new();
+        for (coord, chunk) in world.chunks() {
+            if !field.chunks.contains_key(&coord) {
+                continue;
+            }
+            for (index, voxel) in chunk.voxels().enumerate() {
+                let emission = voxel.emission();
+                if emission == 0 {
+                    continue;
+                }
+                let (x, y, z) = Chunk::position(index);
+                let pos = coord * size + IVec3::new(x as i32, y as i32, z as i32);
+                let level = field.get(pos).with_channel(Channel::Block, emission);
+                field.set(pos, level);
+                block_queue.push_back(pos);
+            }
+        }
+
+        let mut touched = HashSet::new();
+        field.propagate(world, Channel::Sun, sun_queue, &mut touched);
+        field.propagate(world, Channel::Block, block_queue, &mut touched);
+        field
+    }
+
+    pub fn contains(&self, pos: IVec3) -> bool {
+        pos.cmpge(self.min).all() && pos.cmple(self.max).all()
+    }
+
+    pub fn get(&self, pos: IVec3) -> LightLevel {
+        if !self.contains(pos) {
+            return if pos.y < self.min.y {
+                LightLevel::DARK
+            } else {
+                LightLevel::FULL_SUN
+            };
+        }
+        match self.chunks.get(&chunk_coord(pos)) {
+            Some(ChunkLight::Uniform(level)) => *level,
+            Some(ChunkLight::Dense(levels)) => {
+                let local = local_coord(pos);
+                levels[Chunk::index(local.x, local.y, local.z)]
+            }
+            None => LightLevel::DARK,
+        }
+    }
+
+    fn set(&mut self, pos: IVec3, level: LightLevel) {
+        if !self.contains(pos) {
+            return;
+        }
+        let entry = self
+            .chunks
+            .entry(chunk_coord(pos))
+            .or_insert(ChunkLight::Uniform(LightLevel::DARK));
+        if let ChunkLight::Uniform(uniform) = *entry {
+            if uniform == level {
+                return;
+            }
+            *entry = ChunkLight::Dense(vec![uniform; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE]);
+        }
+        if let ChunkLight::Dense(levels) = entry {
+            let local = local_coord(pos);
+            levels[Chunk::index(local.x, local.y, local.z)] = level;
+        }
+    }
+
+    fn set_channel(
+        &mut self,
+        pos: IVec3,
+        channel: Channel,
+        value: u8,
+        touched: &mut HashSet<IVec3>,
+    ) {
+        let level = self.get(pos).with_channel(channel, value);
+        self.set(pos, level);
+        touched.extend(chunks_near(pos));
+    }
+
+    /// Re-lights the neighbourhood of `pos` after its voxel changed from `old`
+    /// to whatever `world` now holds there.
+    ///
+    /// Returns the chunks whose baked lighting may have changed.
+    pub fn update(&mut self, world: &VoxelWorld, pos: IVec3, old: Voxel) -> HashSet<IVec3> {
+        self.update_many(world, &[(pos, old)])
+    }
+
+    /// Like [`LightField::update`] for many changed voxels at once, each
+    /// with its previous voxel. The light is removed and spread again in a
+    /// single pass, so large edits cost about as much as the area they
+    /// relight.
+    pub fn update_many(
+        &mut self,
+        world: &VoxelWorld,
+        changes: &[(IVec3, Voxel)],
+    ) -> HashSet<IVec3> {
+        let mut touched = HashSet::new();
+        let changed: Vec<IVec3> = changes
+            .iter()
+            .filter(|&&(pos, old)| world.get(pos) != old && self.contains(pos))
+            .map(|&(pos, _)| pos)
+            .collect();
+        if changed.is_empty() {
+            return touched;
+        }
+
+        for channel in [Channel::Sun, Channel::Block] {
+            let mut relight = VecDeque::new();
+
+            let mut removal = VecDeque::new();
+            for &pos in &changed {
+                let current = self.get(pos).channel(channel);
+                if current > 0 {
+                    self.set_channel(pos, channel, 0, &mut touched);
+                    removal.push_back((pos, current));
+                }
+            }
+            self.remove(world, channel, removal, &mut relight, &mut touched);
+
+            for &pos in &changed {
+                let new = world.get(pos);
+                if channel == Channel::Block && new.emission() > 0 {
+                    self.set_channel(pos, channel, new.emission(), &mut touched);
+                    relight.push_back(pos);
+                }
+                if !new.is_opaque() {
+                    // Let the surroundings flow back into the opened cell.
+                    for dir in DIRECTIONS {
+                        let neighbour = pos + dir;
+                        if self.get(neighbour).channel(channel) > 0 {
+                            relight.push_back(neighbour);
+                        }
+                    }
+                }
+            }
+
+            self.propagate(world, channel, relight, &mut touched);
+        }
+        touched
+    }
+
+    /// Darkens everything lit (directly or indirectly) by the removed levels,
+    /// collecting the surviving light sources at the frontier into `relight`.
+    fn remove(
+        &mut self,
+        world: &VoxelWorld,
+        channel: Channel,
+        mut queue: VecDeque<(IVec3, u8)>,
+        relight: &mut VecDeque<IVec3>,
+        touched: &mut HashSet<IVec3>,
+    ) {
+        while let Some((pos, level)) = queue.pop_front() {
+            for dir in DIRECTIONS {
+                let neighbour = pos + dir;
+                if !self.contains(neighbour) {
+                    continue;
+                }
+                let neighbour_level = self.get(neighbour).channel(channel);
+                if neighbour_level == 0 {
+                    continue;
+                }
+                let sky_column = channel == Channel::Sun
+                    && dir == IVec3::NEG_Y
+                    && level == MAX_LIGHT
+                    && neighbour_level == MAX_LIGHT;
+                if neighbour_level < level || sky_column {
+                    let emission = world.get(neighbour).emission();
+                    if channel == Channel::Block && emission > 0 {
+                        self.set_channel(neighbour, channel, emission, touched);
+                        relight.push_back(neighbour);
+                    } else {
+                        self.set_channel(neighbour, channel, 0, touched);
+                        queue.push_back((neighbour, neighbour_level));
+                    }
+                } else {
+                    relight.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    /// Breadth-first flood fill from every position in `queue`.
+    fn propagate(
+        &mut self,
+        world: &VoxelWorld,
+        channel: Channel,
+        mut queue: VecDeque<IVec3>,
+        touched: &mut HashSet<IVec3>,
+    ) {
+        while let Some(pos) = queue.pop_front() {
+            let level = self.get(pos).channel(channel);
+            if level <= 1 {
+                continue;
+            }
+            for dir in DIRECTIONS {
+                let neighbour = pos + dir;
+                if !self.contains(neighbour) || world.get(neighbour).is_opaque() {
+                    continue;
+                }
+                // Unobstructed sunlight travels straight down without fading.
+                let spread = if channel == Channel::Sun && dir == IVec3::NEG_Y && level == MAX_LIGHT
+                {
+                    MAX_LIGHT
+                } else {
+                    level - 1
+                };
+                if self.get(neighbour).channel(channel) < spread {
+                    self.set_channel(neighbour, channel, spread, touched);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+}
+
+/// Lights the still-open sky columns of one chunk, closing columns at the
+/// first opaque voxel and recording the lowest lit y of each column in
+/// `floors`. Fully open or fully closed chunks stay uniform.
+fn sky_pass(
+    chunk: Option<&Chunk>,
+    base_y: i32,
+    open: &mut [bool],
+    floors: &mut [i32],
+) -> ChunkLight {
+    let open_count = open.iter().filter(|&&o| o).count();
+    if open_count == 0 {
+        return ChunkLight::Uniform(LightLevel::DARK);
+    }
+    if chunk.is_none() && open_count == open.len() {
+        floors.fill(base_y);
+        return ChunkLight::Uniform(LightLevel::FULL_SUN);
+    }
+
+    let mut levels = vec![LightLevel::DARK; CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE];
+    for (column, is_open) in open.iter_mut().enumerate() {
+        if !*is_open {
+            continue;
+        }
+        let (x, z) = ((column % CHUNK_SIZE) as u32, (column / CHUNK_SIZE) as u32);
+        floors[column] = base_y;
+        for y in (0..CHUNK_SIZE as u32).rev() {
+            if chunk.is_some_and(|c| c.get(x, y, z).is_opaque()) {
+                *is_open = false;
+                floors[column] = base_y + y as i32 + 1;
+                break;
+            }
+            levels[Chunk::index(x, y, z)] = LightLevel::FULL_SUN;
+        }
+    }
+    ChunkLight::Dense(levels)
+}
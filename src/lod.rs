@@ -0,0 +1,169 @@
+use bevy::math::{IVec3, Vec3};
+use bevy::prelude::Resource;
+
+use crate::lighting::LightLevel;
+use crate::meshing::VoxelSource;
+use crate::voxel::Voxel;
+use crate::world::CHUNK_SIZE;
+
+/// How coarsely a chunk is meshed: level `n` merges `2^n` voxels along each
+/// axis into one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LodLevel(u8);
+
+impl LodLevel {
+    /// Full resolution.
+    pub const FULL: LodLevel = LodLevel(0);
+    /// 8× voxels.
+    pub const COARSEST: LodLevel = LodLevel(3);
+
+    /// Level `level`, capped at [`LodLevel::COARSEST`].
+    pub fn new(level: u8) -> Self {
+        Self(level.min(Self::COARSEST.0))
+    }
+
+    pub fn level(self) -> u8 {
+        self.0
+    }
+
+    /// Edge length in voxels of one downsampled cell.
+    pub fn scale(self) -> u32 {
+        1 << self.0
+    }
+}
+
+/// Camera distances at which chunks switch level of detail.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct LodSettings {
+    /// Distance beyond which chunks use 2×, 4× and 8× voxels.
+    pub distances: [f32; 3],
+    /// How far past a threshold the camera must be before a chunk switches,
+    /// so chunks on a boundary do not flicker between levels.
+    pub hysteresis: f32,
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            distances: [96.0, 160.0, 224.0],
+            hysteresis: 8.0,
+        }
+    }
+}
+
+impl LodSettings {
+    /// The level a chunk at `distance` should use, given the level it has
+    /// now. A chunk only coarsens once it is `hysteresis` past a threshold
+    /// and only refines once it is `hysteresis` inside it.
+    pub fn select(&self, current: LodLevel, distance: f32) -> LodLevel {
+        let mut level = current.level() as usize;
+        while level < self.distances.len() && distance > self.distances[level] + self.hysteresis {
+            level += 1;
+        }
+        while level > 0 && distance < self.distances[level - 1] - self.hysteresis {
+            level -= 1;
+        }
+        LodLevel::new(level as u8)
+    }
+}
+
+/// Distance from `point` to the nearest point of the chunk at `coord`.
+pub fn chunk_distance(coord: IVec3, point: Vec3) -> f32 {
+    let min = (coord * CHUNK_SIZE as i32).as_vec3();
+    let max = min + Vec3::splat(CHUNK_SIZE as f32);
+    point.clamp(min, max).distance(point)
+}
+
+/// A chunk's voxels merged into cells of `scale`³ voxels, plus one ring of
+/// merged cells from the neighbouring chunks.
+///
+/// Coordinates are in merged cells, `0..extent` inside the chunk. A cell is
+/// filled with its most common block when at least half of its voxels are
+/// solid or water, so surfaces stay roughly in place; loose plants never
+/// fill a cell. Each cell carries the brightest light of its see-through
+/// voxels.
+pub struct Downsampled {
+    extent: i32,
+    voxels: Vec<Voxel>,
+    light: Vec<LightLevel>,
+}
+
+impl Downsampled {
+    pub fn new(source: &impl VoxelSource, lod: LodLevel) -> Self {
+        let scale = lod.scale() as i32;
+        let extent = CHUNK_SIZE as i32 / scale;
+        let side = (extent + 2) as usize;
+        let mut voxels = Vec::with_capacity(side * side * side);
+        let mut light = Vec::with_capacity(side * side * side);
+        let volume = scale * scale * scale;
+        let mut counts: Vec<(Voxel, i32)> = Vec::new();
+        for z in -1..=extent {
+            for y in -1..=extent {
+                for x in -1..=extent {
+                    counts.clear();
+                    let mut brightest = LightLevel::DARK;
+                    for dz in 0..scale {
+                        for dy in 0..scale {
+                            for dx in 0..scale {
+                                let (fx, fy, fz) = (x * scale + dx, y * scale + dy, z * scale + dz);
+                                let voxel = source.voxel(fx, fy, fz);
+                                if !voxel.is_opaque() {
+                                    brightest = brightest.brighter(source.light(fx, fy, fz));
+                                }
+                                if !voxel.is_solid() && !voxel.is_water() {
+                                    continue;
+                                }
+                                match counts.iter_mut().find(|(v, _)| *v == voxel) {
+                                    Some((_, count)) => *count += 1,
+                                    None => counts.push((voxel, 1)),
+                                }
+                            }
+                        }
+                    }
+                    let filled: i32 = counts.iter().map(|(_, count)| count).sum();
+                    let voxel = if filled * 2 >= volume {
+                        counts
+                            .iter()
+                            .max_by_key(|(_, count)| *count)
+                            .map_or(Voxel::AIR, |(voxel, _)| *voxel)
+                    } else {
+                        Voxel::AIR
+                    };
+                    voxels.push(voxel);
+                    light.push(brightest);
+                }
+            }
+        }
+        Self {
+            extent,
+            voxels,
+            light,
+        }
+    }
+
+    /// Merged cells along each axis of the chunk.
+    pub fn extent(&self) -> i32 {
+        self.extent
+    }
+
+    fn index(&self, x: i32, y: i32, z: i32) -> Option<usize> {
+        let range = -1..=self.extent;
+        if !range.contains(&x) || !range.contains(&y) || !range.contains(&z) {
+            return None;
+        }
+        let side = self.extent + 2;
+        Some(((x + 1) + (y + 1) * side + (z + 1) * side * side) as usize)
+    }
+}
+
+impl VoxelSource for Downsampled {
+    fn voxel(&self, x: i32, y: i32, z: i32) -> Voxel {
+        self.index(x, y, z)
+            .map_or(Voxel::AIR, |index| self.voxels[index])
+    }
+
+    fn light(&self, x: i32, y: i32, z: i32) -> LightLevel {
+        self.index(x, y, z)
+            .map_or(LightLevel::FULL_SUN, |index| self.light[index])
+    }
+}
@@ -0,0 +1,514 @@
+mod shapes;
+mod smooth;
+
+use bevy::math::{IVec3, Vec3};
+use bevy::prelude::Resource;
+
+use crate::lighting::{LightField, LightLevel};
+use crate::lod::{Downsampled, LodLevel};
+use crate::voxel::{BlockMaterial, BlockShape, Transparency, Voxel};
+use crate::world::{Chunk, VoxelWorld, CHUNK_SIZE};
+
+#[derive(Default, Clone)]
+pub struct SurfaceMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    /// Linear RGBA vertex colors carrying baked voxel light.
+    pub colors: Vec<[f32; 4]>,
+    /// Per-vertex ambient occlusion brightness (1.0 = unoccluded).
+    pub ao: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+impl SurfaceMesh {
+    /// Adds `other`'s triangles to this mesh.
+    pub fn append(&mut self, other: SurfaceMesh) {
+        let base = self.positions.len() as u32;
+        self.positions.extend(other.positions);
+        self.normals.extend(other.normals);
+        self.uvs.extend(other.uvs);
+        self.colors.extend(other.colors);
+        self.ao.extend(other.ao);
+        self.indices
+            .extend(other.indices.iter().map(|index| base + index));
+    }
+}
+
+/// How a world's chunks are turned into meshes.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshStyle {
+    /// One cube per voxel.
+    #[default]
+    Blocky,
+    /// Surface nets over opaque voxels; see [`mesh_smooth`]. See-through
+    /// voxels such as water and leaves stay blocky.
+    Smooth,
+}
+
+impl MeshStyle {
+    /// Reads `--mesh blocky|smooth` from the command line.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut style = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--mesh" {
+                let raw = args.next().ok_or("--mesh expects a value")?;
+                style = match raw.as_str() {
+                    "blocky" => MeshStyle::Blocky,
+                    "smooth" => MeshStyle::Smooth,
+                    _ => return Err(format!("unknown mesh style {raw:?}")),
+                };
+            }
+        }
+        Ok(style)
+    }
+}
+
+/// Voxel and light data around the chunk being meshed.
+///
+/// Coordinates are chunk-local and may step one voxel outside the chunk so
+/// faces on the border can see their neighbours.
+pub trait VoxelSource {
+    fn voxel(&self, x: i32, y: i32, z: i32) -> Voxel;
+
+    fn light(&self, _x: i32, _y: i32, _z: i32) -> LightLevel {
+        LightLevel::FULL_SUN
+    }
+}
+
+/// A lone chunk: everything outside it is open, fully lit air.
+impl VoxelSource for Chunk {
+    fn voxel(&self, x: i32, y: i32, z: i32) -> Voxel {
+        if x < 0 || y < 0 || z < 0 {
+            return Voxel::AIR;
+        }
+        self.get(x as u32, y as u32, z as u32)
+    }
+}
+
+/// A chunk together with its 26 neighbours and optional baked light.
+pub struct ChunkNeighborhood<'a> {
+    chunks: [Option<&'a Chunk>; 27],
+    light: Option<&'a LightField>,
+    origin: IVec3,
+}
+
+impl<'a> ChunkNeighborhood<'a> {
+    pub fn new(world: &'a VoxelWorld, coord: IVec3, light: Option<&'a LightField>) -> Self {
+        let mut chunks = [None; 27];
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let slot = ((dx + 1) + (dy + 1) * 3 + (dz + 1) * 9) as usize;
+                    chunks[slot] = world.chunk(coord + IVec3::new(dx, dy, dz));
+                }
+            }
+        }
+        Self {
+            chunks,
+            light,
+            origin: coord * CHUNK_SIZE as i32,
+        }
+    }
+
+    pub fn center(&self) -> Option<&'a Chunk> {
+        self.chunks[13]
+    }
+}
+
+impl VoxelSource for ChunkNeighborhood<'_> {
+    fn voxel(&self, x: i32, y: i32, z: i32) -> Voxel {
+        let size = CHUNK_SIZE as i32;
+        let split = |v: i32| (v.div_euclid(size), v.rem_euclid(size) as u32);
+        let (cx, lx) = split(x);
+        let (cy, ly) = split(y);
+        let (cz, lz) = split(z);
+        if !(-1..=1).contains(&cx) || !(-1..=1).contains(&cy) || !(-1..=1).contains(&cz) {
+            return Voxel::AIR;
+        }
+        let slot = ((cx + 1) + (cy + 1) * 3 + (cz + 1) * 9) as usize;
+        match self.chunks[slot] {
+            Some(chunk) => chunk.get(lx, ly, lz),
+            None => Voxel::AIR,
+        }
+    }
+
+    fn light(&self, x: i32, y: i32, z: i32) -> LightLevel {
+        match self.light {
+            Some(light) => light.get(self.origin + IVec3::new(x, y, z)),
+            None => LightLevel::FULL_SUN,
+        }
+    }
+}
+
+pub fn mesh_chunk(chunk: &Chunk) -> SurfaceMesh {
+    mesh_voxels(chunk)
+}
+
+/// Meshes the chunk-local region `0..CHUNK_SIZE` of `source`, emitting a
+/// face wherever a voxel borders air or a see-through voxel of another
+/// material, with water tops lowered by level. Each face is colored by the
+/// light of the cell it faces and each vertex carries ambient occlusion from
+/// the voxels around it, including voxels in neighbouring chunks.
+pub fn mesh_voxels(source: &impl VoxelSource) -> SurfaceMesh {
+    let mut mesh = SurfaceMesh::default();
+    for_each_filled_cell(source, CHUNK_SIZE as i32, |x, y, z, _| {
+        mesh_cell(&mut mesh, source, x, y, z)
+    });
+    mesh
+}
+
+/// Like [`mesh_voxels`], but splits faces by block material. Only materials
+/// with visible faces are returned.
+pub fn mesh_voxel_layers(source: &impl VoxelSource) -> Vec<(BlockMaterial, SurfaceMesh)> {
+    mesh_layers(source, CHUNK_SIZE as i32, MeshStyle::Blocky, false)
+}
+
+/// Smooth counterpart of [`mesh_voxels`]: opaque voxels are meshed with
+/// surface nets, giving a continuous surface with smooth normals, while
+/// see-through voxels keep their cube faces. The surface passes through the
+/// middle of every face a blocky mesh would show, so flat ground stays at
+/// the same height.
+pub fn mesh_smooth(source: &impl VoxelSource) -> SurfaceMesh {
+    let mut mesh = SurfaceMesh::default();
+    for (_, layer) in mesh_smooth_layers(source) {
+        mesh.append(layer);
+    }
+    mesh
+}
+
+/// Like [`mesh_smooth`], split by block material.
+pub fn mesh_smooth_layers(source: &impl VoxelSource) -> Vec<(BlockMaterial, SurfaceMesh)> {
+    mesh_layers(source, CHUNK_SIZE as i32, MeshStyle::Smooth, false)
+}
+
+/// Like [`mesh_voxel_layers`] or [`mesh_smooth_layers`], but merges voxels into cells of
+/// [`LodLevel::scale`] voxels first (see [`Downsampled`]). Positions stay in
+/// chunk-local voxel units.
+///
+/// With `skirts`, opaque cells on the chunk's sides also show their hidden
+/// side faces for [`SKIRT_DEPTH`] cells below any open cell. Neighbouring
+/// chunks at another level put their surfaces at slightly different heights,
+/// and the skirts hang down over the cracks between them as long as the
+/// levels differ by at most one.
+pub fn mesh_lod_layers(
+    source: &impl VoxelSource,
+    lod: LodLevel,
+    style: MeshStyle,
+    skirts: bool,
+) -> Vec<(BlockMaterial, SurfaceMesh)> {
+    if lod == LodLevel::FULL {
+        return mesh_layers(source, CHUNK_SIZE as i32, style, skirts);
+    }
+    let merged = Downsampled::new(source, lod);
+    let mut layers = mesh_layers(&merged, merged.extent(), style, skirts);
+    let scale = lod.scale() as f32;
+    for (_, mesh) in &mut layers {
+        for position in &mut mesh.positions {
+            *position = position.map(|v| v * scale);
+        }
+    }
+    layers
+}
+
+/// Cells of skirt hung below open cells on a chunk's sides.
+pub const SKIRT_DEPTH: i32 = 2;
+
+fn mesh_layers(
+    source: &impl VoxelSource,
+    extent: i32,
+    style: MeshStyle,
+    skirts: bool,
+) -> Vec<(BlockMaterial, SurfaceMesh)> {
+    let mut layers: Vec<(BlockMaterial, SurfaceMesh)> = match style {
+        MeshStyle::Blocky => BlockMaterial::ALL
+            .iter()
+            .map(|&material| (material, SurfaceMesh::default()))
+            .collect(),
+        MeshStyle::Smooth => smooth::smooth_layers(source, extent),
+    };
+    for_each_filled_cell(source, extent, |x, y, z, voxel| {
+        let slot = BlockMaterial::ALL
+            .iter()
+            .position(|&material| material == voxel.material())
+            .unwrap_or(0);
+        if style == MeshStyle::Blocky || !voxel.is_opaque() {
+            mesh_cell(&mut layers[slot].1, source, x, y, z);
+        }
+        if skirts {
+            push_skirts(&mut layers[slot].1, source, extent, IVec3::new(x, y, z));
+        }
+    });
+    layers.retain(|(_, mesh)| !mesh.indices.is_empty());
+    layers
+}
+
+fn for_each_filled_cell(
+    source: &impl VoxelSource,
+    n: i32,
+    mut f: impl FnMut(i32, i32, i32, Voxel),
+) {
+    for z in 0..n {
+        for y in 0..n {
+            for x in 0..n {
+                let v = source.voxel(x, y, z);
+                if !v.is_empty() {
+                    f(x, y, z, v);
+                }
+            }
+        }
+    }
+}
+
+/// Whether the face of `voxel` towards `direction`, where `neighbour`
+/// lies, can be seen. Opaque neighbours hide the face where their shape
+/// covers it and cutout neighbours never do; translucent neighbours hide it
+/// only between voxels of the same material, so glass walls and bodies of
+/// water show just their outside.
+fn face_visible(voxel: Voxel, neighbour: Voxel, direction: IVec3) -> bool {
+    if neighbour.is_empty() {
+        return true;
+    }
+    match neighbour.transparency() {
+        Transparency::Opaque => !neighbour.shape().covers(-direction),
+        Transparency::Cutout => true,
+        Transparency::Translucent => voxel.material() != neighbour.material(),
+    }
+}
+
+/// Surface height of a water voxel within its cell. Sources sit a little
+/// below the top, flowing water drops with its level, and water with water
+/// above or falling water fills the whole cell.
+fn water_height(voxel: Voxel, above: Voxel) -> f32 {
+    match voxel.water_level() {
+        Some(_) if above.is_water() || voxel.is_falling_water() => 1.0,
+        Some(level) => (8 - level) as f32 / 9.0,
+        None => 1.0,
+    }
+}
+
+fn mesh_cell(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let v = source.voxel(x, y, z);
+    if v.shape() != BlockShape::Cube {
+        shapes::mesh_shape(mesh, source, IVec3::new(x, y, z));
+        return;
+    }
+    let first_vertex = mesh.positions.len();
+    if face_visible(v, source.voxel(x - 1, y, z), IVec3::NEG_X) {
+        push_face_neg_x(mesh, source, x, y, z);
+    }
+    if face_visible(v, source.voxel(x + 1, y, z), IVec3::X) {
+        push_face_pos_x(mesh, source, x, y, z);
+    }
+    if face_visible(v, source.voxel(x, y - 1, z), IVec3::NEG_Y) {
+        push_face_neg_y(mesh, source, x, y, z);
+    }
+    let above = source.voxel(x, y + 1, z);
+    let height = water_height(v, above);
+    // A lowered water surface shows even under a solid block.
+    if face_visible(v, above, IVec3::Y) || (height < 1.0 && !above.is_water()) {
+        push_face_pos_y(mesh, source, x, y, z);
+    }
+    if face_visible(v, source.voxel(x, y, z - 1), IVec3::NEG_Z) {
+        push_face_neg_z(mesh, source, x, y, z);
+    }
+    if face_visible(v, source.voxel(x, y, z + 1), IVec3::Z) {
+        push_face_pos_z(mesh, source, x, y, z);
+    }
+    if height < 1.0 {
+        let top = (y + 1) as f32;
+        for position in &mut mesh.positions[first_vertex..] {
+            if position[1] == top {
+                position[1] = y as f32 + height;
+            }
+        }
+    }
+}
+
+type PushFace<S> = fn(&mut SurfaceMesh, &S, i32, i32, i32);
+
+/// Side faces of an opaque border cell that the mesher hid, lit like the open
+/// cell above them and without occlusion.
+fn push_skirts<S: VoxelSource>(mesh: &mut SurfaceMesh, source: &S, extent: i32, cell: IVec3) {
+    let voxel = source.voxel(cell.x, cell.y, cell.z);
+    if !voxel.is_opaque() {
+        return;
+    }
+    let Some(open) = (1..=SKIRT_DEPTH)
+        .map(|up| cell + IVec3::Y * up)
+        .find(|open| !source.voxel(open.x, open.y, open.z).is_opaque())
+    else {
+        return;
+    };
+    let color = face_color(source, open);
+    let sides: [(bool, IVec3, PushFace<S>); 4] = [
+        (cell.x == 0, IVec3::NEG_X, push_face_neg_x),
+        (cell.x == extent - 1, IVec3::X, push_face_pos_x),
+        (cell.z == 0, IVec3::NEG_Z, push_face_neg_z),
+        (cell.z == extent - 1, IVec3::Z, push_face_pos_z),
+    ];
+    for (on_side, direction, push) in sides {
+        let beyond = cell + direction;
+        if !on_side || face_visible(voxel, source.voxel(beyond.x, beyond.y, beyond.z), direction) {
+            continue;
+        }
+        let first_vertex = mesh.positions.len();
+        push(mesh, source, cell.x, cell.y, cell.z);
+        mesh.colors[first_vertex..].fill(color);
+        mesh.ao[first_vertex..].fill(1.0);
+    }
+}
+
+fn face_color(source: &impl VoxelSource, cell: IVec3) -> [f32; 4] {
+    let [r, g, b] = source.light(cell.x, cell.y, cell.z).color();
+    [r, g, b, 1.0]
+}
+
+/// Brightness for 0..=3 occluding neighbours around a vertex.
+const AO_CURVE: [f32; 4] = [1.0, 0.8, 0.65, 0.5];
+
+fn occludes(source: &impl VoxelSource, cell: IVec3) -> bool {
+    source.voxel(cell.x, cell.y, cell.z).is_opaque()
+}
+
+/// Classic voxel ambient occlusion: for each vertex of the face between
+/// `cell` and `cell + normal`, count the two side neighbours and the corner
+/// neighbour in the layer the face looks into. Two sides fully occlude the
+/// corner regardless of the corner voxel.
+fn vertex_ao(
+    source: &impl VoxelSource,
+    cell: IVec3,
+    normal: IVec3,
+    verts: &[[f32; 3]; 4],
+) -> [f32; 4] {
+    let front = cell + normal;
+    let center = cell.as_vec3() + Vec3::splat(0.5) + normal.as_vec3() * 0.5;
+    let tangents: Vec<usize> = (0..3).filter(|&axis| normal[axis] == 0).collect();
+    let (u, v) = (tangents[0], tangents[1]);
+
+    verts.map(|vert| {
+        let offset = Vec3::from(vert) - center;
+        let mut side_u = IVec3::ZERO;
+        side_u[u] = offset[u].signum() as i32;
+        let mut side_v = IVec3::ZERO;
+        side_v[v] = offset[v].signum() as i32;
+
+        let a = occludes(source, front + side_u);
+        let b = occludes(source, front + side_v);
+        let corner = occludes(source, front + side_u + side_v);
+        let count = if a && b {
+            3
+        } else {
+            a as usize + b as usize + corner as usize
+        };
+        AO_CURVE[count]
+    })
+}
+
+fn push_quad(
+    mesh: &mut SurfaceMesh,
+    source: &impl VoxelSource,
+    cell: IVec3,
+    verts: [[f32; 3]; 4],
+    normal: IVec3,
+) {
+    let color = face_color(source, cell + normal);
+    let ao = vertex_ao(source, cell, normal, &verts);
+
+    let base = mesh.positions.len() as u32;
+    mesh.positions.extend_from_slice(&verts);
+    mesh.normals
+        .extend_from_slice(&[normal.as_vec3().to_array(); 4]);
+    mesh.colors.extend_from_slice(&[color; 4]);
+    mesh.ao.extend_from_slice(&ao);
+    mesh.uvs
+        .extend_from_slice(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+    // Split along the brighter diagonal so the occlusion gradient stays
+    // symmetric instead of showing the triangle seam.
+    if ao[0] + ao[2] < ao[1] + ao[3] {
+        mesh.indices
+            .extend_from_slice(&[base + 1, base + 2, base + 3, base + 1, base + 3, base]);
+    } else {
+        mesh.indices
+            .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+}
+
+fn push_face_neg_x(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let x0 = x as f32;
+    let y0 = y as f32;
+    let z0 = z as f32;
+    let verts = [
+        [x0, y0, z0],
+        [x0, y0 + 1.0, z0],
+        [x0, y0 + 1.0, z0 + 1.0],
+        [x0, y0, z0 + 1.0],
+    ];
+    push_quad(mesh, source, IVec3::new(x, y, z), verts, IVec3::NEG_X);
+}
+
+fn push_face_pos_x(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let x1 = x as f32 + 1.0;
+    let y0 = y as f32;
+    let z0 = z as f32;
+    let verts = [
+        [x1, y0, z0],
+        [x1, y0, z0 + 1.0],
+        [x1, y0 + 1.0, z0 + 1.0],
+        [x1, y0 + 1.0, z0],
+    ];
+    push_quad(mesh, source, IVec3::new(x, y, z), verts, IVec3::X);
+}
+
+fn push_face_neg_y(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let x0 = x as f32;
+    let y0 = y as f32;
+    let z0 = z as f32;
+    let verts = [
+        [x0, y0, z0],
+        [x0, y0, z0 + 1.0],
+        [x0 + 1.0, y0, z0 + 1.0],
+        [x0 + 1.0, y0, z0],
+    ];
+    push_quad(mesh, source, IVec3::new(x, y, z), verts, IVec3::NEG_Y);
+}
+
+fn push_face_pos_y(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let x0 = x as f32;
+    let y1 = y as f32 + 1.0;
+    let z0 = z as f32;
+    let verts = [
+        [x0, y1, z0],
+        [x0 + 1.0, y1, z0],
+        [x0 + 1.0, y1, z0 + 1.0],
+        [x0, y1, z0 + 1.0],
+    ];
+    push_quad(mesh, source, IVec3::new(x, y, z), verts, IVec3::Y);
+}
+
+fn push_face_neg_z(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let x0 = x as f32;
+    let y0 = y as f32;
+    let z0 = z as f32;
+    let verts = [
+        [x0, y0, z0],
+        [x0 + 1.0, y0, z0],
+        [x0 + 1.0, y0 + 1.0, z0],
+        [x0, y0 + 1.0, z0],
+    ];
+    push_quad(mesh, source, IVec3::new(x, y, z), verts, IVec3::NEG_Z);
+}
+
+fn push_face_pos_z(mesh: &mut SurfaceMesh, source: &impl VoxelSource, x: i32, y: i32, z: i32) {
+    let x0 = x as f32;
+    let y0 = y as f32;
+    let z1 = z as f32 + 1.0;
+    let verts = [
+        [x0, y0, z1],
+        [x0, y0 + 1.0, z1],
+        [x0 + 1.0, y0 + 1.0, z1],
+        [x0 + 1.0, y0, z1],
+    ];
+    push_quad(mesh, source, IVec3::new(x, y, z), verts, IVec3::Z);
+}
@@ -0,0 +1,231 @@
+use bevy::math::{IVec3, Vec3};
+
+use super::{face_color, face_visible, SurfaceMesh, VoxelSource};
+use crate::voxel::BlockShape;
+
+/// Unit cube faces with their outward normals, wound like the cube faces of
+/// the blocky mesher.
+const FACES: [(IVec3, [[f32; 3]; 4]); 6] = [
+    (
+        IVec3::NEG_X,
+        [
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 1.0, 1.0],
+            [0.0, 0.0, 1.0],
+        ],
+    ),
+    (
+        IVec3::X,
+        [
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [1.0, 1.0, 0.0],
+        ],
+    ),
+    (
+        IVec3::NEG_Y,
+        [
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 0.0, 0.0],
+        ],
+    ),
+    (
+        IVec3::Y,
+        [
+            [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ],
+    ),
+    (
+        IVec3::NEG_Z,
+        [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ],
+    ),
+    (
+        IVec3::Z,
+        [
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [1.0, 0.0, 1.0],
+        ],
+    ),
+];
+
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+
+/// Heights of the two bars between fence posts.
+const FENCE_BARS: [(f32, f32); 2] = [(0.375, 0.5625), (0.75, 0.9375)];
+
+/// Meshes the non-cube voxel at `cell`.
+pub(super) fn mesh_shape(mesh: &mut SurfaceMesh, source: &impl VoxelSource, cell: IVec3) {
+    let shape = source.voxel(cell.x, cell.y, cell.z).shape();
+    match shape {
+        BlockShape::Cross => push_cross(mesh, source, cell),
+        BlockShape::Stairs(facing) => push_stairs(mesh, source, cell, facing),
+        _ => {
+            for (min, max) in boxes(source, cell, shape) {
+                push_box(mesh, source, cell, min, max, None);
+            }
+        }
+    }
+}
+
+/// Cell-local boxes `shape` is built from at `cell`.
+fn boxes(source: &impl VoxelSource, cell: IVec3, shape: BlockShape) -> Vec<(Vec3, Vec3)> {
+    match shape {
+        BlockShape::Cube | BlockShape::Cross | BlockShape::Stairs(_) => {
+            vec![(Vec3::ZERO, Vec3::ONE)]
+        }
+        BlockShape::Slab { top: false } => vec![(Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0))],
+        BlockShape::Slab { top: true } => vec![(Vec3::new(0.0, 0.5, 0.0), Vec3::ONE)],
+        BlockShape::Fence => {
+            let mut boxes = vec![(Vec3::new(0.375, 0.0, 0.375), Vec3::new(0.625, 1.0, 0.625))];
+            for side in HORIZONTAL {
+                let next = cell + side;
+                let neighbour = source.voxel(next.x, next.y, next.z);
+                if neighbour.shape() != BlockShape::Fence && !neighbour.is_opaque() {
+                    continue;
+                }
+                let (along, across) = if side.x != 0 { (0, 2) } else { (2, 0) };
+                let (start, end) = if side[along] > 0 {
+                    (0.625, 1.0)
+                } else {
+                    (0.0, 0.375)
+                };
+                for (bottom, top) in FENCE_BARS {
+                    let mut min = Vec3::new(0.0, bottom, 0.0);
+                    let mut max = Vec3::new(0.0, top, 0.0);
+                    min[along] = start;
+                    max[along] = end;
+                    min[across] = 0.4375;
+                    max[across] = 0.5625;
+                    boxes.push((min, max));
+                }
+            }
+            boxes
+        }
+    }
+}
+
+/// Faces of a box inside `cell`, leaving out the face towards `hidden`.
+/// Faces on the cell's sides are hidden by neighbours like cube faces;
+/// faces inside the cell always show and take the cell's own light.
+fn push_box(
+    mesh: &mut SurfaceMesh,
+    source: &impl VoxelSource,
+    cell: IVec3,
+    min: Vec3,
+    max: Vec3,
+    hidden: Option<IVec3>,
+) {
+    for (normal, _) in FACES {
+        if Some(normal) != hidden {
+            push_face(mesh, source, cell, min, max, normal);
+        }
+    }
+}
+
+/// The face of a box inside `cell` pointing along `normal`.
+fn push_face(
+    mesh: &mut SurfaceMesh,
+    source: &impl VoxelSource,
+    cell: IVec3,
+    min: Vec3,
+    max: Vec3,
+    normal: IVec3,
+) {
+    let Some((_, corners)) = FACES.into_iter().find(|&(face, _)| face == normal) else {
+        return;
+    };
+    let voxel = source.voxel(cell.x, cell.y, cell.z);
+    let axis = (0..3).find(|&axis| normal[axis] != 0).unwrap_or(0);
+    let on_side = if normal[axis] > 0 {
+        max[axis] == 1.0
+    } else {
+        min[axis] == 0.0
+    };
+    let lit = if on_side {
+        let next = cell + normal;
+        if !face_visible(voxel, source.voxel(next.x, next.y, next.z), normal) {
+            return;
+        }
+        next
+    } else {
+        cell
+    };
+    let verts =
+        corners.map(|corner| (cell.as_vec3() + min + Vec3::from(corner) * (max - min)).to_array());
+    push_flat_quad(mesh, verts, normal.as_vec3(), face_color(source, lit));
+}
+
+/// A bottom slab with a half-depth step on its `facing` side. Where the step
+/// sits on the slab neither the slab's top nor the step's bottom is meshed.
+fn push_stairs(mesh: &mut SurfaceMesh, source: &impl VoxelSource, cell: IVec3, facing: IVec3) {
+    let slab = Vec3::new(1.0, 0.5, 1.0);
+    push_box(mesh, source, cell, Vec3::ZERO, slab, Some(IVec3::Y));
+
+    let axis = if facing.x != 0 { 0 } else { 2 };
+    let (mut step_min, mut step_max) = (Vec3::new(0.0, 0.5, 0.0), Vec3::ONE);
+    let (mut tread_min, mut tread_max) = (Vec3::ZERO, slab);
+    if facing[axis] > 0 {
+        step_min[axis] = 0.5;
+        tread_max[axis] = 0.5;
+    } else {
+        step_max[axis] = 0.5;
+        tread_min[axis] = 0.5;
+    }
+    push_face(mesh, source, cell, tread_min, tread_max, IVec3::Y);
+    push_box(mesh, source, cell, step_min, step_max, Some(IVec3::NEG_Y));
+}
+
+/// Two quads crossing diagonally through the cell.
+fn push_cross(mesh: &mut SurfaceMesh, source: &impl VoxelSource, cell: IVec3) {
+    let color = face_color(source, cell);
+    let quads = [
+        (
+            [
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 1.0],
+                [1.0, 1.0, 1.0],
+                [0.0, 1.0, 0.0],
+            ],
+            Vec3::new(1.0, 0.0, -1.0),
+        ),
+        (
+            [
+                [1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0],
+                [0.0, 1.0, 1.0],
+                [1.0, 1.0, 0.0],
+            ],
+            Vec3::new(1.0, 0.0, 1.0),
+        ),
+    ];
+    for (corners, normal) in quads {
+        let verts = corners.map(|corner| (cell.as_vec3() + Vec3::from(corner)).to_array());
+        push_flat_quad(mesh, verts, normal.normalize(), color);
+    }
+}
+
+fn push_flat_quad(mesh: &mut SurfaceMesh, verts: [[f32; 3]; 4], normal: Vec3, color: [f32; 4]) {
+    let base = mesh.positions.len() as u32;
+    mesh.positions.extend_from_slice(&verts);
+    mesh.normals.extend_from_slice(&[normal.to_array(); 4]);
+    mesh.colors.extend_from_slice(&[color; 4]);
+    mesh.ao.extend_from_slice(&[1.0; 4]);
+    mesh.uvs
+        .extend_from_slice(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+    mesh.indices
+        .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+}
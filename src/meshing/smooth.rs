@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec3, Vec3};
+
+use super::{SurfaceMesh, VoxelSource, AO_CURVE};
+use crate::lighting::LightLevel;
+use crate::voxel::BlockMaterial;
+
+/// Corners of a dual cell as offsets from its lowest voxel.
+const CORNERS: [IVec3; 8] = [
+    IVec3::new(0, 0, 0),
+    IVec3::new(1, 0, 0),
+    IVec3::new(0, 1, 0),
+    IVec3::new(1, 1, 0),
+    IVec3::new(0, 0, 1),
+    IVec3::new(1, 0, 1),
+    IVec3::new(0, 1, 1),
+    IVec3::new(1, 1, 1),
+];
+
+/// The twelve edges of a dual cell, as pairs of [`CORNERS`] indices.
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+const STEPS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// Occupancy density: opaque voxels are inside the surface.
+fn filled(source: &impl VoxelSource, cell: IVec3) -> bool {
+    source.voxel(cell.x, cell.y, cell.z).is_opaque()
+}
+
+/// Surface nets over voxel occupancy for the chunk-local cells
+/// `0..extent`. Every dual cell (the cube between eight voxel centres) that
+/// the surface passes through gets one vertex at the average of its edge
+/// crossings, and each face between an opaque voxel and an open one becomes
+/// a quad joining the four dual cells around it. A chunk emits the quads of
+/// its own opaque voxels, so neighbouring chunks meet without gaps or
+/// overlaps.
+///
+/// Normals come from the occupancy gradient of each dual cell, which only
+/// depends on its eight voxels, so shading stays continuous across chunk
+/// borders. Returns one mesh per material, including empty ones.
+pub(super) fn smooth_layers(
+    source: &impl VoxelSource,
+    extent: i32,
+) -> Vec<(BlockMaterial, SurfaceMesh)> {
+    let mut layers: Vec<(BlockMaterial, SurfaceMesh, HashMap<IVec3, u32>)> = BlockMaterial::ALL
+        .iter()
+        .map(|&material| (material, SurfaceMesh::default(), HashMap::new()))
+        .collect();
+    for z in 0..extent {
+        for y in 0..extent {
+            for x in 0..extent {
+                let cell = IVec3::new(x, y, z);
+                let voxel = source.voxel(x, y, z);
+                if !voxel.is_opaque() {
+                    continue;
+                }
+                let slot = BlockMaterial::ALL
+                    .iter()
+                    .position(|&material| material == voxel.material())
+                    .unwrap_or(0);
+                let (_, mesh, vertices) = &mut layers[slot];
+                for step in STEPS {
+                    if filled(source, cell + step) {
+                        continue;
+                    }
+                    let axis = (0..3).find(|&axis| step[axis] != 0).unwrap_or(0);
+                    let mut u = IVec3::ZERO;
+                    u[(axis + 1) % 3] = 1;
+                    let mut v = IVec3::ZERO;
+                    v[(axis + 2) % 3] = 1;
+                    let low = cell.min(cell + step);
+                    let mut corners = [low, low - u, low - u - v, low - v]
+                        .map(|dual| vertex(mesh, vertices, source, dual, step));
+                    // Same winding as the blocky faces.
+                    if step[axis] > 0 {
+                        corners.reverse();
+                    }
+                    let [a, b, c, d] = corners;
+                    mesh.indices.extend_from_slice(&[a, b, c, a, c, d]);
+                }
+            }
+        }
+    }
+    layers
+        .into_iter()
+        .map(|(material, mesh, _)| (material, mesh))
+        .collect()
+}
+
+/// Index of the vertex of the dual cell whose lowest voxel is `dual`,
+/// adding it on first use. `fallback` is the normal used where the
+/// occupancy gradient cancels out.
+fn vertex(
+    mesh: &mut SurfaceMesh,
+    vertices: &mut HashMap<IVec3, u32>,
+    source: &impl VoxelSource,
+    dual: IVec3,
+    fallback: IVec3,
+) -> u32 {
+    if let Some(&index) = vertices.get(&dual) {
+        return index;
+    }
+    let inside = CORNERS.map(|corner| filled(source, dual + corner));
+
+    let mut crossing_sum = Vec3::ZERO;
+    let mut crossings = 0.0;
+    for (a, b) in EDGES {
+        if inside[a] != inside[b] {
+            crossing_sum += (CORNERS[a] + CORNERS[b]).as_vec3() * 0.5;
+            crossings += 1.0;
+        }
+    }
+    let offset = crossing_sum / f32::max(crossings, 1.0);
+    let position = dual.as_vec3() + Vec3::splat(0.5) + offset;
+
+    let mut gradient = Vec3::ZERO;
+    let mut light = LightLevel::DARK;
+    for (corner, &solid) in CORNERS.iter().zip(&inside) {
+        let sign = corner.as_vec3() * 2.0 - Vec3::ONE;
+        if solid {
+            gradient += sign;
+        } else {
+            let cell = dual + *corner;
+            light = light.brighter(source.light(cell.x, cell.y, cell.z));
+        }
+    }
+    let normal = (-gradient).try_normalize().unwrap_or(fallback.as_vec3());
+    let solid_corners = inside.iter().filter(|&&solid| solid).count();
+    let [r, g, b] = light.color();
+
+    let dominant = normal.abs().max_element();
+    let uv = if normal.x.abs() == dominant {
+        [position.z, position.y]
+    } else if normal.y.abs() == dominant {
+        [position.x, position.z]
+    } else {
+        [position.x, position.y]
+    };
+
+    let index = mesh.positions.len() as u32;
+    mesh.positions.push(position.to_array());
+    mesh.normals.push(normal.to_array());
+    mesh.uvs.push(uv);
+    mesh.colors.push([r, g, b, 1.0]);
+    mesh.ao
+        .push(AO_CURVE[solid_corners.saturating_sub(4).min(3)]);
+    vertices.insert(dual, index);
+    index
+}
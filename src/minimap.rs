@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use bevy::math::{IVec2, IVec3, Vec2};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::Image;
+
+use crate::voxel::{BlockMaterial, Voxel};
+use crate::world::{HeightMap, VoxelWorld, CHUNK_SIZE};
+
+/// Colour of cells nobody has mapped yet.
+pub const UNMAPPED: [u8; 4] = [16, 16, 20, 255];
+
+/// Deepest water the map tints darker.
+const MAX_SHADED_DEPTH: i32 = 8;
+
+/// A top-down picture of the world, one pixel per column, shaded from the
+/// heightmap by the block on top. The picture is kept per chunk column so
+/// it can be filled in and refreshed one tile at a time as chunks are
+/// generated or edited.
+#[derive(Clone, Default)]
+pub struct WorldMap {
+    tiles: HashMap<IVec2, Box<[[u8; 4]]>>,
+    revision: u64,
+}
+
+/// Which part of the map to draw: the world column at the image centre and
+/// how many columns one pixel covers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapView {
+    pub center: Vec2,
+    pub cells_per_pixel: f32,
+}
+
+impl WorldMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Re-draws the tile of chunk column `tile` (chunk x and z) from the
+    /// world. Tiles without any voxel are dropped.
+    pub fn update_tile(&mut self, world: &VoxelWorld, heights: &HeightMap, tile: IVec2) {
+        let size = CHUNK_SIZE as i32;
+        let base = tile * size;
+        let mut pixels = vec![UNMAPPED; CHUNK_SIZE * CHUNK_SIZE];
+        let mut mapped = false;
+        for z in 0..size {
+            for x in 0..size {
+                let column = base + IVec2::new(x, z);
+                if let Some(color) = column_color(world, heights, column) {
+                    pixels[(x + z * size) as usize] = color;
+                    mapped = true;
+                }
+            }
+        }
+        if mapped {
+            self.tiles.insert(tile, pixels.into());
+        } else {
+            self.tiles.remove(&tile);
+        }
+        self.revision += 1;
+    }
+
+    /// Colour of world column `(x, z)`, if it has been mapped.
+    pub fn color(&self, x: i32, z: i32) -> Option<[u8; 4]> {
+        let size = CHUNK_SIZE as i32;
+        let tile = IVec2::new(x.div_euclid(size), z.div_euclid(size));
+        let local = IVec2::new(x.rem_euclid(size), z.rem_euclid(size));
+        self.tiles
+            .get(&tile)
+            .map(|pixels| pixels[(local.x + local.y * size) as usize])
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Bumped whenever a tile changes, so views know to redraw.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Mapped world columns as `min..max`, or `None` while empty.
+    pub fn bounds(&self) -> Option<(IVec2, IVec2)> {
+        let size = CHUNK_SIZE as i32;
+        let min = self.tiles.keys().copied().reduce(IVec2::min)?;
+        let max = self.tiles.keys().copied().reduce(IVec2::max)?;
+        Some((min * size, (max + IVec2::ONE) * size))
+    }
+
+    /// Draws `view` into a `width` × `height` RGBA image, row by row from
+    /// the north (-z) edge.
+    pub fn render(&self, view: MapView, width: u32, height: u32) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        let half = Vec2::new(width as f32, height as f32) * 0.5;
+        for py in 0..height {
+            for px in 0..width {
+                let cell = view.to_world(Vec2::new(px as f32, py as f32) + 0.5, half);
+                let cell = cell.floor().as_ivec2();
+                pixels.extend_from_slice(&self.color(cell.x, cell.y).unwrap_or(UNMAPPED));
+            }
+        }
+        pixels
+    }
+
+    /// Writes the whole mapped area to a PNG, one pixel per column.
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        let (min, max) = self.bounds().ok_or("nothing mapped yet")?;
+        let size = (max - min).as_uvec2();
+        let view = MapView {
+            center: (min + max).as_vec2() * 0.5,
+            cells_per_pixel: 1.0,
+        };
+        let image = rgba_image(self.render(view, size.x, size.y), size.x, size.y);
+        image
+            .try_into_dynamic()
+            .map_err(|err| err.to_string())?
+            .save(path)
+            .map_err(|err| err.to_string())
+    }
+}
+
+impl MapView {
+    /// World column under pixel `pixel` of an image whose centre is
+    /// `half` pixels from its corner.
+    pub fn to_world(&self, pixel: Vec2, half: Vec2) -> Vec2 {
+        self.center + (pixel - half) * self.cells_per_pixel
+    }
+
+    /// Pixel showing world column `cell`; the inverse of
+    /// [`MapView::to_world`].
+    pub fn to_pixel(&self, cell: Vec2, half: Vec2) -> Vec2 {
+        half + (cell - self.center) / self.cells_per_pixel
+    }
+}
+
+/// A texture for `pixels` as produced by [`WorldMap::render`].
+pub fn rgba_image(pixels: Vec<u8>, width: u32, height: u32) -> Image {
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Paints a small arrow at `at` pointing along `heading` (in map pixels,
+/// +y pointing south) into an RGBA image.
+pub fn draw_marker(
+    pixels: &mut [u8],
+    width: u32,
+    height: u32,
+    at: Vec2,
+    heading: Vec2,
+    color: [u8; 4],
+) {
+    let forward = heading.try_normalize().unwrap_or(Vec2::NEG_Y);
+    let side = forward.perp();
+    let tip = at + forward * 5.0;
+    let left = at - forward * 3.0 + side * 3.5;
+    let right = at - forward * 3.0 - side * 3.5;
+    let edge = |a: Vec2, b: Vec2, p: Vec2| (b - a).perp_dot(p - a);
+    let min = tip.min(left).min(right).floor().as_ivec2().max(IVec2::ZERO);
+    let max = tip
+        .max(left)
+        .max(right)
+        .ceil()
+        .as_ivec2()
+        .min(IVec2::new(width as i32 - 1, height as i32 - 1));
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let p = Vec2::new(x as f32, y as f32) + 0.5;
+            let sides = [
+                edge(tip, left, p),
+                edge(left, right, p),
+                edge(right, tip, p),
+            ];
+            if sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0) {
+                let index = ((x as u32 + y as u32 * width) * 4) as usize;
+                pixels[index..index + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Map colour of a world column: the top block's colour, lit from the
+/// north-west by the slope and darkened under deep water.
+fn column_color(world: &VoxelWorld, heights: &HeightMap, column: IVec2) -> Option<[u8; 4]> {
+    let top = heights.top(column.x, column.y)?;
+    let voxel = world.get(IVec3::new(column.x, top, column.y));
+    let mut color = surface_color(voxel);
+    let mut shade = 1.0;
+    if voxel.is_water() {
+        let depth = (1..=MAX_SHADED_DEPTH)
+            .find(|&depth| {
+                !world
+                    .get(IVec3::new(column.x, top - depth, column.y))
+                    .is_water()
+            })
+            .unwrap_or(MAX_SHADED_DEPTH);
+        shade -= 0.05 * depth as f32;
+    } else if let Some(behind) = heights.top(column.x - 1, column.y - 1) {
+        shade += (0.12 * (top - behind) as f32).clamp(-0.35, 0.35);
+    }
+    for channel in &mut color[..3] {
+        *channel = (*channel as f32 * shade).clamp(0.0, 255.0) as u8;
+    }
+    Some(color)
+}
+
+/// Flat map colour of a block.
+pub fn surface_color(voxel: Voxel) -> [u8; 4] {
+    let [r, g, b] = match voxel.material() {
+        BlockMaterial::Air => return UNMAPPED,
+        BlockMaterial::Terrain if voxel == Voxel::GROUND => [96, 150, 72],
+        BlockMaterial::Terrain => [150, 140, 128],
+        BlockMaterial::Snow => [236, 240, 245],
+        BlockMaterial::Sand => [218, 200, 140],
+        BlockMaterial::Gravel => [130, 126, 122],
+        BlockMaterial::Water => [52, 96, 190],
+        BlockMaterial::Wood => [116, 84, 52],
+        BlockMaterial::Leaves => [52, 108, 44],
+        BlockMaterial::Plant => [110, 170, 80],
+        BlockMaterial::Coal => [60, 60, 64],
+        BlockMaterial::Iron => [170, 140, 120],
+        BlockMaterial::Crystal => [150, 220, 240],
+        BlockMaterial::Brick => [160, 80, 64],
+        BlockMaterial::Glass => [200, 230, 240],
+    };
+    [r, g, b, 255]
+}
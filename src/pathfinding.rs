@@ -0,0 +1,240 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use bevy::math::{IVec3, Vec3};
+
+use crate::world::VoxelWorld;
+
+/// Movement limits for a ground agent walking over voxel columns.
+///
+/// Heights are in voxels. A standing cell is the empty voxel the agent's feet
+/// occupy, directly above a solid voxel.
+#[derive(Clone, Copy, Debug)]
+pub struct AgentProfile {
+    /// Highest ledge the agent can climb in a single move.
+    pub max_step_up: i32,
+    /// Deepest drop the agent is willing to take in a single move.
+    pub max_drop: i32,
+    /// Maximum terrain gradient (rise over run) of a cell the agent may stand on.
+    pub max_slope: f32,
+    /// Number of empty voxels the agent needs above its standing cell.
+    pub clearance: i32,
+    /// Upper bound on expanded nodes before the search gives up.
+    pub max_nodes: usize,
+}
+
+impl Default for AgentProfile {
+    fn default() -> Self {
+        Self {
+            max_step_up: 1,
+            max_drop: 3,
+            max_slope: 1.0,
+            clearance: 2,
+            max_nodes: 20_000,
+        }
+    }
+}
+
+const CARDINALS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONALS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Returns true if the agent can stand in `cell`: solid below and enough headroom.
+pub fn is_standable(world: &VoxelWorld, cell: IVec3, agent: &AgentProfile) -> bool {
+    world.is_solid(cell - IVec3::Y) && column_clear(world, cell, 0, agent.clearance)
+}
+
+/// Returns true if `cell` is standable and its surrounding terrain is not too steep.
+pub fn is_walkable(world: &VoxelWorld, cell: IVec3, agent: &AgentProfile) -> bool {
+    is_standable(world, cell, agent) && surface_slope(world, cell, agent) <= agent.max_slope
+}
+
+/// Finds the standing cell at or below `pos`, searching at most `max_depth` voxels down.
+pub fn standing_cell(
+    world: &VoxelWorld,
+    pos: Vec3,
+    max_depth: i32,
+    agent: &AgentProfile,
+) -> Option<IVec3> {
+    let start = pos.floor().as_ivec3();
+    (0..=max_depth)
+        .map(|depth| start - IVec3::Y * depth)
+        .find(|cell| is_standable(world, *cell, agent))
+}
+
+/// Central-difference gradient of the walkable surface around `cell`.
+///
+/// Neighbouring columns without a reachable standing height (walls, cliffs)
+/// are ignored so a lone ledge does not make its top unwalkable.
+pub fn surface_slope(world: &VoxelWorld, cell: IVec3, agent: &AgentProfile) -> f32 {
+    let mut slope: f32 = 0.0;
+    for axis in [IVec3::X, IVec3::Z] {
+        let ahead = neighbour_height(world, cell + axis, agent);
+        let behind = neighbour_height(world, cell - axis, agent);
+        let axis_slope = match (ahead, behind) {
+            (Some(a), Some(b)) => (a - b).abs() as f32 / 2.0,
+            (Some(h), None) | (None, Some(h)) => (h - cell.y).abs() as f32,
+            (None, None) => 0.0,
+        };
+        slope = slope.max(axis_slope);
+    }
+    slope
+}
+
+fn neighbour_height(world: &VoxelWorld, column: IVec3, agent: &AgentProfile) -> Option<i32> {
+    (-agent.max_drop..=agent.max_step_up)
+        .rev()
+        .map(|dy| column + IVec3::Y * dy)
+        .find(|cell| is_standable(world, *cell, agent))
+        .map(|cell| cell.y)
+}
+
+/// True when the voxels `base + y` for `y` in `from..to` are all non-solid.
+fn column_clear(world: &VoxelWorld, base: IVec3, from: i32, to: i32) -> bool {
+    (from..to).all(|dy| !world.is_solid(base + IVec3::Y * dy))
+}
+
+/// Resolves a horizontal move from `from` by `(dx, dz)`, returning the landing cell.
+fn step_target(
+    world: &VoxelWorld,
+    from: IVec3,
+    dx: i32,
+    dz: i32,
+    agent: &AgentProfile,
+) -> Option<IVec3> {
+    let column = from + IVec3::new(dx, 0, dz);
+    for dy in (-agent.max_drop..=agent.max_step_up).rev() {
+        let target = column + IVec3::Y * dy;
+        if !is_walkable(world, target, agent) {
+            continue;
+        }
+        let path_clear = if dy > 0 {
+            // Headroom to climb out of the current cell.
+            column_clear(world, from, agent.clearance, agent.clearance + dy)
+        } else {
+            // Room to walk over the edge and fall down the target column.
+            column_clear(world, column, dy, agent.clearance)
+        };
+        if path_clear {
+            return Some(target);
+        }
+    }
+    None
+}
+
+fn neighbours(world: &VoxelWorld, cell: IVec3, agent: &AgentProfile) -> Vec<IVec3> {
+    let mut result = Vec::with_capacity(8);
+    for (dx, dz) in CARDINALS {
+        if let Some(target) = step_target(world, cell, dx, dz, agent) {
+            result.push(target);
+        }
+    }
+    for (dx, dz) in DIAGONALS {
+        // No corner cutting: both adjacent cardinal moves must be possible.
+        if step_target(world, cell, dx, 0, agent).is_none()
+            || step_target(world, cell, 0, dz, agent).is_none()
+        {
+            continue;
+        }
+        if let Some(target) = step_target(world, cell, dx, dz, agent) {
+            result.push(target);
+        }
+    }
+    result
+}
+
+fn move_cost(from: IVec3, to: IVec3) -> f32 {
+    (to - from).as_vec3().length()
+}
+
+#[derive(Clone, Copy)]
+struct OpenNode {
+    cell: IVec3,
+    estimate: f32,
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenNode {}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so the binary heap pops the lowest estimate first.
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// A* search between two standing cells.
+///
+/// Returns the path as world positions at the centre of each cell's floor,
+/// including both endpoints, or `None` if the goal is unreachable within
+/// the agent's node budget.
+pub fn find_path(
+    world: &VoxelWorld,
+    start: IVec3,
+    goal: IVec3,
+    agent: &AgentProfile,
+) -> Option<Vec<Vec3>> {
+    if !is_standable(world, start, agent) || !is_walkable(world, goal, agent) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec3, IVec3> = HashMap::new();
+    let mut cost_so_far: HashMap<IVec3, f32> = HashMap::new();
+
+    cost_so_far.insert(start, 0.0);
+    open.push(OpenNode {
+        cell: start,
+        estimate: move_cost(start, goal),
+    });
+
+    let mut expanded = 0;
+    while let Some(OpenNode { cell, .. }) = open.pop() {
+        if cell == goal {
+            return Some(reconstruct(&came_from, goal));
+        }
+        expanded += 1;
+        if expanded > agent.max_nodes {
+            break;
+        }
+
+        let current_cost = cost_so_far[&cell];
+        for next in neighbours(world, cell, agent) {
+            let cost = current_cost + move_cost(cell, next);
+            if cost_so_far.get(&next).is_none_or(|&known| cost < known) {
+                cost_so_far.insert(next, cost);
+                came_from.insert(next, cell);
+                open.push(OpenNode {
+                    cell: next,
+                    estimate: cost + move_cost(next, goal),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+fn reconstruct(came_from: &HashMap<IVec3, IVec3>, goal: IVec3) -> Vec<Vec3> {
+    let mut cells = vec![goal];
+    let mut current = goal;
+    while let Some(&previous) = came_from.get(&current) {
+        cells.push(previous);
+        current = previous;
+    }
+    cells.reverse();
+    cells
+        .into_iter()
+        .map(|cell| Vec3::new(cell.x as f32 + 0.5, cell.y as f32, cell.z as f32 + 0.5))
+        .collect()
+}
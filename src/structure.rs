@@ -0,0 +1,313 @@
+use bevy::math::{IVec3, Quat};
+
+use crate::voxel::Voxel;
+use crate::world::VoxelWorld;
+
+/// A named point in a template where the game spawns an entity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Marker {
+    pub name: String,
+    /// Template-local cell.
+    pub pos: IVec3,
+}
+
+/// A doorway where a jigsaw piece joins another one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Connector {
+    /// Only connectors with equal tags join.
+    pub tag: String,
+    pub pos: IVec3,
+    /// Horizontal unit vector pointing out of the template.
+    pub facing: IVec3,
+}
+
+/// A block of voxels plus entity markers, loaded from the text format below.
+///
+/// ```text
+/// // Comments start with two slashes.
+/// anchor 3 0 3          template cell placed at the placement origin
+/// block # brick         palette entry: character and block name
+/// marker S spray        marker character and name; the cell is cleared
+/// connector D door      connector character and tag; the cell is cleared
+/// layer                 one per y, bottom first
+/// -###-                 rows run along +z, characters along +x
+/// ```
+///
+/// `.` clears a cell to air and `-` leaves whatever is already there.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructureTemplate {
+    size: IVec3,
+    /// Per cell in x, z, y order; `None` keeps the existing voxel.
+    cells: Vec<Option<Voxel>>,
+    markers: Vec<Marker>,
+    connectors: Vec<Connector>,
+    anchor: IVec3,
+}
+
+/// Where and how a template is stamped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Placement {
+    /// World cell the template's anchor lands on.
+    pub origin: IVec3,
+    /// Counter-clockwise quarter turns seen from above, applied after
+    /// mirroring.
+    pub quarter_turns: u8,
+    /// Flips the template along x.
+    pub mirror: bool,
+}
+
+impl Placement {
+    pub fn at(origin: IVec3) -> Self {
+        Self {
+            origin,
+            ..Self::default()
+        }
+    }
+
+    /// World cell of template-local `cell` for a template anchored at
+    /// `anchor`.
+    pub fn apply(&self, anchor: IVec3, cell: IVec3) -> IVec3 {
+        let mut offset = cell - anchor;
+        if self.mirror {
+            offset.x = -offset.x;
+        }
+        for _ in 0..self.quarter_turns % 4 {
+            offset = IVec3::new(offset.z, offset.y, -offset.x);
+        }
+        self.origin + offset
+    }
+
+    /// `direction` turned and mirrored like the template's cells.
+    pub fn apply_direction(&self, direction: IVec3) -> IVec3 {
+        Placement {
+            origin: IVec3::ZERO,
+            ..*self
+        }
+        .apply(IVec3::ZERO, direction)
+    }
+
+    /// The rotation as a quaternion, for orienting marker entities.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(std::f32::consts::FRAC_PI_2 * (self.quarter_turns % 4) as f32)
+    }
+}
+
+impl StructureTemplate {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut palette: Vec<(char, Option<Voxel>)> = vec![('.', Some(Voxel::AIR)), ('-', None)];
+        let mut marker_keys: Vec<(char, String)> = Vec::new();
+        let mut connector_keys: Vec<(char, String)> = Vec::new();
+        let mut anchor = IVec3::ZERO;
+        let mut layers: Vec<Vec<&str>> = Vec::new();
+
+        for (index, raw) in source.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw.trim_end();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("anchor") => {
+                    let coords: Vec<i32> = words
+                        .map(|word| word.parse::<i32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("line {line_no}: invalid anchor"))?;
+                    let [x, y, z] = coords[..] else {
+                        return Err(format!("line {line_no}: anchor expects three numbers"));
+                    };
+                    anchor = IVec3::new(x, y, z);
+                }
+                Some("block") => {
+                    let (key, name) = key_and_name(&mut words, line_no)?;
+                    let voxel = Voxel::from_name(&name)
+                        .ok_or_else(|| format!("line {line_no}: unknown block {name:?}"))?;
+                    palette.push((key, Some(voxel)));
+                }
+                Some("marker") => {
+                    let (key, name) = key_and_name(&mut words, line_no)?;
+                    marker_keys.push((key, name));
+                }
+                Some("connector") => {
+                    let (key, tag) = key_and_name(&mut words, line_no)?;
+                    connector_keys.push((key, tag));
+                }
+                Some("layer") => layers.push(Vec::new()),
+                _ => match layers.last_mut() {
+                    Some(rows) => rows.push(line),
+                    None => return Err(format!("line {line_no}: row before the first layer")),
+                },
+            }
+        }
+
+        let depth = layers.first().map_or(0, Vec::len);
+        let width = layers
+            .first()
+            .and_then(|rows| rows.first())
+            .map_or(0, |row| row.chars().count());
+        if width == 0 || depth == 0 {
+            return Err("template has no cells".into());
+        }
+        let size = IVec3::new(width as i32, layers.len() as i32, depth as i32);
+        let mut cells = Vec::with_capacity((size.x * size.y * size.z) as usize);
+        let mut markers = Vec::new();
+        let mut connectors = Vec::new();
+        for (y, rows) in layers.iter().enumerate() {
+            if rows.len() != depth {
+                return Err(format!(
+                    "layer {y} has {} rows, expected {depth}",
+                    rows.len()
+                ));
+            }
+            for (z, row) in rows.iter().enumerate() {
+                if row.chars().count() != width {
+                    return Err(format!("layer {y} row {z} is not {width} wide"));
+                }
+                for (x, key) in row.chars().enumerate() {
+                    let pos = IVec3::new(x as i32, y as i32, z as i32);
+                    if let Some((_, name)) = marker_keys.iter().find(|(k, _)| *k == key) {
+                        markers.push(Marker {
+                            name: name.clone(),
+                            pos,
+                        });
+                        cells.push(Some(Voxel::AIR));
+                        continue;
+                    }
+                    if let Some((_, tag)) = connector_keys.iter().find(|(k, _)| *k == key) {
+                        let facing = face_of(pos, size).ok_or_else(|| {
+                            format!("layer {y} row {z}: connector is not on exactly one side")
+                        })?;
+                        connectors.push(Connector {
+                            tag: tag.clone(),
+                            pos,
+                            facing,
+                        });
+                        cells.push(Some(Voxel::AIR));
+                        continue;
+                    }
+                    let (_, voxel) = palette
+                        .iter()
+                        .rev()
+                        .find(|(k, _)| *k == key)
+                        .ok_or_else(|| format!("layer {y} row {z}: unknown key {key:?}"))?;
+                    cells.push(*voxel);
+                }
+            }
+        }
+        Ok(Self {
+            size,
+            cells,
+            markers,
+            connectors,
+            anchor,
+        })
+    }
+
+    pub fn size(&self) -> IVec3 {
+        self.size
+    }
+
+    pub fn anchor(&self) -> IVec3 {
+        self.anchor
+    }
+
+    /// Template-local voxel at `pos`; `None` outside the template or where
+    /// the template keeps the existing voxel.
+    pub fn get(&self, pos: IVec3) -> Option<Voxel> {
+        if pos.cmplt(IVec3::ZERO).any() || pos.cmpge(self.size).any() {
+            return None;
+        }
+        self.cells[(pos.x + pos.z * self.size.x + pos.y * self.size.x * self.size.z) as usize]
+    }
+
+    /// Every voxel the template writes under `placement`, in world cells.
+    /// Blocks with a facing turn along with the template.
+    pub fn blocks(&self, placement: &Placement) -> Vec<(IVec3, Voxel)> {
+        let mut blocks = Vec::new();
+        for y in 0..self.size.y {
+            for z in 0..self.size.z {
+                for x in 0..self.size.x {
+                    let local = IVec3::new(x, y, z);
+                    if let Some(mut voxel) = self.get(local) {
+                        if let Some(facing) = voxel.facing() {
+                            voxel = voxel.with_facing(placement.apply_direction(facing));
+                        }
+                        blocks.push((placement.apply(self.anchor, local), voxel));
+                    }
+                }
+            }
+        }
+        blocks
+    }
+
+    /// Markers under `placement`, in world cells.
+    pub fn markers(&self, placement: &Placement) -> Vec<Marker> {
+        self.markers
+            .iter()
+            .map(|marker| Marker {
+                name: marker.name.clone(),
+                pos: placement.apply(self.anchor, marker.pos),
+            })
+            .collect()
+    }
+
+    /// Connectors under `placement`, in world cells and directions.
+    pub fn connectors(&self, placement: &Placement) -> Vec<Connector> {
+        self.connectors
+            .iter()
+            .map(|connector| Connector {
+                tag: connector.tag.clone(),
+                pos: placement.apply(self.anchor, connector.pos),
+                facing: placement.apply_direction(connector.facing),
+            })
+            .collect()
+    }
+
+    /// Inclusive world bounds of the template under `placement`.
+    pub fn bounds(&self, placement: &Placement) -> (IVec3, IVec3) {
+        let a = placement.apply(self.anchor, IVec3::ZERO);
+        let b = placement.apply(self.anchor, self.size - IVec3::ONE);
+        (a.min(b), a.max(b))
+    }
+
+    /// Writes the template into `world` and returns its markers.
+    pub fn stamp(&self, world: &mut VoxelWorld, placement: &Placement) -> Vec<Marker> {
+        for (pos, voxel) in self.blocks(placement) {
+            world.set(pos, voxel);
+        }
+        self.markers(placement)
+    }
+}
+
+/// Outward direction of the single vertical side `pos` lies on.
+fn face_of(pos: IVec3, size: IVec3) -> Option<IVec3> {
+    let sides = [
+        (pos.x == 0, IVec3::NEG_X),
+        (pos.x == size.x - 1, IVec3::X),
+        (pos.z == 0, IVec3::NEG_Z),
+        (pos.z == size.z - 1, IVec3::Z),
+    ];
+    let mut faces = sides.iter().filter(|(on, _)| *on);
+    match (faces.next(), faces.next()) {
+        (Some(&(_, facing)), None) => Some(facing),
+        _ => None,
+    }
+}
+
+fn key_and_name<'a>(
+    words: &mut impl Iterator<Item = &'a str>,
+    line_no: usize,
+) -> Result<(char, String), String> {
+    let key = words.next().and_then(|word| {
+        let mut chars = word.chars();
+        let key = chars.next()?;
+        chars.next().is_none().then_some(key)
+    });
+    let name = words.next();
+    match (key, name) {
+        (Some(key), Some(name)) if !matches!(key, '.' | '-') => Ok((key, name.to_string())),
+        _ => Err(format!(
+            "line {line_no}: expected a single character and a name"
+        )),
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::math::IVec3;
+
+use crate::world::{Chunk, ChunkStorage, CHUNK_SIZE, CHUNK_VOLUME};
+
+/// Chunk sides in the order their bits are stored; a side's opposite is the
+/// index with the lowest bit flipped.
+const SIDES: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+fn side_index(side: IVec3) -> Option<usize> {
+    SIDES.iter().position(|&candidate| candidate == side)
+}
+
+/// Chunk counts from a culling pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CullingStats {
+    /// Chunks in the world without any exposed face, so without meshes.
+    pub buried: usize,
+    /// Meshed chunks outside the camera's view.
+    pub outside_view: usize,
+    /// Meshed chunks in view that the camera cannot see into.
+    pub occluded: usize,
+    pub drawn: usize,
+}
+
+/// Which sides of a chunk can see each other through its open voxels.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FaceConnections(u64);
+
+impl FaceConnections {
+    /// A solid chunk: nothing passes through.
+    pub const NONE: FaceConnections = FaceConnections(0);
+    /// An open chunk: every side sees every other.
+    pub const ALL: FaceConnections = FaceConnections((1 << 36) - 1);
+
+    /// Computes the connections of `chunk` by flood filling its
+    /// non-opaque voxels and linking every pair of sides a region touches.
+    pub fn of(chunk: &Chunk) -> FaceConnections {
+        if chunk.storage() == ChunkStorage::Uniform {
+            return if chunk.get(0, 0, 0).is_opaque() {
+                Self::NONE
+            } else {
+                Self::ALL
+            };
+        }
+        let open: Vec<bool> = chunk.voxels().map(|voxel| !voxel.is_opaque()).collect();
+        let mut seen = vec![false; CHUNK_VOLUME];
+        let mut connections = Self::NONE;
+        let mut stack = Vec::new();
+        for start in 0..CHUNK_VOLUME {
+            if !open[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            stack.push(start);
+            let mut touched = 0u8;
+            while let Some(index) = stack.pop() {
+                let (x, y, z) = Chunk::position(index);
+                let cell = IVec3::new(x as i32, y as i32, z as i32);
+                for (side, &step) in SIDES.iter().enumerate() {
+                    let next = cell + step;
+                    if next.cmplt(IVec3::ZERO).any()
+                        || next.cmpge(IVec3::splat(CHUNK_SIZE as i32)).any()
+                    {
+                        touched |= 1 << side;
+                        continue;
+                    }
+                    let next = Chunk::index(next.x as u32, next.y as u32, next.z as u32);
+                    if open[next] && !seen[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            for a in 0..6 {
+                for b in 0..6 {
+                    if touched & (1 << a) != 0 && touched & (1 << b) != 0 {
+                        connections.0 |= 1 << (a * 6 + b);
+                    }
+                }
+            }
+            if connections == Self::ALL {
+                break;
+            }
+        }
+        connections
+    }
+
+    /// Whether looking in through side `from` can reach side `to`. Both are
+    /// unit axis directions.
+    pub fn connects(self, from: IVec3, to: IVec3) -> bool {
+        match (side_index(from), side_index(to)) {
+            (Some(a), Some(b)) => self.connects_index(a, b),
+            _ => false,
+        }
+    }
+
+    fn connects_index(self, a: usize, b: usize) -> bool {
+        self.0 & (1 << (a * 6 + b)) != 0
+    }
+}
+
+/// Chunks the camera in chunk `start` may see, walking outwards from it
+/// through chunk sides. A walk only continues through a chunk when the side
+/// it entered by connects to the side it leaves by, never turns back towards
+/// the camera, and stays within the inclusive `bounds` and `in_view`.
+/// Buried caves and chunks behind solid ground are never reached.
+pub fn visible_chunks(
+    start: IVec3,
+    bounds: (IVec3, IVec3),
+    connections: impl Fn(IVec3) -> FaceConnections,
+    in_view: impl Fn(IVec3) -> bool,
+) -> HashSet<IVec3> {
+    let start = start.clamp(bounds.0, bounds.1);
+    let mut visible = HashSet::from([start]);
+    // Chunk, the side it was entered by and the directions walked so far.
+    let mut queue = VecDeque::from([(start, None::<usize>, 0u8)]);
+    while let Some((coord, entered, walked)) = queue.pop_front() {
+        let links = connections(coord);
+        for (side, &step) in SIDES.iter().enumerate() {
+            if walked & (1 << (side ^ 1)) != 0 {
+                continue;
+            }
+            if entered.is_some_and(|entered| !links.connects_index(entered, side)) {
+                continue;
+            }
+            let next = coord + step;
+            if next.cmplt(bounds.0).any() || next.cmpgt(bounds.1).any() {
+                continue;
+            }
+            if visible.contains(&next) || !in_view(next) {
+                continue;
+            }
+            visible.insert(next);
+            queue.push_back((next, Some(side ^ 1), walked | 1 << side));
+        }
+    }
+    visible
+}
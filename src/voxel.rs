@@ -1,9 +1,593 @@
+use std::fmt;
+
+use bevy::math::IVec3;
+
+/// A block id in the low byte and the block's state bits in the high byte.
+/// The block's [`BlockInfo::properties`] say what the state bits mean; plain
+/// blocks leave them zero.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Voxel(pub u16);
+
+/// Render material a block is meshed with. Each material gets its own mesh
+/// per chunk.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlockMaterial {
+    /// Empty space. Never meshed, so it is left out of
+    /// [`BlockMaterial::ALL`].
+    Air,
+    /// The chunk's terrain band material (grass, dirt or stone).
+    Terrain,
+    Snow,
+    Sand,
+    Gravel,
+    /// Translucent liquid surface.
+    Water,
+    Wood,
+    Leaves,
+    /// Grass and other small plants.
+    Plant,
+    Coal,
+    Iron,
+    /// Glowing crystal ore.
+    Crystal,
+    /// Dungeon masonry.
+    Brick,
+    /// Clear glass.
+    Glass,
+}
+
+impl BlockMaterial {
+    pub const ALL: [BlockMaterial; 13] = [
+        BlockMaterial::Terrain,
+        BlockMaterial::Snow,
+        BlockMaterial::Sand,
+        BlockMaterial::Gravel,
+        BlockMaterial::Water,
+        BlockMaterial::Wood,
+        BlockMaterial::Leaves,
+        BlockMaterial::Plant,
+        BlockMaterial::Coal,
+        BlockMaterial::Iron,
+        BlockMaterial::Crystal,
+        BlockMaterial::Brick,
+        BlockMaterial::Glass,
+    ];
+
+    /// Transparency shared by every block drawn with this material. Each
+    /// material is its own mesh, so opaque, cutout and translucent faces
+    /// never share one and translucent meshes are sorted on their own.
+    pub fn transparency(self) -> Transparency {
+        match self {
+            BlockMaterial::Air | BlockMaterial::Leaves | BlockMaterial::Plant => {
+                Transparency::Cutout
+            }
+            BlockMaterial::Water | BlockMaterial::Glass => Transparency::Translucent,
+            _ => Transparency::Opaque,
+        }
+    }
+}
+
+/// How much of what lies behind a block shows through it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Transparency {
+    /// Hides faces behind it and blocks light.
+    Opaque,
+    /// Solid pixels with holes, like leaves, or nothing at all, like air.
+    /// Never hides a neighbour's face, not even one of the same block.
+    Cutout,
+    /// Seen through as a tinted surface, like glass and water. Hides faces
+    /// between blocks of the same material, so a pane of glass or a pool of
+    /// water shows only its outside.
+    Translucent,
+}
+
+/// Geometry a block is meshed with. Shapes other than [`BlockShape::Cube`]
+/// let light through and only hide the neighbouring faces they fully cover.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Voxel(pub u8);
+pub enum BlockShape {
+    Cube,
+    /// Lower or upper half of a cube.
+    Slab {
+        top: bool,
+    },
+    /// A slab with a full-height back half on the side facing the given
+    /// horizontal direction.
+    Stairs(IVec3),
+    /// A post that grows bars towards neighbouring fences and opaque blocks.
+    Fence,
+    /// Two crossed quads, for plants.
+    Cross,
+}
+
+impl BlockShape {
+    /// Whether the shape fills the whole side of its cell towards
+    /// `direction`, hiding the neighbouring face there.
+    pub fn covers(self, direction: IVec3) -> bool {
+        match self {
+            BlockShape::Cube => true,
+            BlockShape::Slab { top } => direction == if top { IVec3::Y } else { IVec3::NEG_Y },
+            BlockShape::Stairs(facing) => direction == IVec3::NEG_Y || direction == facing,
+            BlockShape::Fence | BlockShape::Cross => false,
+        }
+    }
+}
+
+/// A piece of block state. A block's properties are packed into the state
+/// bits in the order the block lists them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Property {
+    /// Horizontal direction: `east` (+x), `south` (+z), `west` (-x) or
+    /// `north` (-z).
+    Facing,
+    /// `bottom` or `top` half of the cell.
+    Half,
+    /// Water flow level from 0 (source) to 7.
+    Level,
+    /// Water pouring down rather than spreading: `false` or `true`.
+    Falling,
+}
+
+const FACINGS: [IVec3; 4] = [IVec3::X, IVec3::Z, IVec3::NEG_X, IVec3::NEG_Z];
+
+impl Property {
+    pub fn name(self) -> &'static str {
+        match self {
+            Property::Facing => "facing",
+            Property::Half => "half",
+            Property::Level => "level",
+            Property::Falling => "falling",
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            Property::Facing => 2,
+            Property::Half | Property::Falling => 1,
+            Property::Level => 3,
+        }
+    }
+
+    /// Names of the values, for properties whose values are not numbers.
+    fn value_names(self) -> Option<&'static [&'static str]> {
+        match self {
+            Property::Facing => Some(&["east", "south", "west", "north"]),
+            Property::Half => Some(&["bottom", "top"]),
+            Property::Falling => Some(&["false", "true"]),
+            Property::Level => None,
+        }
+    }
+
+    fn parse(self, raw: &str) -> Option<u8> {
+        let value = match self.value_names() {
+            Some(names) => names.iter().position(|&name| name == raw)?,
+            None => raw.parse::<usize>().ok().filter(|&level| level < 8)?,
+        };
+        Some(value as u8)
+    }
+}
+
+/// Static properties shared by every voxel of a given type.
+#[derive(Clone, Copy, Debug)]
+pub struct BlockInfo {
+    pub name: &'static str,
+    /// Blocks movement.
+    pub solid: bool,
+    /// Block light level emitted by the voxel (0 = none).
+    pub emission: u8,
+    /// Falls when nothing solid is underneath.
+    pub gravity: bool,
+    /// Explosion force needed to destroy the block.
+    pub resistance: f32,
+    pub material: BlockMaterial,
+    pub shape: BlockShape,
+    /// State the block keeps per voxel.
+    pub properties: &'static [Property],
+}
+
+const UNKNOWN: BlockInfo = BlockInfo {
+    name: "unknown",
+    solid: true,
+    emission: 0,
+    gravity: false,
+    resistance: 1.0,
+    material: BlockMaterial::Terrain,
+    shape: BlockShape::Cube,
+    properties: &[],
+};
+
+/// Block registry indexed by voxel id.
+static BLOCKS: &[BlockInfo] = &[
+    BlockInfo {
+        name: "air",
+        solid: false,
+        emission: 0,
+        gravity: false,
+        resistance: 0.0,
+        material: BlockMaterial::Air,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "ground",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 1.0,
+        material: BlockMaterial::Terrain,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "lamp",
+        solid: true,
+        emission: 14,
+        gravity: false,
+        resistance: 0.5,
+        material: BlockMaterial::Terrain,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "snow",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 0.1,
+        material: BlockMaterial::Snow,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "sand",
+        solid: true,
+        emission: 0,
+        gravity: true,
+        resistance: 0.5,
+        material: BlockMaterial::Sand,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "gravel",
+        solid: true,
+        emission: 0,
+        gravity: true,
+        resistance: 0.6,
+        material: BlockMaterial::Gravel,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "log",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 1.5,
+        material: BlockMaterial::Wood,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "leaves",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 0.2,
+        material: BlockMaterial::Leaves,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "tall_grass",
+        solid: false,
+        emission: 0,
+        gravity: false,
+        resistance: 0.0,
+        material: BlockMaterial::Plant,
+        shape: BlockShape::Cross,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "coal_ore",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 1.2,
+        material: BlockMaterial::Coal,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "iron_ore",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 1.5,
+        material: BlockMaterial::Iron,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "crystal",
+        solid: true,
+        emission: 10,
+        gravity: false,
+        resistance: 0.8,
+        material: BlockMaterial::Crystal,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "brick",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 3.0,
+        material: BlockMaterial::Brick,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "cobblestone",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 2.0,
+        material: BlockMaterial::Gravel,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "glass",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 0.3,
+        material: BlockMaterial::Glass,
+        shape: BlockShape::Cube,
+        properties: &[],
+    },
+    BlockInfo {
+        name: "cobblestone_slab",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 2.0,
+        material: BlockMaterial::Gravel,
+        shape: BlockShape::Slab { top: false },
+        properties: &[Property::Half],
+    },
+    BlockInfo {
+        name: "water",
+        solid: false,
+        emission: 0,
+        gravity: false,
+        resistance: 100.0,
+        material: BlockMaterial::Water,
+        shape: BlockShape::Cube,
+        properties: &[Property::Level, Property::Falling],
+    },
+    BlockInfo {
+        name: "brick_stairs",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 3.0,
+        material: BlockMaterial::Brick,
+        shape: BlockShape::Stairs(IVec3::X),
+        properties: &[Property::Facing],
+    },
+    BlockInfo {
+        name: "fence",
+        solid: true,
+        emission: 0,
+        gravity: false,
+        resistance: 1.5,
+        material: BlockMaterial::Wood,
+        shape: BlockShape::Fence,
+        properties: &[],
+    },
+];
 
 impl Voxel {
-    pub const AIR: Voxel = Voxel(0);
+    pub const AIR: Voxel = Voxel::new(0, 0);
+    pub const GROUND: Voxel = Voxel::new(1, 0);
+    pub const LAMP: Voxel = Voxel::new(2, 0);
+    pub const SNOW: Voxel = Voxel::new(3, 0);
+    pub const SAND: Voxel = Voxel::new(4, 0);
+    pub const GRAVEL: Voxel = Voxel::new(5, 0);
+    pub const LOG: Voxel = Voxel::new(6, 0);
+    pub const LEAVES: Voxel = Voxel::new(7, 0);
+    pub const TALL_GRASS: Voxel = Voxel::new(8, 0);
+    pub const COAL_ORE: Voxel = Voxel::new(9, 0);
+    pub const IRON_ORE: Voxel = Voxel::new(10, 0);
+    pub const CRYSTAL: Voxel = Voxel::new(11, 0);
+    pub const BRICK: Voxel = Voxel::new(12, 0);
+    pub const COBBLESTONE: Voxel = Voxel::new(13, 0);
+    pub const GLASS: Voxel = Voxel::new(14, 0);
+    /// Bottom half slab.
+    pub const COBBLESTONE_SLAB: Voxel = Voxel::new(15, 0);
+    /// Still water source block.
+    pub const WATER: Voxel = Voxel::new(16, 0);
+    /// Stairs rising towards +x.
+    pub const BRICK_STAIRS: Voxel = Voxel::new(17, 0);
+    pub const FENCE: Voxel = Voxel::new(18, 0);
+    /// Highest (thinnest) level of flowing water.
+    pub const MAX_WATER_LEVEL: u8 = 7;
+
+    pub const fn new(id: u8, state: u8) -> Voxel {
+        Voxel(id as u16 | (state as u16) << 8)
+    }
+
+    /// Registry index of the block.
+    pub fn id(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Raw state bits; see [`Voxel::get`].
+    pub fn state(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// Water at `level` (0 = source, up to [`Voxel::MAX_WATER_LEVEL`]).
+    pub fn water(level: u8, falling: bool) -> Voxel {
+        Voxel::WATER
+            .with(Property::Level, level.min(Self::MAX_WATER_LEVEL))
+            .with(Property::Falling, falling as u8)
+    }
+
+    /// Brick stairs rising towards the horizontal direction `facing`.
+    pub fn brick_stairs(facing: IVec3) -> Voxel {
+        Voxel::BRICK_STAIRS.with_facing(facing)
+    }
+
+    /// Looks a block up by its registry name, optionally followed by state
+    /// in brackets, like `brick_stairs[facing=north]` or
+    /// `water[level=3,falling=true]`. Unlisted properties are zero.
+    pub fn from_name(name: &str) -> Option<Voxel> {
+        let (name, state) = match name.split_once('[') {
+            Some((name, rest)) => (name, Some(rest.strip_suffix(']')?)),
+            None => (name, None),
+        };
+        let id = BLOCKS.iter().position(|info| info.name == name)?;
+        let mut voxel = Voxel::new(id as u8, 0);
+        for entry in state.into_iter().flat_map(|state| state.split(',')) {
+            let (key, raw) = entry.split_once('=')?;
+            let property = voxel
+                .info()
+                .properties
+                .iter()
+                .find(|property| property.name() == key.trim())?;
+            voxel = voxel.with(*property, property.parse(raw.trim())?);
+        }
+        Some(voxel)
+    }
+
+    /// Where `property` sits in the state bits, if the block has it.
+    fn field(self, property: Property) -> Option<(u32, u8)> {
+        let mut shift = 0;
+        for &candidate in self.info().properties {
+            let mask = ((1u32 << candidate.bits()) - 1) as u8;
+            if candidate == property {
+                return Some((shift, mask));
+            }
+            shift += candidate.bits();
+        }
+        None
+    }
+
+    /// Value of `property`, if the block has it.
+    pub fn get(self, property: Property) -> Option<u8> {
+        let (shift, mask) = self.field(property)?;
+        Some((self.state() >> shift) & mask)
+    }
+
+    /// The voxel with `property` set to `value`; unchanged if the block
+    /// does not have the property.
+    pub fn with(self, property: Property, value: u8) -> Voxel {
+        let Some((shift, mask)) = self.field(property) else {
+            return self;
+        };
+        let state = (self.state() & !(mask << shift)) | ((value & mask) << shift);
+        Voxel::new(self.id(), state)
+    }
+
+    /// Horizontal direction of blocks with [`Property::Facing`].
+    pub fn facing(self) -> Option<IVec3> {
+        self.get(Property::Facing)
+            .map(|index| FACINGS[index as usize])
+    }
+
+    /// The voxel turned towards `facing`, which should be horizontal.
+    pub fn with_facing(self, facing: IVec3) -> Voxel {
+        let index = FACINGS.iter().position(|&side| side == facing).unwrap_or(0);
+        self.with(Property::Facing, index as u8)
+    }
+
+    pub fn is_water(self) -> bool {
+        self.id() == Voxel::WATER.id()
+    }
+
+    /// Flow level of a water voxel; 0 is a source or full column.
+    pub fn water_level(self) -> Option<u8> {
+        self.is_water()
+            .then(|| self.get(Property::Level).unwrap_or(0))
+    }
+
+    /// Water pouring down from above rather than spreading sideways.
+    pub fn is_falling_water(self) -> bool {
+        self.is_water() && self.get(Property::Falling) == Some(1)
+    }
+
     pub fn is_empty(self) -> bool {
-        self.0 == 0
+        self.id() == 0
+    }
+
+    pub fn info(self) -> &'static BlockInfo {
+        BLOCKS.get(self.id() as usize).unwrap_or(&UNKNOWN)
+    }
+
+    /// Whether the voxel blocks movement (agents stand on it and cannot pass through).
+    pub fn is_solid(self) -> bool {
+        self.info().solid
+    }
+
+    /// A full cube that stops light and hides the faces of its neighbours.
+    pub fn is_opaque(self) -> bool {
+        self.transparency() == Transparency::Opaque && self.shape() == BlockShape::Cube
+    }
+
+    pub fn transparency(self) -> Transparency {
+        self.material().transparency()
+    }
+
+    pub fn emission(self) -> u8 {
+        self.info().emission
+    }
+
+    /// Whether the voxel falls when unsupported (sand, gravel).
+    pub fn falls(self) -> bool {
+        self.info().gravity
+    }
+
+    pub fn resistance(self) -> f32 {
+        self.info().resistance
+    }
+
+    pub fn material(self) -> BlockMaterial {
+        self.info().material
+    }
+
+    /// The registry shape, turned and flipped by the voxel's state.
+    pub fn shape(self) -> BlockShape {
+        match self.info().shape {
+            BlockShape::Slab { .. } => BlockShape::Slab {
+                top: self.get(Property::Half) == Some(1),
+            },
+            BlockShape::Stairs(_) => BlockShape::Stairs(self.facing().unwrap_or(IVec3::X)),
+            shape => shape,
+        }
+    }
+}
+
+/// Writes the block name with its state in the form [`Voxel::from_name`]
+/// reads, like `water[level=3,falling=false]`.
+impl fmt::Display for Voxel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.info().name)?;
+        let properties = self.info().properties;
+        for (index, &property) in properties.iter().enumerate() {
+            let value = self.get(property).unwrap_or(0);
+            let separator = if index == 0 { '[' } else { ',' };
+            write!(f, "{separator}{}=", property.name())?;
+            match property.value_names() {
+                Some(names) => f.write_str(names[value as usize])?,
+                None => write!(f, "{value}")?,
+            }
+        }
+        if !properties.is_empty() {
+            f.write_str("]")?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,346 @@
+use bevy::pbr::{FogFalloff, FogSettings};
+use bevy::prelude::*;
+
+use crate::console::{ConsoleAppExt, ConsoleCommand};
+use crate::daynight::{SkyConditions, TimeOfDay};
+use crate::voxel::Voxel;
+use crate::world::{HeightMap, VoxelWorld};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeatherKind {
+    Clear,
+    Overcast,
+    Rain,
+    Snow,
+    Fog,
+}
+
+impl WeatherKind {
+    pub const ALL: [WeatherKind; 5] = [
+        WeatherKind::Clear,
+        WeatherKind::Overcast,
+        WeatherKind::Rain,
+        WeatherKind::Snow,
+        WeatherKind::Fog,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "clear",
+            WeatherKind::Overcast => "overcast",
+            WeatherKind::Rain => "rain",
+            WeatherKind::Snow => "snow",
+            WeatherKind::Fog => "fog",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn profile(self) -> WeatherProfile {
+        let (fog_density, light_factor, haze, rain, snow) = match self {
+            WeatherKind::Clear => (0.002, 1.0, 0.0, 0.0, 0.0),
+            WeatherKind::Overcast => (0.006, 0.45, 0.7, 0.0, 0.0),
+            WeatherKind::Rain => (0.012, 0.3, 0.85, 1.0, 0.0),
+            WeatherKind::Snow => (0.015, 0.4, 0.8, 0.0, 1.0),
+            WeatherKind::Fog => (0.05, 0.55, 0.6, 0.0, 0.0),
+        };
+        WeatherProfile {
+            fog_density,
+            light_factor,
+            haze,
+            rain,
+            snow,
+        }
+    }
+
+    /// States reachable from this one, with relative weights. Precipitation
+    /// always passes through overcast on the way in and out.
+    pub fn transitions(self) -> &'static [(WeatherKind, u32)] {
+        match self {
+            WeatherKind::Clear => &[(WeatherKind::Overcast, 3), (WeatherKind::Fog, 1)],
+            WeatherKind::Overcast => &[
+                (WeatherKind::Clear, 3),
+                (WeatherKind::Rain, 3),
+                (WeatherKind::Snow, 1),
+                (WeatherKind::Fog, 1),
+            ],
+            WeatherKind::Rain | WeatherKind::Snow => &[(WeatherKind::Overcast, 1)],
+            WeatherKind::Fog => &[(WeatherKind::Clear, 2), (WeatherKind::Overcast, 1)],
+        }
+    }
+}
+
+/// Rendering parameters for one weather state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeatherProfile {
+    /// Exponential-squared distance fog density.
+    pub fog_density: f32,
+    /// Multiplier on sun and moon illuminance.
+    pub light_factor: f32,
+    /// How grey the sky turns.
+    pub haze: f32,
+    /// Rain intensity in `0..=1`.
+    pub rain: f32,
+    /// Snowfall intensity in `0..=1`.
+    pub snow: f32,
+}
+
+impl WeatherProfile {
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self {
+            fog_density: mix(self.fog_density, other.fog_density),
+            light_factor: mix(self.light_factor, other.light_factor),
+            haze: mix(self.haze, other.haze),
+            rain: mix(self.rain, other.rain),
+            snow: mix(self.snow, other.snow),
+        }
+    }
+}
+
+/// Weather state machine. Each state holds for a random duration, then
+/// blends into a weighted random successor over `transition_secs`.
+#[derive(Resource, Clone, Debug)]
+pub struct Weather {
+    current: WeatherKind,
+    next: Option<WeatherKind>,
+    /// Progress of the blend from `current` (or `from`) to `next`, in
+    /// `0..=1`.
+    blend: f32,
+    /// What was on screen when [`Weather::set`] interrupted a transition;
+    /// the blend to `next` starts from here instead of from `current`.
+    from: Option<WeatherProfile>,
+    /// Seconds left before the next transition starts.
+    remaining: f32,
+    seed: u32,
+    pub transition_secs: f32,
+    pub min_hold_secs: f32,
+    pub max_hold_secs: f32,
+    /// Stops automatic transitions; explicit [`Weather::set`] still works.
+    pub locked: bool,
+    /// Let falling snow settle as snow voxels on exposed tops.
+    pub accumulate_snow: bool,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new(WeatherKind::Clear, 0x2545_f491)
+    }
+}
+
+impl Weather {
+    pub fn new(kind: WeatherKind, seed: u32) -> Self {
+        let mut weather = Self {
+            current: kind,
+            next: None,
+            blend: 0.0,
+            from: None,
+            remaining: 0.0,
+            seed: seed.max(1),
+            transition_secs: 20.0,
+            min_hold_secs: 60.0,
+            max_hold_secs: 180.0,
+            locked: false,
+            accumulate_snow: false,
+        };
+        weather.remaining = weather.roll_hold();
+        weather
+    }
+
+    /// Reads `--weather KIND`, `--lock-weather` and `--snow-cover` from the command line.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut weather = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--weather" => {
+                    let raw = args.next().ok_or("--weather expects a value")?;
+                    let kind = WeatherKind::parse(&raw)
+                        .ok_or_else(|| format!("unknown weather {raw:?}"))?;
+                    weather = Self {
+                        locked: weather.locked,
+                        accumulate_snow: weather.accumulate_snow,
+                        ..Self::new(kind, weather.seed)
+                    };
+                }
+                "--lock-weather" => weather.locked = true,
+                "--snow-cover" => weather.accumulate_snow = true,
+                _ => {}
+            }
+        }
+        Ok(weather)
+    }
+
+    pub fn current(&self) -> WeatherKind {
+        self.current
+    }
+
+    /// The state being blended towards, if a transition is under way.
+    pub fn next(&self) -> Option<WeatherKind> {
+        self.next
+    }
+
+    /// Starts blending towards `kind` right away. A transition under way is
+    /// cut short and the new blend starts from the mix currently shown.
+    pub fn set(&mut self, kind: WeatherKind) {
+        self.remaining = self.roll_hold();
+        if self.next.is_none() && kind == self.current {
+            return;
+        }
+        if self.next.is_some() {
+            self.from = Some(self.profile());
+        }
+        self.next = Some(kind);
+        self.blend = 0.0;
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if let Some(next) = self.next {
+            self.blend += dt / self.transition_secs.max(f32::EPSILON);
+            if self.blend >= 1.0 {
+                self.current = next;
+                self.next = None;
+                self.blend = 0.0;
+                self.from = None;
+                self.remaining = self.roll_hold();
+            }
+            return;
+        }
+        if self.locked {
+            return;
+        }
+        self.remaining -= dt;
+        if self.remaining <= 0.0 {
+            self.next = Some(self.roll_successor());
+            self.blend = 0.0;
+        }
+    }
+
+    /// Current rendering parameters, blended mid-transition.
+    pub fn profile(&self) -> WeatherProfile {
+        let from = self.from.unwrap_or(self.current.profile());
+        match self.next {
+            Some(next) => from.lerp(next.profile(), self.blend),
+            None => from,
+        }
+    }
+
+    fn roll_hold(&mut self) -> f32 {
+        let t = (next_random(&mut self.seed) % 1000) as f32 / 1000.0;
+        self.min_hold_secs + (self.max_hold_secs - self.min_hold_secs) * t
+    }
+
+    fn roll_successor(&mut self) -> WeatherKind {
+        let options = self.current.transitions();
+        let total: u32 = options.iter().map(|(_, weight)| weight).sum();
+        let mut pick = next_random(&mut self.seed) % total;
+        for &(kind, weight) in options {
+            if pick < weight {
+                return kind;
+            }
+            pick -= weight;
+        }
+        options[0].0
+    }
+}
+
+/// Xorshift step shared by the weather systems.
+pub fn next_random(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+/// Whether something in the column above `pos` stops rain and snow.
+pub fn is_sheltered(heights: &HeightMap, pos: Vec3) -> bool {
+    let cell = pos.floor().as_ivec3();
+    heights.top(cell.x, cell.z).is_some_and(|top| cell.y <= top)
+}
+
+/// Cell where a snow layer settles in column `(x, z)`: right above the
+/// column's top, if that top is solid and not already snow.
+pub fn snow_layer_cell(world: &VoxelWorld, heights: &HeightMap, x: i32, z: i32) -> Option<IVec3> {
+    let top = heights.top(x, z)?;
+    let surface = world.get(IVec3::new(x, top, z));
+    if !surface.is_solid() || surface == Voxel::SNOW {
+        return None;
+    }
+    let cell = IVec3::new(x, top + 1, z);
+    world.get(cell).is_empty().then_some(cell)
+}
+
+/// Weather state machine driving fog, sky haze and sun dimming.
+pub struct WeatherPlugin {
+    pub initial: Weather,
+}
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.initial.clone())
+            .add_systems(
+                Update,
+                (advance_weather, apply_weather.after(advance_weather)),
+            )
+            .register_console_command(ConsoleCommand {
+                name: "weather",
+                usage: "[clear|overcast|rain|snow|fog]",
+                help: "show the weather or blend into another",
+                run: weather_command,
+            });
+    }
+}
+
+fn weather_command(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut weather = world.resource_mut::<Weather>();
+    match args {
+        [] => Ok(format!("weather is {}", weather.current().name())),
+        [name] => {
+            let kind =
+                WeatherKind::parse(name).ok_or_else(|| format!("unknown weather {name:?}"))?;
+            weather.set(kind);
+            Ok(format!("weather turning {}", kind.name()))
+        }
+        _ => Err("usage: weather [clear|overcast|rain|snow|fog]".into()),
+    }
+}
+
+fn advance_weather(time: Res<Time>, mut weather: ResMut<Weather>) {
+    weather.advance(time.delta_seconds());
+}
+
+fn apply_weather(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    time_of_day: Res<TimeOfDay>,
+    mut sky: ResMut<SkyConditions>,
+    mut cameras: Query<(Entity, Option<&mut FogSettings>), With<Camera3d>>,
+) {
+    let profile = weather.profile();
+    sky.light_factor = profile.light_factor;
+    sky.haze = profile.haze;
+
+    let fog_color = sky.apply_haze(time_of_day.sky_color());
+    let falloff = FogFalloff::ExponentialSquared {
+        density: profile.fog_density,
+    };
+    for (entity, fog) in &mut cameras {
+        match fog {
+            Some(mut fog) => {
+                fog.color = fog_color;
+                fog.falloff = falloff.clone();
+            }
+            None => {
+                commands.entity(entity).insert(FogSettings {
+                    color: fog_color,
+                    falloff: falloff.clone(),
+                    ..default()
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec2, IVec3};
+
+use super::{chunk_coord, VoxelWorld, CHUNK_SIZE};
+
+/// Highest non-empty voxel of every column, kept in sync with voxel edits.
+///
+/// Columns are keyed by world `(x, z)`; columns without any voxel have no
+/// entry.
+#[derive(Clone, Default)]
+pub struct HeightMap {
+    tops: HashMap<IVec2, i32>,
+    /// Lowest voxel y that can hold a voxel, bounding downward rescans.
+    floor: i32,
+}
+
+impl HeightMap {
+    pub fn build(world: &VoxelWorld) -> Self {
+        let size = CHUNK_SIZE as i32;
+        let mut map = Self::default();
+        let mut floor = None::<i32>;
+        for (coord, chunk) in world.chunks() {
+            let base = coord * size;
+            floor = Some(floor.map_or(base.y, |f| f.min(base.y)));
+            for z in 0..size {
+                for x in 0..size {
+                    let top = (0..size)
+                        .rev()
+                        .find(|&y| !chunk.get(x as u32, y as u32, z as u32).is_empty());
+                    if let Some(y) = top {
+                        let column = IVec2::new(base.x + x, base.z + z);
+                        let entry = map.tops.entry(column).or_insert(base.y + y);
+                        *entry = (*entry).max(base.y + y);
+                    }
+                }
+            }
+        }
+        map.floor = floor.unwrap_or(0);
+        map
+    }
+
+    /// Y of the highest non-empty voxel in column `(x, z)`.
+    pub fn top(&self, x: i32, z: i32) -> Option<i32> {
+        self.tops.get(&IVec2::new(x, z)).copied()
+    }
+
+    /// Refreshes the column containing `pos` after the world changed there.
+    pub fn update(&mut self, world: &VoxelWorld, pos: IVec3) {
+        self.floor = self.floor.min(chunk_coord(pos).y * CHUNK_SIZE as i32);
+        let column = IVec2::new(pos.x, pos.z);
+        let top = self.tops.get(&column).copied();
+        if !world.get(pos).is_empty() {
+            if top.is_none_or(|t| pos.y > t) {
+                self.tops.insert(column, pos.y);
+            }
+        } else if top == Some(pos.y) {
+            let below = (self.floor..pos.y)
+                .rev()
+                .find(|&y| !world.get(IVec3::new(pos.x, y, pos.z)).is_empty());
+            match below {
+                Some(y) => self.tops.insert(column, y),
+                None => self.tops.remove(&column),
+            };
+        }
+    }
+
+    /// Refreshes column `(x, z)` after any of its voxels from `min_y` to
+    /// `max_y` changed.
+    pub fn update_span(&mut self, world: &VoxelWorld, x: i32, z: i32, min_y: i32, max_y: i32) {
+        self.floor = self
+            .floor
+            .min(chunk_coord(IVec3::new(x, min_y, z)).y * CHUNK_SIZE as i32);
+        let column = IVec2::new(x, z);
+        if self.tops.get(&column).is_some_and(|&top| top > max_y) {
+            return;
+        }
+        let top = (self.floor..=max_y)
+            .rev()
+            .find(|&y| !world.get(IVec3::new(x, y, z)).is_empty());
+        match top {
+            Some(y) => self.tops.insert(column, y),
+            None => self.tops.remove(&column),
+        };
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec3, UVec3};
+
+use super::{Chunk, CHUNK_SIZE};
+use crate::voxel::Voxel;
+
+/// Sparse collection of chunks addressed by chunk coordinate.
+///
+/// Lookups use world voxel coordinates and transparently cross chunk
+/// boundaries; missing chunks read as air.
+#[derive(Clone, Default)]
+pub struct VoxelWorld {
+    chunks: HashMap<IVec3, Chunk>,
+}
+
+/// Chunk coordinate containing the given world voxel position.
+#[inline]
+pub fn chunk_coord(pos: IVec3) -> IVec3 {
+    pos.div_euclid(IVec3::splat(CHUNK_SIZE as i32))
+}
+
+/// Position of a world voxel inside its chunk.
+#[inline]
+pub fn local_coord(pos: IVec3) -> UVec3 {
+    pos.rem_euclid(IVec3::splat(CHUNK_SIZE as i32)).as_uvec3()
+}
+
+/// Chunks whose meshes can depend on the voxel at `pos`: its own chunk plus
+/// any neighbour (including edge and corner neighbours) it borders.
+pub fn chunks_near(pos: IVec3) -> Vec<IVec3> {
+    let size = CHUNK_SIZE as i32;
+    let local = pos.rem_euclid(IVec3::splat(size));
+    let offsets = |l: i32| -> &'static [i32] {
+        if l == 0 {
+            &[0, -1]
+        } else if l == size - 1 {
+            &[0, 1]
+        } else {
+            &[0]
+        }
+    };
+    let base = chunk_coord(pos);
+    let mut result = Vec::new();
+    for &dz in offsets(local.z) {
+        for &dy in offsets(local.y) {
+            for &dx in offsets(local.x) {
+                result.push(base + IVec3::new(dx, dy, dz));
+            }
+        }
+    }
+    result
+}
+
+impl VoxelWorld {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert_chunk(&mut self, coord: IVec3, chunk: Chunk) {
+        self.chunks.insert(coord, chunk);
+    }
+
+    pub fn chunk(&self, coord: IVec3) -> Option<&Chunk> {
+        self.chunks.get(&coord)
+    }
+
+    pub fn chunk_mut(&mut self, coord: IVec3) -> Option<&mut Chunk> {
+        self.chunks.get_mut(&coord)
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (IVec3, &Chunk)> {
+        self.chunks.iter().map(|(coord, chunk)| (*coord, chunk))
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn get(&self, pos: IVec3) -> Voxel {
+        match self.chunks.get(&chunk_coord(pos)) {
+            Some(chunk) => {
+                let local = local_coord(pos);
+                chunk.get(local.x, local.y, local.z)
+            }
+            None => Voxel::AIR,
+        }
+    }
+
+    /// Writes a voxel, creating the owning chunk if needed. Returns the previous value.
+    pub fn set(&mut self, pos: IVec3, v: Voxel) -> Voxel {
+        let coord = chunk_coord(pos);
+        if v.is_empty() && !self.chunks.contains_key(&coord) {
+            return Voxel::AIR;
+        }
+        let chunk = self.chunks.entry(coord).or_default();
+        let local = local_coord(pos);
+        let previous = chunk.get(local.x, local.y, local.z);
+        chunk.set(local.x, local.y, local.z, v);
+        previous
+    }
+
+    pub fn is_solid(&self, pos: IVec3) -> bool {
+        self.get(pos).is_solid()
+    }
+}
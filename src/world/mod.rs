@@ -1,17 +1,56 @@
+mod heightmap;
+mod map;
+mod save;
+
+pub use heightmap::HeightMap;
+pub use map::{chunk_coord, chunks_near, local_coord, VoxelWorld};
+
 use crate::voxel::Voxel;
 
 pub const CHUNK_SIZE: usize = 32;
 pub const CHUNK_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
 
+/// How a chunk currently holds its voxels; see [`Chunk::storage`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChunkStorage {
+    /// One voxel repeated through the whole chunk.
+    Uniform,
+    /// A block id per voxel; every voxel has empty state.
+    Plain,
+    /// A full voxel, id and state, per voxel.
+    Full,
+}
+
+#[derive(Clone)]
+enum Voxels {
+    Uniform(Voxel),
+    Plain(Box<[u8]>),
+    Full(Box<[Voxel]>),
+}
+
+/// A cube of [`CHUNK_SIZE`] voxels. Chunks start out uniform, take a byte
+/// per voxel once they hold different plain blocks and only switch to full
+/// voxels when a block with state is written, so most terrain stays small.
 #[derive(Clone)]
 pub struct Chunk {
-    data: Vec<Voxel>,
+    data: Voxels,
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Chunk {
     pub fn new() -> Self {
+        Self::filled(Voxel::AIR)
+    }
+
+    /// A chunk made entirely of `voxel`.
+    pub fn filled(voxel: Voxel) -> Self {
         Self {
-            data: vec![Voxel::AIR; CHUNK_VOLUME],
+            data: Voxels::Uniform(voxel),
         }
     }
 
@@ -20,6 +59,15 @@ impl Chunk {
         (x as usize) + (y as usize) * CHUNK_SIZE + (z as usize) * CHUNK_SIZE * CHUNK_SIZE
     }
 
+    /// Inverse of [`Chunk::index`].
+    #[inline]
+    pub fn position(index: usize) -> (u32, u32, u32) {
+        let x = index % CHUNK_SIZE;
+        let y = (index / CHUNK_SIZE) % CHUNK_SIZE;
+        let z = index / (CHUNK_SIZE * CHUNK_SIZE);
+        (x as u32, y as u32, z as u32)
+    }
+
     #[inline]
     pub fn in_bounds(x: u32, y: u32, z: u32) -> bool {
         x < CHUNK_SIZE as u32 && y < CHUNK_SIZE as u32 && z < CHUNK_SIZE as u32
@@ -29,7 +77,16 @@ impl Chunk {
         if !Self::in_bounds(x, y, z) {
             return Voxel::AIR;
         }
-        self.data[Self::index(x, y, z)]
+        self.at(Self::index(x, y, z))
+    }
+
+    #[inline]
+    fn at(&self, index: usize) -> Voxel {
+        match &self.data {
+            Voxels::Uniform(voxel) => *voxel,
+            Voxels::Plain(ids) => Voxel::new(ids[index], 0),
+            Voxels::Full(voxels) => voxels[index],
+        }
     }
 
     pub fn set(&mut self, x: u32, y: u32, z: u32, v: Voxel) {
@@ -37,6 +94,62 @@ impl Chunk {
             return;
         }
         let idx = Self::index(x, y, z);
-        self.data[idx] = v;
+        match &mut self.data {
+            Voxels::Uniform(voxel) if *voxel == v => return,
+            Voxels::Plain(ids) if v.state() == 0 => {
+                ids[idx] = v.id();
+                return;
+            }
+            Voxels::Full(voxels) => {
+                voxels[idx] = v;
+                return;
+            }
+            _ => {}
+        }
+        // The current storage cannot hold `v`: widen it and retry.
+        let widened = match &self.data {
+            Voxels::Uniform(voxel) if voxel.state() == 0 && v.state() == 0 => {
+                Voxels::Plain(vec![voxel.id(); CHUNK_VOLUME].into())
+            }
+            _ => Voxels::Full(self.voxels().collect()),
+        };
+        self.data = widened;
+        self.set(x, y, z, v);
+    }
+
+    /// How the voxels are currently stored.
+    pub fn storage(&self) -> ChunkStorage {
+        match self.data {
+            Voxels::Uniform(_) => ChunkStorage::Uniform,
+            Voxels::Plain(_) => ChunkStorage::Plain,
+            Voxels::Full(_) => ChunkStorage::Full,
+        }
+    }
+
+    /// Switches to the smallest storage that holds the current voxels.
+    /// Storage only widens as voxels are set, so call this once a chunk is
+    /// finished, like after generation.
+    pub fn compact(&mut self) {
+        let first = self.at(0);
+        if self.voxels().all(|voxel| voxel == first) {
+            self.data = Voxels::Uniform(first);
+        } else if self.voxels().all(|voxel| voxel.state() == 0) {
+            let ids = self.voxels().map(Voxel::id).collect();
+            self.data = Voxels::Plain(ids);
+        }
+    }
+
+    /// True when every voxel is air.
+    pub fn is_empty(&self) -> bool {
+        match &self.data {
+            Voxels::Uniform(voxel) => voxel.is_empty(),
+            Voxels::Plain(ids) => ids.iter().all(|&id| id == 0),
+            Voxels::Full(voxels) => voxels.iter().all(|v| v.is_empty()),
+        }
+    }
+
+    /// Every voxel in [`Chunk::index`] order.
+    pub fn voxels(&self) -> impl Iterator<Item = Voxel> + '_ {
+        (0..CHUNK_VOLUME).map(|index| self.at(index))
     }
 }
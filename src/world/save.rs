@@ -0,0 +1,79 @@
+use std::io::{self, Read, Write};
+
+use bevy::math::IVec3;
+
+use super::{Chunk, VoxelWorld, CHUNK_VOLUME};
+use crate::voxel::Voxel;
+
+const MAGIC: &[u8; 4] = b"PLTZ";
+const VERSION: u8 = 1;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+impl VoxelWorld {
+    /// Writes every chunk to `out`. The file starts with a magic number,
+    /// version and chunk count; each chunk is its coordinate followed by
+    /// runs of `(length, voxel)`, all little endian.
+    pub fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        out.write_all(&(self.chunk_count() as u32).to_le_bytes())?;
+        for (coord, chunk) in self.chunks() {
+            for axis in coord.to_array() {
+                out.write_all(&axis.to_le_bytes())?;
+            }
+            let mut voxels = chunk.voxels().peekable();
+            while let Some(voxel) = voxels.next() {
+                let mut run: u16 = 1;
+                while voxels.next_if_eq(&voxel).is_some() {
+                    run += 1;
+                }
+                out.write_all(&run.to_le_bytes())?;
+                out.write_all(&voxel.0.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a world written by [`VoxelWorld::write_to`].
+    pub fn read_from(input: &mut impl Read) -> io::Result<Self> {
+        if &read_array::<4>(input)? != MAGIC {
+            return Err(invalid("not a world file"));
+        }
+        let [version] = read_array::<1>(input)?;
+        if version != VERSION {
+            return Err(invalid("unsupported world file version"));
+        }
+        let count = u32::from_le_bytes(read_array(input)?);
+        let mut world = VoxelWorld::new();
+        for _ in 0..count {
+            let [x, y, z] = [(); 3].map(|()| read_array(input).map(i32::from_le_bytes));
+            let coord = IVec3::new(x?, y?, z?);
+            let mut chunk = Chunk::new();
+            let mut index = 0;
+            while index < CHUNK_VOLUME {
+                let run = u16::from_le_bytes(read_array(input)?) as usize;
+                let voxel = Voxel(u16::from_le_bytes(read_array(input)?));
+                if run == 0 || index + run > CHUNK_VOLUME {
+                    return Err(invalid("corrupt chunk"));
+                }
+                for index in index..index + run {
+                    let (x, y, z) = Chunk::position(index);
+                    chunk.set(x, y, z, voxel);
+                }
+                index += run;
+            }
+            chunk.compact();
+            world.insert_chunk(coord, chunk);
+        }
+        Ok(world)
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec2, IVec3, Vec2};
+
+use super::{fbm, hash, lattice, TerrainGenerator};
+use crate::voxel::Voxel;
+use crate::world::{Chunk, CHUNK_SIZE};
+
+/// Columns at or above this height are highland.
+const HIGHLAND_HEIGHT: i32 = 25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Biome {
+    /// Beaches, lake beds and river beds; nothing grows here.
+    Shore,
+    Plains,
+    Forest,
+    Highland,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecorationKind {
+    Tree,
+    Bush,
+    Grass,
+}
+
+impl DecorationKind {
+    pub const ALL: [DecorationKind; 3] = [
+        DecorationKind::Tree,
+        DecorationKind::Bush,
+        DecorationKind::Grass,
+    ];
+
+    /// Minimum distance between two decorations of this kind, which is also
+    /// the size of the sampling grid cell.
+    pub fn spacing(self) -> i32 {
+        match self {
+            DecorationKind::Tree => 6,
+            DecorationKind::Bush => 3,
+            DecorationKind::Grass => 2,
+        }
+    }
+
+    /// Largest height difference to a neighbouring column it accepts.
+    pub fn max_slope(self) -> i32 {
+        match self {
+            DecorationKind::Tree => 1,
+            DecorationKind::Bush | DecorationKind::Grass => 2,
+        }
+    }
+
+    /// How far the footprint reaches sideways from the base column.
+    pub fn reach(self) -> i32 {
+        match self {
+            DecorationKind::Tree => 3,
+            DecorationKind::Bush => 1,
+            DecorationKind::Grass => 0,
+        }
+    }
+
+    /// Chance that a sample point in `biome` grows this kind.
+    fn density(self, biome: Biome) -> f32 {
+        match (self, biome) {
+            (_, Biome::Shore) => 0.0,
+            (DecorationKind::Tree, Biome::Plains) => 0.12,
+            (DecorationKind::Tree, Biome::Forest) => 0.85,
+            (DecorationKind::Tree, Biome::Highland) => 0.05,
+            (DecorationKind::Bush, Biome::Plains) => 0.25,
+            (DecorationKind::Bush, Biome::Forest) => 0.35,
+            (DecorationKind::Bush, Biome::Highland) => 0.4,
+            (DecorationKind::Grass, Biome::Plains) => 0.7,
+            (DecorationKind::Grass, Biome::Forest) => 0.35,
+            (DecorationKind::Grass, Biome::Highland) => 0.15,
+        }
+    }
+
+    fn salt(self) -> u32 {
+        match self {
+            DecorationKind::Tree => 0x7e3a,
+            DecorationKind::Bush => 0xb0b5,
+            DecorationKind::Grass => 0x9a55,
+        }
+    }
+}
+
+/// A tree, bush or plant rooted on the ground.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Decoration {
+    pub kind: DecorationKind,
+    /// First empty cell above the ground.
+    pub base: IVec3,
+    pub biome: Biome,
+    /// Hash picking the shape variation.
+    pub variant: u32,
+}
+
+impl Decoration {
+    /// Every voxel the decoration places, in world coordinates.
+    pub fn voxels(&self) -> Vec<(IVec3, Voxel)> {
+        let mut voxels = Vec::new();
+        match self.kind {
+            DecorationKind::Tree => {
+                let big = self.biome == Biome::Forest && self.variant % 5 < 2;
+                let trunk = if big { 6 } else { 4 } + (self.variant >> 4) as i32 % 3;
+                let radius: i32 = if big { 3 } else { 2 };
+                for y in 0..trunk {
+                    voxels.push((self.base + IVec3::Y * y, Voxel::LOG));
+                }
+                // Two wide layers around the top of the trunk, two narrower
+                // ones above it; the outer ring is trimmed at random.
+                for dy in -2..=1 {
+                    let layer_radius = if dy < 0 { radius } else { radius - 1 };
+                    let y = trunk + dy;
+                    for dz in -layer_radius..=layer_radius {
+                        for dx in -layer_radius..=layer_radius {
+                            let distance = dx * dx + dz * dz;
+                            if distance > layer_radius * layer_radius + 1 {
+                                continue;
+                            }
+                            let outer = distance > (layer_radius - 1).pow(2) + 1;
+                            if outer && lattice(self.variant, dx + y * 16, dz) < 0.4 {
+                                continue;
+                            }
+                            voxels.push((self.base + IVec3::new(dx, y, dz), Voxel::LEAVES));
+                        }
+                    }
+                }
+            }
+            DecorationKind::Bush => {
+                voxels.push((self.base, Voxel::LEAVES));
+                let side =
+                    [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z][self.variant as usize % 4];
+                if self.variant & 0x10 != 0 {
+                    voxels.push((self.base + side, Voxel::LEAVES));
+                }
+                if self.variant & 0x20 != 0 {
+                    voxels.push((self.base + IVec3::Y, Voxel::LEAVES));
+                }
+            }
+            DecorationKind::Grass => voxels.push((self.base, Voxel::TALL_GRASS)),
+        }
+        voxels
+    }
+}
+
+/// Grid sample point of one decoration kind.
+#[derive(Clone, Copy)]
+struct Candidate {
+    decoration: Decoration,
+    priority: (u32, i32, i32),
+}
+
+impl TerrainGenerator {
+    pub fn biome(&self, world_x: i32, world_z: i32) -> Biome {
+        let top = self.surface_height(world_x as f32, world_z as f32).floor() as i32;
+        if top <= self.sea_level + self.beach_height {
+            return Biome::Shore;
+        }
+        if top >= HIGHLAND_HEIGHT {
+            return Biome::Highland;
+        }
+        let moisture = fbm(
+            self.seed.wrapping_add(0xf0e5),
+            world_x as f32 * 0.015,
+            world_z as f32 * 0.015,
+        );
+        if moisture > 0.55 {
+            Biome::Forest
+        } else {
+            Biome::Plains
+        }
+    }
+
+    /// Decorations whose footprint may reach into the chunk column at
+    /// `column` (chunk `x`, `z`).
+    ///
+    /// Each kind is Poisson-disc sampled: every grid cell of size
+    /// [`DecorationKind::spacing`] holds one hashed sample point, and a point
+    /// survives only if no eligible point closer than the spacing has a
+    /// higher hashed priority. Whether a point grows depends on nothing but
+    /// the seed and the terrain around it, so every chunk column sees the
+    /// same decorations near its borders.
+    pub fn column_decorations(&self, column: IVec2) -> Vec<Decoration> {
+        let size = CHUNK_SIZE as i32;
+        let mut decorations = Vec::new();
+        for kind in DecorationKind::ALL {
+            let spacing = kind.spacing();
+            let min = column * size - IVec2::splat(kind.reach());
+            let max = (column + IVec2::ONE) * size - IVec2::ONE + IVec2::splat(kind.reach());
+            let mut candidates = HashMap::new();
+            for cz in min.y.div_euclid(spacing)..=max.y.div_euclid(spacing) {
+                for cx in min.x.div_euclid(spacing)..=max.x.div_euclid(spacing) {
+                    let cell = IVec2::new(cx, cz);
+                    let Some(candidate) = self.candidate(kind, cell, &mut candidates) else {
+                        continue;
+                    };
+                    let base = candidate.decoration.base;
+                    let inside =
+                        base.x >= min.x && base.x <= max.x && base.z >= min.y && base.z <= max.y;
+                    if inside && self.survives(kind, cell, candidate, &mut candidates) {
+                        decorations.push(candidate.decoration);
+                    }
+                }
+            }
+        }
+        decorations
+    }
+
+    /// Writes the parts of `decorations` that fall inside the chunk at
+    /// `coord`. Logs win over leaves and leaves over grass, and nothing
+    /// replaces terrain or water, so overlapping decorations give the same
+    /// result in any order.
+    pub fn decorate_chunk(&self, coord: IVec3, chunk: &mut Chunk, decorations: &[Decoration]) {
+        let size = CHUNK_SIZE as i32;
+        let base = coord * size;
+        for decoration in decorations {
+            for (pos, voxel) in decoration.voxels() {
+                let local = pos - base;
+                if local.cmplt(IVec3::ZERO).any() || local.cmpge(IVec3::splat(size)).any() {
+                    continue;
+                }
+                let (x, y, z) = (local.x as u32, local.y as u32, local.z as u32);
+                if rank(voxel) > rank(chunk.get(x, y, z)) {
+                    chunk.set(x, y, z, voxel);
+                }
+            }
+        }
+    }
+
+    /// The sample point of `kind` in grid cell `cell`, if it is eligible to
+    /// grow.
+    fn candidate(
+        &self,
+        kind: DecorationKind,
+        cell: IVec2,
+        cache: &mut HashMap<IVec2, Option<Candidate>>,
+    ) -> Option<Candidate> {
+        *cache.entry(cell).or_insert_with(|| self.sample(kind, cell))
+    }
+
+    fn sample(&self, kind: DecorationKind, cell: IVec2) -> Option<Candidate> {
+        let seed = self.seed.wrapping_mul(0x2f6b).wrapping_add(kind.salt());
+        let spacing = kind.spacing();
+        let offset = IVec2::new(
+            (hash(seed, cell.x, cell.y) % spacing as u32) as i32,
+            (hash(seed.wrapping_add(1), cell.x, cell.y) % spacing as u32) as i32,
+        );
+        let (x, z) = (cell.x * spacing + offset.x, cell.y * spacing + offset.y);
+        let point = Vec2::new(x as f32 + 0.5, z as f32 + 0.5);
+        if self
+            .clearings
+            .iter()
+            .any(|&(centre, radius)| point.distance(centre) < radius)
+        {
+            return None;
+        }
+
+        let biome = self.biome(x, z);
+        if lattice(seed.wrapping_add(2), cell.x, cell.y) >= kind.density(biome) {
+            return None;
+        }
+        let top = self.surface_height(x as f32, z as f32).floor() as i32;
+        let steep = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().any(|&(dx, dz)| {
+            let neighbour = self
+                .surface_height((x + dx) as f32, (z + dz) as f32)
+                .floor() as i32;
+            (neighbour - top).abs() > kind.max_slope()
+        });
+        if steep {
+            return None;
+        }
+        Some(Candidate {
+            decoration: Decoration {
+                kind,
+                base: IVec3::new(x, top + 1, z),
+                biome,
+                variant: hash(seed.wrapping_add(3), x, z),
+            },
+            priority: (hash(seed.wrapping_add(4), cell.x, cell.y), cell.x, cell.y),
+        })
+    }
+
+    /// Whether no eligible neighbour within the spacing outranks `candidate`.
+    fn survives(
+        &self,
+        kind: DecorationKind,
+        cell: IVec2,
+        candidate: Candidate,
+        cache: &mut HashMap<IVec2, Option<Candidate>>,
+    ) -> bool {
+        let spacing = kind.spacing();
+        let base = candidate.decoration.base;
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dz == 0 {
+                    continue;
+                }
+                let Some(other) = self.candidate(kind, cell + IVec2::new(dx, dz), cache) else {
+                    continue;
+                };
+                let delta = other.decoration.base - base;
+                let close = delta.x * delta.x + delta.z * delta.z < spacing * spacing;
+                if close && other.priority > candidate.priority {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Which voxel wins where decorations overlap.
+fn rank(voxel: Voxel) -> u8 {
+    match voxel {
+        Voxel::AIR => 0,
+        Voxel::TALL_GRASS => 1,
+        Voxel::LEAVES => 2,
+        Voxel::LOG => 3,
+        _ => 4,
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::VecDeque;
+
+use bevy::math::{IVec2, IVec3, Vec2};
+
+use super::{hash, lattice, TerrainGenerator};
+use crate::structure::{Connector, Placement, StructureTemplate};
+use crate::voxel::Voxel;
+use crate::world::{Chunk, CHUNK_SIZE};
+
+/// Side of the square regions that each hold at most one village. A village
+/// never leaves its region, so regions can be built independently.
+pub const VILLAGE_REGION: i32 = 96;
+
+/// Deepest foundation a piece may stand on.
+const MAX_FOUNDATION: i32 = 6;
+/// Spots tried for the start piece before a region gives up on its village.
+const START_ATTEMPTS: u32 = 12;
+
+const HALL: &str = include_str!("../../assets/structures/village/hall.txt");
+const HOUSE: &str = include_str!("../../assets/structures/village/house.txt");
+const CORRIDOR: &str = include_str!("../../assets/structures/village/corridor.txt");
+const CORNER: &str = include_str!("../../assets/structures/village/corner.txt");
+const YARD: &str = include_str!("../../assets/structures/village/yard.txt");
+
+/// A template that can be joined to others through its connectors.
+#[derive(Clone, Debug)]
+pub struct JigsawPiece {
+    pub name: String,
+    pub template: StructureTemplate,
+    /// Relative chance of being picked for an open connector.
+    pub weight: u32,
+}
+
+/// The pieces a village is assembled from.
+#[derive(Clone, Debug)]
+pub struct JigsawLibrary {
+    pub pieces: Vec<JigsawPiece>,
+    /// Index of the piece every village starts from.
+    pub start: usize,
+    /// Most pieces in one village.
+    pub max_pieces: usize,
+    /// Most connections between the start piece and any other piece.
+    pub max_depth: u32,
+}
+
+impl JigsawLibrary {
+    /// Builds a library from `(name, template source, weight)` entries.
+    pub fn parse(entries: &[(&str, &str, u32)], start: usize) -> Result<Self, String> {
+        let pieces = entries
+            .iter()
+            .map(|&(name, source, weight)| {
+                Ok(JigsawPiece {
+                    name: name.to_string(),
+                    template: StructureTemplate::parse(source)
+                        .map_err(|err| format!("{name}: {err}"))?,
+                    weight,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if start >= pieces.len() {
+            return Err(format!("start piece {start} out of range"));
+        }
+        Ok(Self {
+            pieces,
+            start,
+            max_pieces: 12,
+            max_depth: 4,
+        })
+    }
+
+    /// Brick halls, houses, passages and walled yards.
+    pub fn village() -> Self {
+        Self::parse(
+            &[
+                ("hall", HALL, 1),
+                ("house", HOUSE, 4),
+                ("corridor", CORRIDOR, 4),
+                ("corner", CORNER, 2),
+                ("yard", YARD, 2),
+            ],
+            0,
+        )
+        .expect("built-in village pieces")
+    }
+}
+
+/// One piece of an assembled village.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlacedPiece {
+    /// Index into [`JigsawLibrary::pieces`].
+    pub piece: usize,
+    pub placement: Placement,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Village {
+    pub region: IVec2,
+    pub pieces: Vec<PlacedPiece>,
+}
+
+impl TerrainGenerator {
+    /// The village in region `region`, if one was rolled there and its start
+    /// piece fits somewhere. The layout depends only on the seed, the region and the
+    /// terrain, so every chunk sees the same village.
+    ///
+    /// Pieces are added breadth first: each open connector tries weighted
+    /// random pieces in every orientation until one joins it door to door
+    /// without overlapping earlier pieces, leaving the region, touching a
+    /// clearing or needing more than a short foundation.
+    pub fn village_in_region(&self, region: IVec2) -> Option<Village> {
+        let library = &self.village_pieces;
+        let seed = self.seed.wrapping_mul(0x3c6e).wrapping_add(0x7111);
+        if lattice(seed, region.x, region.y) >= self.village_chance {
+            return None;
+        }
+        let mut counter = 0;
+        let mut roll = |range: u32| {
+            counter += 1;
+            hash(seed, region.x ^ (counter << 8), region.y) % range.max(1)
+        };
+
+        // The hall needs fairly level ground; try a few spots for it. Its
+        // floor replaces the top ground voxel.
+        let margin = VILLAGE_REGION / 4;
+        let span = (VILLAGE_REGION - 2 * margin) as u32;
+        let start = (0..START_ATTEMPTS).find_map(|_| {
+            let xz = region * VILLAGE_REGION
+                + IVec2::new(margin + roll(span) as i32, margin + roll(span) as i32);
+            let start = PlacedPiece {
+                piece: library.start,
+                placement: Placement {
+                    origin: self.surface_cell(xz.x, xz.y) - IVec3::Y,
+                    quarter_turns: roll(4) as u8,
+                    mirror: false,
+                },
+            };
+            self.piece_fits(region, &start, &[]).then_some(start)
+        })?;
+
+        let mut pieces = vec![start];
+        let mut open: VecDeque<(Connector, u32)> = self
+            .piece_template(&start)
+            .connectors(&start.placement)
+            .into_iter()
+            .map(|connector| (connector, 1))
+            .collect();
+        while let Some((connector, depth)) = open.pop_front() {
+            if pieces.len() >= library.max_pieces {
+                break;
+            }
+            if depth > library.max_depth {
+                continue;
+            }
+            let total: u32 = library.pieces.iter().map(|piece| piece.weight).sum();
+            for _ in 0..4 {
+                let mut pick = roll(total);
+                let Some(index) = library.pieces.iter().position(|piece| {
+                    let hit = pick < piece.weight;
+                    pick = pick.saturating_sub(piece.weight);
+                    hit
+                }) else {
+                    break;
+                };
+                let first_turn = roll(4) as u8;
+                let Some((placed, used)) =
+                    self.join(region, &connector, index, first_turn, &pieces)
+                else {
+                    continue;
+                };
+                let connectors = self.piece_template(&placed).connectors(&placed.placement);
+                open.extend(
+                    connectors
+                        .into_iter()
+                        .enumerate()
+                        .filter(|&(i, _)| i != used)
+                        .map(|(_, connector)| (connector, depth + 1)),
+                );
+                pieces.push(placed);
+                break;
+            }
+        }
+        Some(Village { region, pieces })
+    }
+
+    /// Writes the parts of `villages` inside the chunk at `coord`, with
+    /// cobblestone foundations down to the ground under every floor.
+    pub fn build_villages(&self, coord: IVec3, chunk: &mut Chunk, villages: &[Village]) {
+        let size = CHUNK_SIZE as i32;
+        let base = coord * size;
+        let mut put = |pos: IVec3, voxel: Voxel| {
+            let local = pos - base;
+            if local.cmpge(IVec3::ZERO).all() && local.cmplt(IVec3::splat(size)).all() {
+                chunk.set(local.x as u32, local.y as u32, local.z as u32, voxel);
+            }
+        };
+        for village in villages {
+            for placed in &village.pieces {
+                let template = self.piece_template(placed);
+                let (min, max) = template.bounds(&placed.placement);
+                if max.x < base.x
+                    || max.z < base.z
+                    || min.x >= base.x + size
+                    || min.z >= base.z + size
+                {
+                    continue;
+                }
+                for (pos, voxel) in template.blocks(&placed.placement) {
+                    if pos.y == min.y && !voxel.is_empty() {
+                        let top = self.surface_height(pos.x as f32, pos.z as f32).floor() as i32;
+                        for y in top + 1..pos.y {
+                            put(IVec3::new(pos.x, y, pos.z), Voxel::COBBLESTONE);
+                        }
+                    }
+                    put(pos, voxel);
+                }
+            }
+        }
+    }
+
+    fn piece_template(&self, placed: &PlacedPiece) -> &StructureTemplate {
+        &self.village_pieces.pieces[placed.piece].template
+    }
+
+    /// Places piece `index` so one of its connectors meets `open` face to
+    /// face, trying every orientation starting at `first_turn`. Returns the
+    /// piece and the index of the connector it used.
+    fn join(
+        &self,
+        region: IVec2,
+        open: &Connector,
+        index: usize,
+        first_turn: u8,
+        placed: &[PlacedPiece],
+    ) -> Option<(PlacedPiece, usize)> {
+        let template = &self.village_pieces.pieces[index].template;
+        for mirror in [false, true] {
+            for turn in 0..4 {
+                let orientation = Placement {
+                    origin: IVec3::ZERO,
+                    quarter_turns: (first_turn + turn) % 4,
+                    mirror,
+                };
+                for (used, connector) in template.connectors(&orientation).iter().enumerate() {
+                    if connector.tag != open.tag || connector.facing != -open.facing {
+                        continue;
+                    }
+                    // Shift so the connector sits right outside the open one.
+                    let candidate = PlacedPiece {
+                        piece: index,
+                        placement: Placement {
+                            origin: open.pos + open.facing - connector.pos,
+                            ..orientation
+                        },
+                    };
+                    if self.piece_fits(region, &candidate, placed) {
+                        return Some((candidate, used));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn piece_fits(&self, region: IVec2, candidate: &PlacedPiece, placed: &[PlacedPiece]) -> bool {
+        let (min, max) = self.piece_template(candidate).bounds(&candidate.placement);
+        let region_min = region * VILLAGE_REGION;
+        let region_max = region_min + IVec2::splat(VILLAGE_REGION - 1);
+        if min.x < region_min.x
+            || min.z < region_min.y
+            || max.x > region_max.x
+            || max.z > region_max.y
+        {
+            return false;
+        }
+        let overlaps = placed.iter().any(|other| {
+            let (other_min, other_max) = self.piece_template(other).bounds(&other.placement);
+            min.x <= other_max.x
+                && max.x >= other_min.x
+                && min.z <= other_max.z
+                && max.z >= other_min.z
+        });
+        if overlaps {
+            return false;
+        }
+        let in_clearing = self.clearings.iter().any(|&(centre, radius)| {
+            let nearest = centre.clamp(
+                Vec2::new(min.x as f32, min.z as f32),
+                Vec2::new(max.x as f32 + 1.0, max.z as f32 + 1.0),
+            );
+            nearest.distance(centre) < radius
+        });
+        if in_clearing {
+            return false;
+        }
+        // Dry ground that neither buries the walls nor needs a deep
+        // foundation.
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                let top = self.surface_height(x as f32, z as f32).floor() as i32;
+                if top <= self.sea_level || top > min.y + 1 || top < min.y - MAX_FOUNDATION {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
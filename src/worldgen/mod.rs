@@ -0,0 +1,220 @@
+mod decoration;
+mod jigsaw;
+mod underground;
+
+pub use decoration::{Biome, Decoration, DecorationKind};
+pub use jigsaw::{JigsawLibrary, JigsawPiece, PlacedPiece, Village, VILLAGE_REGION};
+pub use underground::{Dungeon, OreVein, DUNGEON_REGION};
+
+use bevy::math::{IVec3, Vec2};
+use bevy::prelude::Resource;
+
+use crate::daynight::smoothstep;
+use crate::voxel::Voxel;
+use crate::world::{Chunk, CHUNK_SIZE};
+
+/// Rolling sine heightfield the generator starts from, before lakes and
+/// rivers are carved into it.
+pub fn base_height(world_x: f32, world_z: f32) -> f32 {
+    let coarse = (world_x * 0.05).sin() + (world_z * 0.05).cos();
+    let medium = ((world_x + world_z) * 0.02).sin();
+    let detail = (world_x * 0.14).cos() * (world_z * 0.14).sin();
+    let height = coarse * 4.5 + medium * 7.5 + detail * 2.0 + 14.0;
+    height.max(0.0)
+}
+
+/// Heightfield terrain with a sea level, noise-carved lakes and rivers, and
+/// sand along the shore.
+#[derive(Resource, Clone, Debug)]
+pub struct TerrainGenerator {
+    pub seed: u32,
+    /// Every open cell at or below this height fills with water.
+    pub sea_level: i32,
+    /// Fraction of the ridged river noise that becomes river bed.
+    pub river_width: f32,
+    /// Lake noise above this value turns into a lake basin.
+    pub lake_threshold: f32,
+    /// Columns whose top is at most this far above sea level are sand.
+    pub beach_height: i32,
+    /// Depth of the sand layer on beaches and lake beds.
+    pub sand_depth: i32,
+    /// Discs, as `(centre, radius)` in world `(x, z)`, kept free of trees
+    /// and plants.
+    pub clearings: Vec<(Vec2, f32)>,
+    /// Ores scattered by [`Self::populate_underground`].
+    pub ores: Vec<OreVein>,
+    /// Chance that a [`DUNGEON_REGION`] holds a dungeon.
+    pub dungeon_chance: f32,
+    /// Pieces villages are assembled from.
+    pub village_pieces: JigsawLibrary,
+    /// Chance that a [`VILLAGE_REGION`] holds a village.
+    pub village_chance: f32,
+}
+
+impl Default for TerrainGenerator {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            sea_level: 6,
+            river_width: 0.04,
+            lake_threshold: 0.74,
+            beach_height: 1,
+            sand_depth: 3,
+            clearings: Vec::new(),
+            ores: OreVein::table(),
+            dungeon_chance: 0.5,
+            village_pieces: JigsawLibrary::village(),
+            village_chance: 0.6,
+        }
+    }
+}
+
+impl TerrainGenerator {
+    /// Reads `--seed N` and `--sea-level N` from the command line.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut generator = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let raw = args.next().ok_or("--seed expects a value")?;
+                    generator.seed = raw.parse().map_err(|_| format!("invalid seed {raw:?}"))?;
+                }
+                "--sea-level" => {
+                    let raw = args.next().ok_or("--sea-level expects a value")?;
+                    generator.sea_level = raw
+                        .parse()
+                        .map_err(|_| format!("invalid sea level {raw:?}"))?;
+                }
+                _ => {}
+            }
+        }
+        Ok(generator)
+    }
+
+    /// How strongly `(x, z)` is carved by a river or lake, in `0..=1`.
+    pub fn water_carve(&self, world_x: f32, world_z: f32) -> f32 {
+        // Rivers follow the zero crossings of a low-frequency noise field.
+        let river_noise = fbm(self.seed, world_x * 0.012, world_z * 0.012);
+        let ridge = (river_noise * 2.0 - 1.0).abs();
+        let river = 1.0 - smoothstep(self.river_width * 0.5, self.river_width, ridge);
+
+        let lake_noise = fbm(
+            self.seed.wrapping_add(0x51ed),
+            world_x * 0.02,
+            world_z * 0.02,
+        );
+        let lake = smoothstep(self.lake_threshold - 0.06, self.lake_threshold, lake_noise);
+        river.max(lake)
+    }
+
+    /// Ground height at `(x, z)`; voxels at or below its floor are solid.
+    pub fn surface_height(&self, world_x: f32, world_z: f32) -> f32 {
+        let height = base_height(world_x, world_z);
+        let bed = self.sea_level as f32 - 2.5;
+        let carve = self.water_carve(world_x, world_z);
+        if height <= bed {
+            return height;
+        }
+        (height + (bed - height) * carve).max(0.0)
+    }
+
+    /// Height of whatever is on top at `(x, z)`: the ground, or the water
+    /// surface where the ground lies below sea level.
+    pub fn surface_or_water(&self, world_x: f32, world_z: f32) -> f32 {
+        self.surface_height(world_x, world_z)
+            .max(self.sea_level as f32 + 1.0)
+    }
+
+    /// First open cell above the ground or water in column `(x, z)`.
+    pub fn surface_cell(&self, world_x: i32, world_z: i32) -> IVec3 {
+        let top = self.surface_height(world_x as f32, world_z as f32).floor() as i32;
+        IVec3::new(world_x, top.max(self.sea_level) + 1, world_z)
+    }
+
+    /// Voxel at world position `pos` in the generated heightfield, before
+    /// ores, dungeons and decorations are added.
+    pub fn voxel_at(&self, pos: IVec3) -> Voxel {
+        self.column_voxel(
+            self.surface_height(pos.x as f32, pos.z as f32).floor() as i32,
+            pos.y,
+        )
+    }
+
+    fn column_voxel(&self, top: i32, y: i32) -> Voxel {
+        if y <= top {
+            let shore = top <= self.sea_level + self.beach_height;
+            if shore && y > top - self.sand_depth {
+                Voxel::SAND
+            } else {
+                Voxel::GROUND
+            }
+        } else if y <= self.sea_level {
+            Voxel::WATER
+        } else {
+            Voxel::AIR
+        }
+    }
+
+    /// Fills the chunk at chunk coordinate `coord` with terrain and water.
+    /// Ores and dungeons are added by [`Self::populate_underground`], trees
+    /// and plants by [`Self::decorate_chunk`] and villages by
+    /// [`Self::build_villages`].
+    pub fn populate_chunk(&self, coord: IVec3, chunk: &mut Chunk) {
+        let size = CHUNK_SIZE as i32;
+        let base = coord * size;
+        for z in 0..size {
+            for x in 0..size {
+                let top = self
+                    .surface_height((base.x + x) as f32, (base.z + z) as f32)
+                    .floor() as i32;
+                for y in 0..size {
+                    let voxel = self.column_voxel(top, base.y + y);
+                    if !voxel.is_empty() {
+                        chunk.set(x as u32, y as u32, z as u32, voxel);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Hash of an integer lattice point.
+fn hash(seed: u32, x: i32, z: i32) -> u32 {
+    let mut h = seed
+        .wrapping_mul(0x9e37_79b9)
+        .wrapping_add((x as u32).wrapping_mul(0x85eb_ca6b))
+        .wrapping_add((z as u32).wrapping_mul(0xc2b2_ae35));
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297a_2d39);
+    h ^ (h >> 15)
+}
+
+/// Hash of an integer lattice point to `0..1`.
+fn lattice(seed: u32, x: i32, z: i32) -> f32 {
+    (hash(seed, x, z) & 0x00ff_ffff) as f32 / 0x0100_0000 as f32
+}
+
+/// Smoothly interpolated value noise in `0..1`.
+pub fn value_noise(seed: u32, x: f32, z: f32) -> f32 {
+    let (x0, z0) = (x.floor(), z.floor());
+    let (ix, iz) = (x0 as i32, z0 as i32);
+    let fade = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, tz) = (fade(x - x0), fade(z - z0));
+    let a = lattice(seed, ix, iz);
+    let b = lattice(seed, ix + 1, iz);
+    let c = lattice(seed, ix, iz + 1);
+    let d = lattice(seed, ix + 1, iz + 1);
+    let top = a + (b - a) * tx;
+    let bottom = c + (d - c) * tx;
+    top + (bottom - top) * tz
+}
+
+/// Two octaves of value noise, normalised to `0..1`.
+fn fbm(seed: u32, x: f32, z: f32) -> f32 {
+    let low = value_noise(seed, x, z);
+    let high = value_noise(seed.wrapping_add(1), x * 2.0, z * 2.0);
+    (low * 2.0 + high) / 3.0
+}
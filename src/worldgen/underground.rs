@@ -0,0 +1,243 @@
+use bevy::math::{IVec2, IVec3};
+
+use super::{hash, lattice, TerrainGenerator};
+use crate::voxel::Voxel;
+use crate::world::{Chunk, CHUNK_SIZE};
+
+/// The ore table the generator uses, in the format read by
+/// [`OreVein::parse`].
+const ORES: &str = include_str!("../../assets/ores.txt");
+
+/// Side of the square regions that each hold at most one dungeon.
+pub const DUNGEON_REGION: i32 = 48;
+
+const STEPS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// How one ore is scattered through the ground.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OreVein {
+    pub voxel: Voxel,
+    /// World heights veins start in, inclusive.
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Average number of veins per chunk whose height range overlaps the
+    /// chunk.
+    pub frequency: f32,
+    /// Steps of the random walk that lays out one vein. Capped at the chunk
+    /// size.
+    pub size: u32,
+}
+
+impl OreVein {
+    /// The ores listed in `assets/ores.txt`.
+    pub fn table() -> Vec<OreVein> {
+        Self::parse(ORES).expect("built-in ore table")
+    }
+
+    /// Reads an ore table: one `ore <block> <min y> <max y> <frequency>
+    /// <size>` line per ore, with `//` comments.
+    pub fn parse(source: &str) -> Result<Vec<OreVein>, String> {
+        let mut ores = Vec::new();
+        for (index, raw) in source.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let ["ore", name, min_y, max_y, frequency, size] = words[..] else {
+                return Err(format!(
+                    "line {line_no}: expected ore block min_y max_y frequency size"
+                ));
+            };
+            let voxel = Voxel::from_name(name)
+                .ok_or_else(|| format!("line {line_no}: unknown block {name:?}"))?;
+            let invalid = |what: &str| format!("line {line_no}: invalid {what}");
+            let ore = OreVein {
+                voxel,
+                min_y: min_y.parse().map_err(|_| invalid("min_y"))?,
+                max_y: max_y.parse().map_err(|_| invalid("max_y"))?,
+                frequency: frequency.parse().map_err(|_| invalid("frequency"))?,
+                size: size.parse().map_err(|_| invalid("size"))?,
+            };
+            if ore.min_y > ore.max_y {
+                return Err(format!("line {line_no}: min_y is above max_y"));
+            }
+            ores.push(ore);
+        }
+        Ok(ores)
+    }
+}
+
+/// A buried brick room.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dungeon {
+    /// Minimum corner of the outer walls.
+    pub min: IVec3,
+    /// Outer size, walls included.
+    pub size: IVec3,
+}
+
+impl Dungeon {
+    pub fn max(&self) -> IVec3 {
+        self.min + self.size - IVec3::ONE
+    }
+
+    /// What the room puts at `pos`, if `pos` lies inside its walls.
+    pub fn voxel_at(&self, pos: IVec3) -> Option<Voxel> {
+        let max = self.max();
+        if pos.cmplt(self.min).any() || pos.cmpgt(max).any() {
+            return None;
+        }
+        let on_wall = pos.cmpeq(self.min).any() || pos.cmpeq(max).any();
+        let lamp = IVec3::new(
+            (self.min.x + max.x) / 2,
+            max.y - 1,
+            (self.min.z + max.z) / 2,
+        );
+        Some(if on_wall {
+            Voxel::BRICK
+        } else if pos == lamp {
+            Voxel::LAMP
+        } else {
+            Voxel::AIR
+        })
+    }
+}
+
+impl TerrainGenerator {
+    /// The dungeon in region `region`, if one was rolled there and fits
+    /// entirely underground.
+    pub fn dungeon_in_region(&self, region: IVec2) -> Option<Dungeon> {
+        let seed = self.seed.wrapping_mul(0x6d2b).wrapping_add(0xd00d);
+        if lattice(seed, region.x, region.y) >= self.dungeon_chance {
+            return None;
+        }
+        let roll = |salt: u32, range: i32| {
+            (hash(seed.wrapping_add(salt), region.x, region.y) % range.max(1) as u32) as i32
+        };
+        let size = IVec3::new(7 + roll(1, 5), 5 + roll(2, 2), 7 + roll(3, 5));
+        let min_xz = region * DUNGEON_REGION
+            + IVec2::new(
+                roll(4, DUNGEON_REGION - size.x),
+                roll(5, DUNGEON_REGION - size.z),
+            );
+
+        // Keep at least three voxels of ground over the ceiling everywhere.
+        let mut lowest_top = i32::MAX;
+        for z in min_xz.y..min_xz.y + size.z {
+            for x in min_xz.x..min_xz.x + size.x {
+                let top = self.surface_height(x as f32, z as f32).floor() as i32;
+                lowest_top = lowest_top.min(top);
+            }
+        }
+        let highest_floor = lowest_top - 3 - size.y;
+        if highest_floor < 1 {
+            return None;
+        }
+        let floor = 1 + roll(6, highest_floor);
+        Some(Dungeon {
+            min: IVec3::new(min_xz.x, floor, min_xz.y),
+            size,
+        })
+    }
+
+    /// Cells of every vein of `ore` that starts in chunk `source`, in order.
+    fn vein_cells(&self, ore_index: usize, ore: &OreVein, source: IVec3) -> Vec<IVec3> {
+        let size = CHUNK_SIZE as i32;
+        let low = ore.min_y.max(source.y * size);
+        let high = ore.max_y.min(source.y * size + size - 1);
+        if low > high {
+            return Vec::new();
+        }
+        let seed = hash(
+            hash(
+                self.seed.wrapping_add(ore_index as u32 * 0x1f3),
+                source.x,
+                source.y,
+            ),
+            source.z,
+            0x0e1e,
+        );
+        let count =
+            ore.frequency.floor() as u32 + u32::from(lattice(seed, 0, -1) < ore.frequency.fract());
+        let steps = ore.size.min(CHUNK_SIZE as u32) as i32;
+
+        let mut cells = Vec::new();
+        for vein in 0..count as i32 {
+            let mut cell = IVec3::new(
+                source.x * size + (hash(seed, vein, 1) % size as u32) as i32,
+                low + (hash(seed, vein, 2) % (high - low + 1) as u32) as i32,
+                source.z * size + (hash(seed, vein, 3) % size as u32) as i32,
+            );
+            for step in 0..steps {
+                cells.push(cell);
+                cell += STEPS[(hash(seed, vein, 4 + step) % 6) as usize];
+            }
+        }
+        cells
+    }
+
+    /// Scatters ore veins and dungeons into the chunk at `coord`, after
+    /// [`Self::populate_chunk`]. Veins only replace ground. Veins and rooms
+    /// started in neighbouring chunks or regions are written too, always in
+    /// the same order, so features crossing chunk borders line up however
+    /// the chunks are generated.
+    pub fn populate_underground(&self, coord: IVec3, chunk: &mut Chunk) {
+        let size = CHUNK_SIZE as i32;
+        let base = coord * size;
+        let local = |pos: IVec3| {
+            let local = pos - base;
+            (local.cmpge(IVec3::ZERO).all() && local.cmplt(IVec3::splat(size)).all())
+                .then(|| local.as_uvec3())
+        };
+
+        for (ore_index, ore) in self.ores.iter().enumerate() {
+            for dz in -1..=1 {
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let source = coord + IVec3::new(dx, dy, dz);
+                        for cell in self.vein_cells(ore_index, ore, source) {
+                            let Some(l) = local(cell) else {
+                                continue;
+                            };
+                            if chunk.get(l.x, l.y, l.z) == Voxel::GROUND {
+                                chunk.set(l.x, l.y, l.z, ore.voxel);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let first = IVec2::new(base.x, base.z).div_euclid(IVec2::splat(DUNGEON_REGION));
+        let last = IVec2::new(base.x + size - 1, base.z + size - 1)
+            .div_euclid(IVec2::splat(DUNGEON_REGION));
+        for rz in first.y..=last.y {
+            for rx in first.x..=last.x {
+                let Some(dungeon) = self.dungeon_in_region(IVec2::new(rx, rz)) else {
+                    continue;
+                };
+                let min = dungeon.min.max(base);
+                let max = dungeon.max().min(base + IVec3::splat(size - 1));
+                for y in min.y..=max.y {
+                    for z in min.z..=max.z {
+                        for x in min.x..=max.x {
+                            let pos = IVec3::new(x, y, z);
+                            if let (Some(voxel), Some(l)) = (dungeon.voxel_at(pos), local(pos)) {
+                                chunk.set(l.x, l.y, l.z, voxel);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::math::{IVec3, Vec3};
+use platz::character::{step_walker, WalkInput, WalkerSettings, WalkerState};
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+const STONE: Voxel = Voxel(1);
+const DT: f32 = 1.0 / 60.0;
+
+fn ground(world: &mut VoxelWorld, top: i32, xs: std::ops::Range<i32>) {
+    for x in xs {
+        for z in -4..4 {
+            for y in 0..=top {
+                world.set(IVec3::new(x, y, z), STONE);
+            }
+        }
+    }
+}
+
+fn simulate(world: &VoxelWorld, state: &mut WalkerState, input: WalkInput, frames: usize) {
+    let settings = WalkerSettings::default();
+    for _ in 0..frames {
+        step_walker(world, state, input, &settings, DT);
+    }
+}
+
+#[test]
+fn walker_falls_and_lands_on_ground() {
+    let mut world = VoxelWorld::new();
+    ground(&mut world, 0, -8..8);
+    let mut state = WalkerState::new(Vec3::new(0.5, 6.0, 0.5));
+
+    simulate(&world, &mut state, WalkInput::default(), 120);
+    assert!(state.grounded);
+    assert!((state.position.y - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn jump_leaves_ground_and_returns() {
+    let mut world = VoxelWorld::new();
+    ground(&mut world, 0, -8..8);
+    let mut state = WalkerState::new(Vec3::new(0.5, 1.0, 0.5));
+    simulate(&world, &mut state, WalkInput::default(), 5);
+    assert!(state.grounded);
+
+    let jump = WalkInput {
+        jump: true,
+        ..WalkInput::default()
+    };
+    simulate(&world, &mut state, jump, 1);
+    assert!(!state.grounded);
+    simulate(&world, &mut state, WalkInput::default(), 10);
+    assert!(state.position.y > 1.5);
+    simulate(&world, &mut state, WalkInput::default(), 120);
+    assert!(state.grounded);
+    assert!((state.position.y - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn walker_steps_onto_single_voxel_ledge() {
+    let mut world = VoxelWorld::new();
+    ground(&mut world, 0, -8..3);
+    ground(&mut world, 1, 3..12);
+    let mut state = WalkerState::new(Vec3::new(0.5, 1.0, 0.5));
+    simulate(&world, &mut state, WalkInput::default(), 5);
+
+    let forward = WalkInput {
+        movement: Vec3::X,
+        ..WalkInput::default()
+    };
+    simulate(&world, &mut state, forward, 90);
+    assert!(state.position.x > 4.0);
+    assert!((state.position.y - 2.0).abs() < 0.01);
+}
+
+#[test]
+fn walker_is_stopped_by_two_voxel_wall() {
+    let mut world = VoxelWorld::new();
+    ground(&mut world, 0, -8..3);
+    ground(&mut world, 2, 3..12);
+    let mut state = WalkerState::new(Vec3::new(0.5, 1.0, 0.5));
+    simulate(&world, &mut state, WalkInput::default(), 5);
+
+    let forward = WalkInput {
+        movement: Vec3::X,
+        ..WalkInput::default()
+    };
+    simulate(&world, &mut state, forward, 90);
+    assert!(state.position.x < 3.0);
+    assert!((state.position.y - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn crouched_walker_stays_crouched_under_low_ceiling() {
+    let mut world = VoxelWorld::new();
+    ground(&mut world, 0, -8..8);
+    // Two voxels of headroom fit this tall walker only while crouched.
+    for x in -8..8 {
+        for z in -4..4 {
+            world.set(IVec3::new(x, 3, z), STONE);
+        }
+    }
+    let settings = WalkerSettings {
+        height: 2.4,
+        crouch_height: 1.6,
+        ..WalkerSettings::default()
+    };
+    let mut state = WalkerState::new(Vec3::new(0.5, 1.0, 0.5));
+    state.crouching = true;
+    let crouch = WalkInput {
+        crouch: true,
+        ..WalkInput::default()
+    };
+    for _ in 0..5 {
+        step_walker(&world, &mut state, crouch, &settings, DT);
+    }
+    assert!(state.crouching);
+    assert!(state.eye_height(&settings) < settings.height - settings.eye_offset);
+
+    // Releasing crouch does not stand up into the ceiling.
+    for _ in 0..5 {
+        step_walker(&world, &mut state, WalkInput::default(), &settings, DT);
+    }
+    assert!(state.crouching);
+    assert!(state.grounded);
+}
@@ -0,0 +1,131 @@
+use bevy::math::{IVec3, Vec3};
+use platz::collision::{raycast, sweep_aabb, Aabb};
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+const STONE: Voxel = Voxel(1);
+const EPS: f32 = 1e-3;
+
+fn flat_ground(size: i32) -> VoxelWorld {
+    let mut world = VoxelWorld::new();
+    for x in -size..size {
+        for z in -size..size {
+            world.set(IVec3::new(x, 0, z), STONE);
+        }
+    }
+    world
+}
+
+fn unit_box(center: Vec3) -> Aabb {
+    Aabb::from_center(center, Vec3::new(0.4, 0.5, 0.4))
+}
+
+#[test]
+fn free_motion_is_unchanged() {
+    let world = flat_ground(8);
+    let result = sweep_aabb(
+        &world,
+        unit_box(Vec3::new(0.5, 3.0, 0.5)),
+        Vec3::new(1.0, 0.5, -2.0),
+    );
+    assert_eq!(result.motion, Vec3::new(1.0, 0.5, -2.0));
+    assert!(!result.blocked.any());
+}
+
+#[test]
+fn falling_box_lands_on_ground() {
+    let world = flat_ground(8);
+    let start = unit_box(Vec3::new(0.5, 3.0, 0.5));
+    let result = sweep_aabb(&world, start, Vec3::new(0.0, -10.0, 0.0));
+    assert!(result.blocked.y);
+    let landed = start.translated(result.motion);
+    assert!((landed.min.y - 1.0).abs() < EPS);
+    assert!(!landed.intersects_solid(&world));
+}
+
+#[test]
+fn diagonal_motion_slides_along_wall() {
+    let mut world = flat_ground(8);
+    for z in -8..8 {
+        for y in 1..4 {
+            world.set(IVec3::new(3, y, z), STONE);
+        }
+    }
+    let start = unit_box(Vec3::new(1.5, 1.6, 0.5));
+    let result = sweep_aabb(&world, start, Vec3::new(4.0, 0.0, 2.0));
+
+    assert!(result.blocked.x);
+    assert!(!result.blocked.z);
+    assert_eq!(result.motion.z, 2.0);
+    let moved = start.translated(result.motion);
+    assert!((moved.max.x - 3.0).abs() < EPS);
+    assert!(!moved.intersects_solid(&world));
+}
+
+#[test]
+fn fast_motion_does_not_tunnel_through_thin_wall() {
+    let mut world = VoxelWorld::new();
+    world.set(IVec3::new(0, 0, 10), STONE);
+    let start = unit_box(Vec3::new(0.5, 0.5, 0.5));
+    let result = sweep_aabb(&world, start, Vec3::new(0.0, 0.0, 100.0));
+    assert!(result.blocked.z);
+    assert!((start.translated(result.motion).max.z - 10.0).abs() < EPS);
+}
+
+#[test]
+fn sweeps_across_chunk_boundary_and_negative_coordinates() {
+    let mut world = VoxelWorld::new();
+    for y in -40..40 {
+        world.set(IVec3::new(-33, y, 0), STONE);
+    }
+    let start = unit_box(Vec3::new(-30.5, -0.5, 0.5));
+    let result = sweep_aabb(&world, start, Vec3::new(-10.0, 0.0, 0.0));
+    assert!(result.blocked.x);
+    assert!((start.translated(result.motion).min.x - -32.0).abs() < EPS);
+}
+
+#[test]
+fn resting_contact_stays_blocked() {
+    let world = flat_ground(4);
+    let start = unit_box(Vec3::new(0.5, 3.0, 0.5));
+    let first = sweep_aabb(&world, start, Vec3::new(0.0, -5.0, 0.0));
+    let resting = start.translated(first.motion);
+    let second = sweep_aabb(&world, resting, Vec3::new(0.5, -0.1, 0.0));
+    assert!(second.blocked.y);
+    assert!(second.motion.y.abs() < EPS);
+    assert_eq!(second.motion.x, 0.5);
+}
+
+#[test]
+fn embedded_box_can_move_out() {
+    let world = flat_ground(4);
+    let embedded = unit_box(Vec3::new(0.5, 0.2, 0.5));
+    assert!(embedded.intersects_solid(&world));
+    let result = sweep_aabb(&world, embedded, Vec3::new(0.0, 2.0, 0.0));
+    assert_eq!(result.motion.y, 2.0);
+}
+
+#[test]
+fn rays_stop_at_the_first_block_they_enter() {
+    let mut world = flat_ground(8);
+    world.set(IVec3::new(3, 1, 0), Voxel::WATER);
+    world.set(IVec3::new(5, 1, 0), STONE);
+    let hit = raycast(&world, Vec3::new(0.5, 1.5, 0.5), Vec3::X, 10.0).unwrap();
+    // Water does not stop the ray.
+    assert_eq!(hit.cell, IVec3::new(5, 1, 0));
+    assert_eq!(hit.normal, IVec3::NEG_X);
+    assert!((hit.distance - 4.5).abs() < EPS);
+
+    let down = raycast(
+        &world,
+        Vec3::new(-2.3, 6.0, 1.7),
+        Vec3::new(0.2, -1.0, 0.1),
+        20.0,
+    )
+    .unwrap();
+    assert_eq!(down.cell.y, 0);
+    assert_eq!(down.normal, IVec3::Y);
+
+    assert!(raycast(&world, Vec3::new(0.5, 1.5, 0.5), Vec3::X, 3.0).is_none());
+    assert!(raycast(&world, Vec3::new(0.5, 1.5, 0.5), Vec3::Y, 50.0).is_none());
+}
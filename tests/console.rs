@@ -0,0 +1,155 @@
+use bevy::prelude::World;
+use platz::console::{
+    exec, execute, run_console_commands, script_lines, CommandRegistry, Console, ConsoleCommand,
+    ConsoleScript, MAX_EXEC_DEPTH,
+};
+
+fn echo(_: &mut World, args: &[&str]) -> Result<String, String> {
+    Ok(args.join(" "))
+}
+
+fn fail(_: &mut World, _: &[&str]) -> Result<String, String> {
+    Err("nope".into())
+}
+
+fn registry() -> CommandRegistry {
+    let mut registry = CommandRegistry::default();
+    for (name, run) in [
+        ("echo", echo as fn(&mut World, &[&str]) -> _),
+        ("exit", fail),
+        ("time", echo),
+    ] {
+        registry.register(ConsoleCommand {
+            name,
+            usage: "",
+            help: "",
+            run,
+        });
+    }
+    registry
+}
+
+#[test]
+fn commands_run_with_their_arguments() {
+    let mut world = World::new();
+    world.insert_resource(registry());
+    assert_eq!(execute(&mut world, "echo  a b\tc"), Ok("a b c".into()));
+    assert_eq!(execute(&mut world, "exit"), Err("nope".into()));
+    assert!(execute(&mut world, "teleport 1 2 3").is_err());
+    assert_eq!(execute(&mut world, "   "), Ok(String::new()));
+}
+
+#[test]
+fn tab_completes_command_names() {
+    let registry = registry();
+    assert_eq!(registry.completions("e"), ["echo", "exit"]);
+    assert_eq!(registry.complete("e"), "e");
+    assert_eq!(registry.complete("ec"), "echo ");
+    assert_eq!(registry.complete("t"), "time ");
+    assert_eq!(registry.complete("zz"), "zz");
+    // Arguments are left alone.
+    assert!(registry.completions("echo e").is_empty());
+    // Registering a name again replaces the command.
+    let mut registry = registry;
+    registry.register(ConsoleCommand {
+        name: "echo",
+        usage: "text",
+        help: "",
+        run: fail,
+    });
+    assert_eq!(registry.commands().count(), 3);
+    assert_eq!(registry.get("echo").unwrap().usage, "text");
+}
+
+#[test]
+fn history_walks_back_and_forward() {
+    let mut console = Console::default();
+    console.submit("tp 1 2 3");
+    console.submit("seed");
+    console.submit("seed");
+    console.submit("  ");
+    assert_eq!(console.history(), ["tp 1 2 3", "seed"]);
+
+    console.history_back();
+    assert_eq!(console.input, "seed");
+    console.history_back();
+    console.history_back();
+    assert_eq!(console.input, "tp 1 2 3");
+    console.history_forward();
+    assert_eq!(console.input, "seed");
+    console.history_forward();
+    assert_eq!(console.input, "");
+}
+
+#[test]
+fn log_keeps_the_newest_lines() {
+    let mut console = Console::default();
+    for i in 0..250 {
+        console.print(&format!("line {i}"));
+    }
+    console.print("a\nb");
+    let log: Vec<&str> = console.log().collect();
+    assert_eq!(log.len(), 200);
+    assert_eq!(log[log.len() - 2..], ["a", "b"]);
+}
+
+#[test]
+fn scripts_skip_comments_and_blank_lines() {
+    let source = "# warm start\ntime set 6:30\n\n   give glass  \n#tp 0 0 0\n";
+    assert_eq!(script_lines(source), ["time set 6:30", "give glass"]);
+    let parse = |args: &[&str]| ConsoleScript::from_args(args.iter().map(|arg| arg.to_string()));
+    assert_eq!(parse(&[]), Ok(ConsoleScript(None)));
+    assert_eq!(
+        parse(&["--seed", "4", "--script", "start.cfg"]),
+        Ok(ConsoleScript(Some("start.cfg".into())))
+    );
+    assert!(parse(&["--script"]).is_err());
+}
+
+#[test]
+fn scripts_that_exec_themselves_stop_nesting() {
+    let path = std::env::temp_dir().join(format!("platz-loop-{}.cfg", std::process::id()));
+    std::fs::write(&path, format!("echo again\nexec {}\n", path.display())).unwrap();
+    let mut registry = registry();
+    registry.register(ConsoleCommand {
+        name: "exec",
+        usage: "file",
+        help: "",
+        run: exec,
+    });
+    let mut world = World::new();
+    world.insert_resource(registry);
+    world.insert_resource(Console::default());
+    world
+        .resource_mut::<Console>()
+        .queue(&format!("exec {}", path.display()));
+
+    // Each frame runs only what was queued before it: one level per frame.
+    run_console_commands(&mut world);
+    let log: Vec<String> = world
+        .resource::<Console>()
+        .log()
+        .map(String::from)
+        .collect();
+    assert!(log[1].starts_with("queued 2 commands"), "{log:?}");
+    assert!(!log.iter().any(|line| line == "again"));
+    run_console_commands(&mut world);
+    let again = |world: &World| {
+        let console = world.resource::<Console>();
+        console.log().filter(|&line| line == "again").count()
+    };
+    assert_eq!(again(&world), 1);
+
+    for _ in 0..2 * MAX_EXEC_DEPTH {
+        run_console_commands(&mut world);
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(again(&world), MAX_EXEC_DEPTH);
+    let console = world.resource::<Console>();
+    let errors: Vec<&str> = console
+        .log()
+        .filter(|line| line.starts_with("error:"))
+        .collect();
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    assert!(errors[0].contains("nested"), "{}", errors[0]);
+}
@@ -0,0 +1,83 @@
+use platz::daynight::{parse_clock, TimeOfDay};
+
+fn at(hours: f32) -> TimeOfDay {
+    TimeOfDay {
+        hours,
+        ..TimeOfDay::default()
+    }
+}
+
+#[test]
+fn clock_strings_parse_to_fractional_hours() {
+    assert_eq!(parse_clock("6"), Some(6.0));
+    assert_eq!(parse_clock("18:30"), Some(18.5));
+    assert_eq!(parse_clock("7.25"), Some(7.25));
+    assert_eq!(parse_clock("24"), None);
+    assert_eq!(parse_clock("12:60"), None);
+    assert_eq!(parse_clock("noon"), None);
+}
+
+#[test]
+fn command_line_flags_configure_the_clock() {
+    let args = [
+        "--time",
+        "21:15",
+        "--day-length",
+        "120",
+        "--latitude",
+        "-30",
+        "--freeze-time",
+    ]
+    .map(String::from);
+    let time = TimeOfDay::from_args(args).unwrap();
+    assert_eq!(time.hours, 21.25);
+    assert_eq!(time.day_length, 120.0);
+    assert_eq!(time.latitude, -30.0);
+    assert!(time.paused);
+
+    assert!(TimeOfDay::from_args(["--time".to_string()]).is_err());
+    assert!(TimeOfDay::from_args(["--day-length", "0"].map(String::from)).is_err());
+}
+
+#[test]
+fn clock_advances_with_day_length_and_wraps() {
+    let mut time = TimeOfDay {
+        hours: 23.0,
+        day_length: 240.0,
+        ..TimeOfDay::default()
+    };
+    // 240 s per day is 10 s per hour.
+    time.advance(20.0);
+    assert!((time.hours - 1.0).abs() < 1e-4);
+
+    time.paused = true;
+    time.advance(100.0);
+    assert!((time.hours - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn sun_rises_in_the_east_peaks_at_noon_and_sets_in_the_west() {
+    let morning = at(7.0).sun_direction();
+    let noon = at(12.0).sun_direction();
+    let evening = at(17.0).sun_direction();
+    assert!(morning.x > 0.0);
+    assert!(evening.x < 0.0);
+    assert!(noon.y > morning.y && noon.y > evening.y);
+    // From the northern hemisphere the noon sun stands to the south (+Z).
+    assert!(noon.z > 0.0);
+    assert!(at(0.0).sun_elevation() < 0.0);
+}
+
+#[test]
+fn light_ramps_down_to_night() {
+    let noon = at(12.0);
+    let midnight = at(0.0);
+    assert_eq!(noon.daylight(), 1.0);
+    assert_eq!(midnight.daylight(), 0.0);
+    assert_eq!(midnight.sun_illuminance(), 0.0);
+    assert!(midnight.moon_illuminance() > 0.0);
+    assert!(noon.ambient_brightness() > midnight.ambient_brightness());
+
+    let dusk = at(19.0);
+    assert!(dusk.sun_illuminance() < noon.sun_illuminance());
+}
@@ -0,0 +1,47 @@
+use bevy::math::{IVec3, Vec3};
+use platz::debug::{compass, DebugInfo};
+use platz::visibility::CullingStats;
+use platz::voxel::Voxel;
+
+#[test]
+fn compass_names_the_closest_horizontal_direction() {
+    assert_eq!(compass(Vec3::new(0.9, -0.4, 0.2)), "east (+x)");
+    assert_eq!(compass(Vec3::new(-0.1, 0.0, 1.0)), "south (+z)");
+    assert_eq!(compass(Vec3::new(-1.0, 0.5, 0.3)), "west (-x)");
+    assert_eq!(compass(Vec3::NEG_Z), "north (-z)");
+}
+
+#[test]
+fn overlay_lists_every_stat() {
+    let info = DebugInfo {
+        fps: Some(59.6),
+        frame_time: Some(16.78),
+        camera: "Walker".into(),
+        position: Vec3::new(40.25, 70.0, -3.5),
+        forward: Vec3::NEG_Z,
+        target: Some((IVec3::new(40, 69, -4), Voxel::brick_stairs(IVec3::X))),
+        loaded_chunks: 300,
+        meshed_chunks: 120,
+        culling: CullingStats {
+            buried: 180,
+            outside_view: 50,
+            occluded: 20,
+            drawn: 50,
+        },
+        vertices: 4000,
+        triangles: 2000,
+    };
+    let lines = info.lines();
+    assert_eq!(lines[0], "60 fps (16.8 ms)");
+    assert_eq!(lines[1], "Camera: Walker");
+    assert_eq!(lines[2], "XYZ: 40.2 / 70.0 / -3.5  Chunk: 1 2 -1");
+    assert_eq!(lines[3], "Facing: north (-z) (0° pitch)");
+    assert_eq!(lines[4], "Target: brick_stairs[facing=east] at 40 69 -4");
+    assert!(lines[5].contains("300 loaded, 120 meshed, 50 drawn"));
+    assert!(lines[6].contains("20 occluded, 50 outside view, 180 buried"));
+    assert_eq!(lines[7], "Mesh: 4000 vertices, 2000 triangles");
+
+    let empty = DebugInfo::default().lines();
+    assert_eq!(empty[0], "-- fps");
+    assert_eq!(empty[4], "Target: none");
+}
@@ -0,0 +1,99 @@
+use bevy::math::{IVec3, Vec3};
+use platz::explosion::{blast_cells, spawn_debris, step_debris, Explosion, DEBRIS_LIFETIME};
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+fn solid_block(voxel: Voxel) -> VoxelWorld {
+    let mut world = VoxelWorld::new();
+    for x in -8..8 {
+        for y in -8..8 {
+            for z in -8..8 {
+                world.set(IVec3::new(x, y, z), voxel);
+            }
+        }
+    }
+    world
+}
+
+fn blast(power: f32) -> Explosion {
+    Explosion {
+        center: Vec3::ZERO,
+        radius: 5.0,
+        power,
+    }
+}
+
+#[test]
+fn crater_stays_within_radius() {
+    let world = solid_block(Voxel::GROUND);
+    let destroyed = blast_cells(&world, &blast(4.0));
+    assert!(!destroyed.is_empty());
+    for (cell, voxel) in &destroyed {
+        assert_eq!(*voxel, Voxel::GROUND);
+        assert!((cell.as_vec3() + Vec3::splat(0.5)).length() <= 5.0);
+    }
+    // The cells right at the centre always go.
+    assert!(destroyed.iter().any(|(cell, _)| *cell == IVec3::ZERO));
+}
+
+#[test]
+fn resistance_limits_the_crater() {
+    let ground = blast_cells(&solid_block(Voxel::GROUND), &blast(4.0)).len();
+    let sand = blast_cells(&solid_block(Voxel::SAND), &blast(4.0)).len();
+    let snow = blast_cells(&solid_block(Voxel::SNOW), &blast(4.0)).len();
+    assert!(ground < sand && sand < snow, "{ground} {sand} {snow}");
+    // Water shrugs off blasts entirely.
+    assert!(blast_cells(&solid_block(Voxel::WATER), &blast(4.0)).is_empty());
+    // Too weak to break anything.
+    assert!(blast_cells(&solid_block(Voxel::GROUND), &blast(0.5)).is_empty());
+}
+
+#[test]
+fn same_blast_carves_same_crater() {
+    let world = solid_block(Voxel::GROUND);
+    assert_eq!(
+        blast_cells(&world, &blast(3.0)),
+        blast_cells(&world, &blast(3.0))
+    );
+}
+
+#[test]
+fn debris_is_capped_and_flies_outward() {
+    let world = solid_block(Voxel::GROUND);
+    let explosion = blast(4.0);
+    let destroyed = blast_cells(&world, &explosion);
+    let debris = spawn_debris(&explosion, &destroyed, 10);
+    assert!(!debris.is_empty() && debris.len() <= 10);
+    for piece in &debris {
+        let outward = piece.position - explosion.center;
+        assert!(piece.velocity.y > 0.0 || piece.velocity.dot(outward) > 0.0);
+    }
+    assert!(spawn_debris(&explosion, &[], 10).is_empty());
+}
+
+#[test]
+fn debris_lands_on_the_ground_and_expires() {
+    let mut world = VoxelWorld::new();
+    for x in -20..20 {
+        for z in -20..20 {
+            world.set(IVec3::new(x, 0, z), Voxel::GROUND);
+        }
+    }
+    let explosion = Explosion {
+        center: Vec3::new(0.5, 1.0, 0.5),
+        radius: 3.0,
+        power: 4.0,
+    };
+    let mut pieces = spawn_debris(&explosion, &[(IVec3::new(1, 1, 0), Voxel::GROUND)], 4);
+    let piece = &mut pieces[0];
+
+    let dt = 1.0 / 60.0;
+    let mut steps = 0;
+    while step_debris(&world, piece, dt) {
+        assert!(piece.position.y >= 1.0, "fell through the floor");
+        steps += 1;
+    }
+    assert!((steps as f32 * dt - DEBRIS_LIFETIME).abs() < 0.05);
+    // Comes to rest on top of the floor.
+    assert!((piece.position.y - 1.2).abs() < 0.01);
+}
@@ -0,0 +1,170 @@
+use bevy::math::IVec3;
+use platz::falling::FallingSim;
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+const DT: f32 = 1.0 / 20.0;
+
+fn floor(world: &mut VoxelWorld) {
+    for x in -4..4 {
+        for z in -4..4 {
+            world.set(IVec3::new(x, 0, z), Voxel::GROUND);
+        }
+    }
+}
+
+fn edit(world: &mut VoxelWorld, sim: &mut FallingSim, pos: IVec3, voxel: Voxel) {
+    world.set(pos, voxel);
+    sim.notify(pos);
+}
+
+#[test]
+fn registry_marks_sand_and_gravel_as_falling() {
+    assert!(Voxel::SAND.falls());
+    assert!(Voxel::GRAVEL.falls());
+    assert!(!Voxel::GROUND.falls());
+    assert!(!Voxel::WATER.falls());
+}
+
+#[test]
+fn column_falls_when_its_support_is_removed() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world);
+    let mut sim = FallingSim::new();
+    // Ground pillar with sand, gravel, sand stacked on top.
+    for y in 1..=4 {
+        world.set(IVec3::new(0, y, 0), Voxel::GROUND);
+    }
+    world.set(IVec3::new(0, 5, 0), Voxel::SAND);
+    world.set(IVec3::new(0, 6, 0), Voxel::GRAVEL);
+    world.set(IVec3::new(0, 7, 0), Voxel::SAND);
+
+    for y in 1..=4 {
+        edit(&mut world, &mut sim, IVec3::new(0, y, 0), Voxel::AIR);
+    }
+    let steps = sim.settle(&mut world, DT, 200);
+    assert!(steps < 200);
+    assert_eq!(world.get(IVec3::new(0, 1, 0)), Voxel::SAND);
+    assert_eq!(world.get(IVec3::new(0, 2, 0)), Voxel::GRAVEL);
+    assert_eq!(world.get(IVec3::new(0, 3, 0)), Voxel::SAND);
+    assert_eq!(world.get(IVec3::new(0, 4, 0)), Voxel::AIR);
+    assert!(sim.bodies().is_empty());
+}
+
+#[test]
+fn supported_sand_stays_put() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world);
+    let mut sim = FallingSim::new();
+    edit(&mut world, &mut sim, IVec3::new(1, 1, 1), Voxel::SAND);
+    sim.detach(&world);
+    assert!(sim.bodies().is_empty());
+
+    // Removing a side neighbour does not matter; only the block beneath does.
+    edit(&mut world, &mut sim, IVec3::new(2, 1, 1), Voxel::GROUND);
+    edit(&mut world, &mut sim, IVec3::new(2, 1, 1), Voxel::AIR);
+    assert!(sim.detach(&world).is_empty());
+}
+
+#[test]
+fn placed_sand_drops_through_water_onto_the_bed() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world);
+    let mut sim = FallingSim::new();
+    for y in 1..=3 {
+        world.set(IVec3::new(0, y, 0), Voxel::WATER);
+    }
+    edit(&mut world, &mut sim, IVec3::new(0, 10, 0), Voxel::SAND);
+    sim.settle(&mut world, DT, 200);
+    assert_eq!(world.get(IVec3::new(0, 1, 0)), Voxel::SAND);
+    assert_eq!(world.get(IVec3::new(0, 10, 0)), Voxel::AIR);
+}
+
+#[test]
+fn bodies_land_on_each_other_in_the_same_column() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world);
+    let mut sim = FallingSim::new();
+    // Two separate sand blocks in mid-air; the upper one lands on the lower.
+    edit(&mut world, &mut sim, IVec3::new(0, 5, 0), Voxel::SAND);
+    edit(&mut world, &mut sim, IVec3::new(0, 12, 0), Voxel::GRAVEL);
+    sim.settle(&mut world, DT, 400);
+    assert_eq!(world.get(IVec3::new(0, 1, 0)), Voxel::SAND);
+    assert_eq!(world.get(IVec3::new(0, 2, 0)), Voxel::GRAVEL);
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let run = || {
+        let mut world = VoxelWorld::new();
+        floor(&mut world);
+        let mut sim = FallingSim::new();
+        for (i, x) in (-3..3).enumerate() {
+            let voxel = if i % 2 == 0 {
+                Voxel::SAND
+            } else {
+                Voxel::GRAVEL
+            };
+            edit(
+                &mut world,
+                &mut sim,
+                IVec3::new(x, 4 + i as i32 * 2, 0),
+                voxel,
+            );
+        }
+        let mut trace = Vec::new();
+        for _ in 0..60 {
+            for cell in sim.detach(&world) {
+                world.set(cell, Voxel::AIR);
+                sim.notify(cell);
+            }
+            trace.push(sim.bodies().to_vec());
+            for (cell, voxel) in sim.step(&world, DT) {
+                world.set(cell, voxel);
+                sim.notify(cell);
+            }
+        }
+        trace
+    };
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn landing_under_a_ceiling_keeps_every_voxel() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world);
+    let mut sim = FallingSim::new();
+    world.set(IVec3::new(0, 9, 0), Voxel::GROUND);
+    for y in 10..=13 {
+        world.set(IVec3::new(0, y, 0), Voxel::SAND);
+    }
+    world.set(IVec3::new(0, 14, 0), Voxel::GRAVEL);
+    edit(&mut world, &mut sim, IVec3::new(0, 9, 0), Voxel::AIR);
+    for cell in sim.detach(&world) {
+        world.set(cell, Voxel::AIR);
+    }
+    assert_eq!(sim.bodies().len(), 1);
+
+    // While the column falls, ground appears under it and an overhang
+    // inside it, so part of the column comes to rest under the overhang.
+    world.set(IVec3::new(0, 7, 0), Voxel::GROUND);
+    world.set(IVec3::new(0, 10, 0), Voxel::GROUND);
+    let writes = sim.step(&world, 0.5);
+    assert_eq!(writes.len(), 5);
+    for (cell, voxel) in writes {
+        world.set(cell, voxel);
+    }
+    assert!(sim.bodies().is_empty());
+    let column: Vec<Voxel> = (8..=13).map(|y| world.get(IVec3::new(0, y, 0))).collect();
+    assert_eq!(
+        column,
+        [
+            Voxel::SAND,
+            Voxel::SAND,
+            Voxel::GROUND,
+            Voxel::SAND,
+            Voxel::SAND,
+            Voxel::GRAVEL
+        ]
+    );
+}
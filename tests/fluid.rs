@@ -0,0 +1,124 @@
+use bevy::math::IVec3;
+use platz::fluid::FluidSim;
+use platz::meshing::mesh_voxels;
+use platz::voxel::Voxel;
+use platz::world::{Chunk, VoxelWorld};
+
+fn floor(world: &mut VoxelWorld, y: i32, xs: std::ops::Range<i32>, zs: std::ops::Range<i32>) {
+    for x in xs {
+        for z in zs.clone() {
+            world.set(IVec3::new(x, y, z), Voxel::GROUND);
+        }
+    }
+}
+
+fn place(world: &mut VoxelWorld, sim: &mut FluidSim, pos: IVec3, voxel: Voxel) {
+    world.set(pos, voxel);
+    sim.wake(pos);
+}
+
+#[test]
+fn source_spreads_seven_cells_and_settles() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, 0, -16..16, -16..16);
+    let mut sim = FluidSim::new();
+    place(&mut world, &mut sim, IVec3::new(0, 1, 0), Voxel::WATER);
+
+    let ticks = sim.settle(&mut world, 100);
+    assert!(ticks < 100, "water never settled");
+    for d in 1..=7 {
+        assert_eq!(
+            world.get(IVec3::new(d, 1, 0)).water_level(),
+            Some(d as u8),
+            "level at distance {d}"
+        );
+    }
+    assert_eq!(world.get(IVec3::new(8, 1, 0)), Voxel::AIR);
+    // Levels follow Manhattan distance.
+    assert_eq!(world.get(IVec3::new(3, 1, 4)).water_level(), Some(7));
+    assert_eq!(world.get(IVec3::new(4, 1, 4)), Voxel::AIR);
+}
+
+#[test]
+fn water_falls_off_a_ledge_before_spreading() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, 0, -16..16, -16..16);
+    floor(&mut world, 4, -2..3, -2..3);
+    let mut sim = FluidSim::new();
+    place(&mut world, &mut sim, IVec3::new(2, 5, 0), Voxel::WATER);
+    sim.settle(&mut world, 200);
+
+    // Flows one cell past the ledge edge, then pours down.
+    assert_eq!(world.get(IVec3::new(3, 5, 0)).water_level(), Some(1));
+    for y in 1..=4 {
+        assert!(world.get(IVec3::new(3, y, 0)).is_falling_water(), "y = {y}");
+    }
+    // Lands as a fresh spread from the bottom of the fall.
+    assert_eq!(world.get(IVec3::new(4, 1, 0)).water_level(), Some(1));
+}
+
+#[test]
+fn removing_the_source_drains_the_flow() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, 0, -16..16, -16..16);
+    let mut sim = FluidSim::new();
+    let source = IVec3::new(0, 1, 0);
+    place(&mut world, &mut sim, source, Voxel::WATER);
+    sim.settle(&mut world, 100);
+
+    place(&mut world, &mut sim, source, Voxel::AIR);
+    let ticks = sim.settle(&mut world, 100);
+    assert!(ticks < 100);
+    for x in -8..=8 {
+        for z in -8..=8 {
+            assert!(
+                !world.get(IVec3::new(x, 1, z)).is_water(),
+                "water left at {x}, {z}"
+            );
+        }
+    }
+}
+
+#[test]
+fn two_sources_fill_the_gap_between_them() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, 0, -4..4, -4..4);
+    let mut sim = FluidSim::new();
+    place(&mut world, &mut sim, IVec3::new(-1, 1, 0), Voxel::WATER);
+    place(&mut world, &mut sim, IVec3::new(1, 1, 0), Voxel::WATER);
+    sim.settle(&mut world, 100);
+    assert_eq!(world.get(IVec3::new(0, 1, 0)), Voxel::WATER);
+}
+
+#[test]
+fn simulation_stays_inside_its_bounds() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, 0, 0..4, 0..4);
+    let mut sim = FluidSim::with_bounds(IVec3::ZERO, IVec3::splat(8));
+    // Falls off the edge of the floor and stops at the bottom bound.
+    place(&mut world, &mut sim, IVec3::new(3, 1, 1), Voxel::WATER);
+    sim.settle(&mut world, 100);
+    assert!(world.get(IVec3::new(4, 0, 1)).is_falling_water());
+    assert_eq!(world.get(IVec3::new(4, -1, 1)), Voxel::AIR);
+}
+
+#[test]
+fn partial_water_has_lowered_top_and_no_inner_faces() {
+    let mut chunk = Chunk::new();
+    chunk.set(1, 0, 1, Voxel::GROUND);
+    chunk.set(1, 1, 1, Voxel::water(4, false));
+    chunk.set(2, 1, 1, Voxel::water(4, false));
+    let mesh = mesh_voxels(&chunk);
+
+    let top = 1.0 + 4.0 / 9.0;
+    let water_top_verts = mesh
+        .positions
+        .iter()
+        .filter(|p| (p[1] - top).abs() < 1e-5)
+        .count();
+    assert!(water_top_verts > 0);
+    assert!(!mesh.positions.iter().any(|p| p[1] == 2.0));
+    // The ground keeps its top face under the water. The two water cells hide
+    // the face between them; the one over the ground hides its bottom face.
+    assert_eq!(mesh.indices.len() / 6, 6 + 4 + 5);
+}
@@ -0,0 +1,94 @@
+use bevy::input::keyboard::KeyCode;
+use bevy::input::ButtonInput;
+use platz::input::{key_name, parse_key, Action, InputMap};
+
+#[test]
+fn defaults_have_no_conflicts() {
+    let map = InputMap::default();
+    assert!(map.conflicts().is_empty());
+    assert_eq!(map.keys(Action::Quit), [KeyCode::Backspace]);
+    assert_eq!(map.keys(Action::CycleCamera), [KeyCode::Tab]);
+    for action in Action::all() {
+        assert_eq!(Action::parse(action.name()), Some(action));
+        assert!(!map.keys(action).is_empty(), "{} unbound", action.name());
+    }
+}
+
+#[test]
+fn key_names_round_trip() {
+    assert_eq!(parse_key("W"), Some(KeyCode::KeyW));
+    assert_eq!(parse_key("KeyW"), Some(KeyCode::KeyW));
+    assert_eq!(parse_key("Digit7"), Some(KeyCode::Digit7));
+    assert_eq!(parse_key("escape"), Some(KeyCode::Escape));
+    assert_eq!(parse_key("Hyper"), None);
+    assert_eq!(key_name(KeyCode::F3), Some("F3"));
+    assert_eq!(
+        parse_key(key_name(KeyCode::ArrowUp).unwrap()),
+        Some(KeyCode::ArrowUp)
+    );
+}
+
+#[test]
+fn file_replaces_listed_actions() {
+    let map = InputMap::parse(
+        "# Quit with Escape instead of Backspace\n\
+         quit = Escape\n\
+         \n\
+         move_forward = W, Numpad8  # keypad too\n\
+         toggle_collision =\n",
+    )
+    .unwrap();
+    assert_eq!(map.keys(Action::Quit), [KeyCode::Escape]);
+    assert_eq!(
+        map.keys(Action::MoveForward),
+        [KeyCode::KeyW, KeyCode::Numpad8]
+    );
+    assert!(map.keys(Action::ToggleCollision).is_empty());
+    assert_eq!(map.keys(Action::Screenshot), [KeyCode::KeyP]);
+    assert_eq!(
+        map.describe(Action::MoveForward),
+        "move_forward = W, Numpad8"
+    );
+}
+
+#[test]
+fn conflicts_and_mistakes_are_reported() {
+    let err = InputMap::parse("screenshot = F3").unwrap_err();
+    assert!(err.contains("F3"), "{err}");
+    assert!(
+        err.contains("screenshot") && err.contains("debug_overlay"),
+        "{err}"
+    );
+    assert!(InputMap::parse("screenshot = F3\ndebug_overlay = F4").is_ok());
+
+    let err = InputMap::parse("quit = Escape\nfly = W").unwrap_err();
+    assert!(err.starts_with("line 2"), "{err}");
+    assert!(InputMap::parse("quit = Hyper").is_err());
+    assert!(InputMap::parse("quit Escape").is_err());
+
+    let mut map = InputMap::default();
+    map.bind(Action::Jump, vec![KeyCode::KeyW, KeyCode::KeyW]);
+    assert_eq!(map.keys(Action::Jump), [KeyCode::KeyW]);
+    assert_eq!(
+        map.conflicts(),
+        vec![(KeyCode::KeyW, vec![Action::MoveForward, Action::Jump])]
+    );
+}
+
+#[test]
+fn bindings_flag_reads_the_file() {
+    let path = std::env::temp_dir().join(format!("platz-bindings-{}.cfg", std::process::id()));
+    std::fs::write(&path, "cycle_camera = Tab, C\ntoggle_collision = G\n").unwrap();
+    let args = ["--bindings".to_string(), path.display().to_string()];
+    let map = InputMap::from_args(args).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(map.keys(Action::CycleCamera), [KeyCode::Tab, KeyCode::KeyC]);
+
+    let mut keys = ButtonInput::<KeyCode>::default();
+    keys.press(KeyCode::KeyC);
+    assert!(keys.any_just_pressed(map.keys(Action::CycleCamera).iter().copied()));
+    assert!(!keys.any_just_pressed(map.keys(Action::ToggleCollision).iter().copied()));
+
+    assert!(InputMap::from_args(["--bindings".to_string()]).is_err());
+    assert!(InputMap::from_args(["--bindings".to_string(), "/nonexistent.cfg".into()]).is_err());
+}
@@ -0,0 +1,244 @@
+use bevy::math::IVec3;
+use platz::lighting::{LightField, MAX_LIGHT};
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+/// Two chunks wide, one chunk tall world with flat ground at y = 3.
+fn flat_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new();
+    for x in 0..64 {
+        for z in 0..32 {
+            for y in 0..=3 {
+                world.set(IVec3::new(x, y, z), Voxel::GROUND);
+            }
+        }
+    }
+    world
+}
+
+fn compute(world: &VoxelWorld) -> LightField {
+    LightField::compute(world, IVec3::ZERO, IVec3::new(1, 0, 0))
+}
+
+fn edit(world: &mut VoxelWorld, light: &mut LightField, pos: IVec3, voxel: Voxel) {
+    let old = world.set(pos, voxel);
+    light.update(world, pos, old);
+}
+
+fn roof(world: &mut VoxelWorld, light: &mut LightField, y: i32, xs: std::ops::Range<i32>) {
+    for x in xs {
+        for z in 10..20 {
+            edit(world, light, IVec3::new(x, y, z), Voxel::GROUND);
+        }
+    }
+}
+
+fn assert_matches_full_recompute(world: &VoxelWorld, light: &LightField) {
+    let fresh = compute(world);
+    for x in 0..64 {
+        for z in 0..32 {
+            for y in 0..32 {
+                let pos = IVec3::new(x, y, z);
+                assert_eq!(light.get(pos), fresh.get(pos), "mismatch at {pos}");
+            }
+        }
+    }
+}
+
+#[test]
+fn open_sky_is_fully_lit_and_ground_is_dark() {
+    let world = flat_world();
+    let light = compute(&world);
+    assert_eq!(light.get(IVec3::new(5, 4, 5)).sun(), MAX_LIGHT);
+    assert_eq!(light.get(IVec3::new(40, 31, 5)).sun(), MAX_LIGHT);
+    assert_eq!(light.get(IVec3::new(5, 3, 5)).sun(), 0);
+    assert_eq!(light.get(IVec3::new(5, 4, 5)).block(), 0);
+}
+
+#[test]
+fn sunlight_is_removed_under_new_roof_and_spreads_in_from_the_side() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+
+    roof(&mut world, &mut light, 6, 20..40);
+    // Directly under the roof centre the sky is blocked; light arrives sideways.
+    let under = IVec3::new(30, 4, 15);
+    assert!(light.get(under).sun() < MAX_LIGHT);
+    assert!(light.get(under).sun() > 0);
+    // The edge of the roof is brighter than its middle.
+    assert!(light.get(IVec3::new(20, 4, 15)).sun() > light.get(under).sun());
+    assert_matches_full_recompute(&world, &light);
+}
+
+#[test]
+fn removing_roof_restores_full_sunlight() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    roof(&mut world, &mut light, 6, 20..40);
+
+    for x in 20..40 {
+        for z in 10..20 {
+            edit(&mut world, &mut light, IVec3::new(x, 6, z), Voxel::AIR);
+        }
+    }
+    assert_eq!(light.get(IVec3::new(30, 4, 15)).sun(), MAX_LIGHT);
+    assert_matches_full_recompute(&world, &light);
+}
+
+#[test]
+fn sealed_room_goes_dark_and_opening_it_relights() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    // Hollow box from (10, 4, 10) to (14, 8, 14) with a 3x3x3 interior.
+    for x in 10..=14 {
+        for y in 4..=8 {
+            for z in 10..=14 {
+                let shell = x == 10 || x == 14 || y == 8 || z == 10 || z == 14;
+                if shell {
+                    edit(&mut world, &mut light, IVec3::new(x, y, z), Voxel::GROUND);
+                }
+            }
+        }
+    }
+    let inside = IVec3::new(12, 5, 12);
+    assert_eq!(light.get(inside).sun(), 0);
+
+    edit(&mut world, &mut light, IVec3::new(12, 8, 12), Voxel::AIR);
+    assert_eq!(light.get(inside).sun(), MAX_LIGHT);
+    assert_matches_full_recompute(&world, &light);
+}
+
+#[test]
+fn lamp_light_falls_off_and_is_removed_with_the_lamp() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    let lamp = IVec3::new(30, 4, 16);
+    edit(&mut world, &mut light, lamp, Voxel::LAMP);
+
+    let emission = Voxel::LAMP.emission();
+    assert_eq!(light.get(lamp + IVec3::X).block(), emission - 1);
+    assert_eq!(light.get(lamp + IVec3::new(3, 0, 0)).block(), emission - 3);
+    // Light crosses the chunk boundary at x = 32.
+    assert_eq!(light.get(IVec3::new(33, 4, 16)).block(), emission - 3);
+    assert_matches_full_recompute(&world, &light);
+
+    edit(&mut world, &mut light, lamp, Voxel::AIR);
+    assert_eq!(light.get(lamp + IVec3::X).block(), 0);
+    assert_eq!(light.get(IVec3::new(33, 4, 16)).block(), 0);
+    assert_matches_full_recompute(&world, &light);
+}
+
+#[test]
+fn removing_one_of_two_lamps_keeps_the_other_lit() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    let a = IVec3::new(20, 4, 16);
+    let b = IVec3::new(26, 4, 16);
+    edit(&mut world, &mut light, a, Voxel::LAMP);
+    edit(&mut world, &mut light, b, Voxel::LAMP);
+
+    edit(&mut world, &mut light, a, Voxel::AIR);
+    let emission = Voxel::LAMP.emission();
+    assert_eq!(light.get(b + IVec3::NEG_X).block(), emission - 1);
+    assert_eq!(light.get(a).block(), emission - 6);
+    assert_matches_full_recompute(&world, &light);
+}
+
+#[test]
+fn blocking_lamp_light_with_a_wall_reroutes_it() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    let lamp = IVec3::new(30, 4, 16);
+    edit(&mut world, &mut light, lamp, Voxel::LAMP);
+    let target = lamp + IVec3::new(2, 0, 0);
+    let direct = light.get(target).block();
+
+    edit(&mut world, &mut light, lamp + IVec3::X, Voxel::GROUND);
+    assert!(light.get(target).block() < direct);
+    assert!(light.get(target).block() > 0);
+    assert_matches_full_recompute(&world, &light);
+}
+
+#[test]
+fn scattered_edits_match_full_recompute() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    // Deterministic pseudo-random edits: digging, building and lamps.
+    let mut seed: u32 = 0x9e37_79b9;
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed
+    };
+    for _ in 0..200 {
+        let pos = IVec3::new(
+            (next() % 24) as i32 + 20,
+            (next() % 8) as i32 + 1,
+            (next() % 12) as i32 + 10,
+        );
+        let voxel = match next() % 4 {
+            0 => Voxel::AIR,
+            1 => Voxel::LAMP,
+            _ => Voxel::GROUND,
+        };
+        edit(&mut world, &mut light, pos, voxel);
+    }
+    assert_matches_full_recompute(&world, &light);
+}
+
+/// Sets every voxel in `min..=max` and relights them in one batch.
+fn fill(world: &mut VoxelWorld, light: &mut LightField, min: IVec3, max: IVec3, voxel: Voxel) {
+    let mut changes = Vec::new();
+    for z in min.z..=max.z {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let pos = IVec3::new(x, y, z);
+                changes.push((pos, world.set(pos, voxel)));
+            }
+        }
+    }
+    light.update_many(world, &changes);
+}
+
+#[test]
+fn batched_edits_match_full_recompute() {
+    let mut world = flat_world();
+    let mut light = compute(&world);
+    // A roofed hall spanning both chunks, then a pit dug through its floor
+    // and a row of lamps.
+    fill(
+        &mut world,
+        &mut light,
+        IVec3::new(20, 10, 8),
+        IVec3::new(44, 12, 22),
+        Voxel::GROUND,
+    );
+    assert_eq!(light.get(IVec3::new(32, 6, 15)).sun(), MAX_LIGHT - 8);
+    assert_matches_full_recompute(&world, &light);
+
+    fill(
+        &mut world,
+        &mut light,
+        IVec3::new(28, 1, 12),
+        IVec3::new(36, 3, 18),
+        Voxel::AIR,
+    );
+    fill(
+        &mut world,
+        &mut light,
+        IVec3::new(24, 4, 15),
+        IVec3::new(40, 4, 15),
+        Voxel::LAMP,
+    );
+    assert_matches_full_recompute(&world, &light);
+
+    fill(
+        &mut world,
+        &mut light,
+        IVec3::new(20, 10, 8),
+        IVec3::new(44, 12, 22),
+        Voxel::AIR,
+    );
+    assert_matches_full_recompute(&world, &light);
+}
@@ -0,0 +1,197 @@
+use bevy::math::{IVec3, Vec3};
+use platz::lod::{chunk_distance, Downsampled, LodLevel, LodSettings};
+use platz::meshing::{
+    mesh_lod_layers, ChunkNeighborhood, MeshStyle, SurfaceMesh, VoxelSource, SKIRT_DEPTH,
+};
+use platz::voxel::{BlockMaterial, Voxel};
+use platz::world::{Chunk, VoxelWorld, CHUNK_SIZE};
+
+/// A chunk filled with ground from the bottom up to `top`, inclusive.
+fn flat_chunk(top: u32) -> Chunk {
+    let mut chunk = Chunk::new();
+    for z in 0..CHUNK_SIZE as u32 {
+        for x in 0..CHUNK_SIZE as u32 {
+            for y in 0..=top {
+                chunk.set(x, y, z, Voxel::GROUND);
+            }
+        }
+    }
+    chunk
+}
+
+fn merged(layers: Vec<(BlockMaterial, SurfaceMesh)>) -> SurfaceMesh {
+    let mut mesh = SurfaceMesh::default();
+    for (_, layer) in layers {
+        mesh.positions.extend(layer.positions);
+        mesh.normals.extend(layer.normals);
+    }
+    mesh
+}
+
+fn highest_top(mesh: &SurfaceMesh) -> f32 {
+    mesh.positions
+        .iter()
+        .zip(&mesh.normals)
+        .filter(|(_, normal)| normal[1] > 0.5)
+        .map(|(position, _)| position[1])
+        .fold(f32::MIN, f32::max)
+}
+
+#[test]
+fn levels_switch_with_hysteresis() {
+    let settings = LodSettings {
+        distances: [100.0, 200.0, 300.0],
+        hysteresis: 10.0,
+    };
+    let full = LodLevel::FULL;
+    let half = LodLevel::new(1);
+
+    // Just past the threshold is not far enough to coarsen...
+    assert_eq!(settings.select(full, 105.0), full);
+    assert_eq!(settings.select(full, 111.0), half);
+    // ...and just inside it is not near enough to refine again.
+    assert_eq!(settings.select(half, 95.0), half);
+    assert_eq!(settings.select(half, 89.0), full);
+
+    assert_eq!(settings.select(full, 500.0), LodLevel::COARSEST);
+    assert_eq!(settings.select(LodLevel::COARSEST, 0.0), full);
+    assert_eq!(LodLevel::new(9), LodLevel::COARSEST);
+    assert_eq!(LodLevel::COARSEST.scale(), 8);
+}
+
+#[test]
+fn chunk_distance_measures_to_the_nearest_face() {
+    let coord = IVec3::new(1, 0, 0);
+    assert_eq!(chunk_distance(coord, Vec3::new(40.0, 5.0, 5.0)), 0.0);
+    assert_eq!(chunk_distance(coord, Vec3::new(10.0, 5.0, 5.0)), 22.0);
+    assert_eq!(chunk_distance(coord, Vec3::new(80.0, 5.0, 5.0)), 16.0);
+}
+
+#[test]
+fn downsampling_keeps_the_surface_near_its_height() {
+    // Ten layers of ground: voxels 0..=9.
+    let chunk = flat_chunk(9);
+    let expected = [(0, 10.0), (1, 10.0), (2, 12.0), (3, 8.0)];
+    for (level, top) in expected {
+        let mesh = merged(mesh_lod_layers(
+            &chunk,
+            LodLevel::new(level),
+            MeshStyle::Blocky,
+            false,
+        ));
+        assert_eq!(highest_top(&mesh), top, "level {level}");
+    }
+}
+
+#[test]
+fn most_common_solid_block_fills_a_cell_and_plants_never_do() {
+    let mut chunk = Chunk::new();
+    // One 2x2x2 cell: five sand, two ground, one grass.
+    let cells = [(0, 0, 0), (1, 0, 0), (0, 0, 1), (1, 0, 1), (0, 1, 0)];
+    for (x, y, z) in cells {
+        chunk.set(x, y, z, Voxel::SAND);
+    }
+    chunk.set(1, 1, 0, Voxel::GROUND);
+    chunk.set(0, 1, 1, Voxel::GROUND);
+    chunk.set(1, 1, 1, Voxel::TALL_GRASS);
+    // The next cell along holds only grass.
+    for y in 0..2 {
+        for z in 0..2 {
+            for x in 2..4 {
+                chunk.set(x, y, z, Voxel::TALL_GRASS);
+            }
+        }
+    }
+
+    let merged = Downsampled::new(&chunk, LodLevel::new(1));
+    assert_eq!(merged.extent(), CHUNK_SIZE as i32 / 2);
+    assert_eq!(merged.voxel(0, 0, 0), Voxel::SAND);
+    assert_eq!(merged.voxel(1, 0, 0), Voxel::AIR);
+    assert_eq!(merged.voxel(-1, 0, 0), Voxel::AIR);
+}
+
+#[test]
+fn coarser_levels_emit_less_geometry() {
+    let mut chunk = Chunk::new();
+    for z in 0..CHUNK_SIZE as u32 {
+        for x in 0..CHUNK_SIZE as u32 {
+            let top = 8 + (x * 7 + z * 13) % 5;
+            for y in 0..=top {
+                chunk.set(x, y, z, Voxel::GROUND);
+            }
+        }
+    }
+    let counts: Vec<usize> = (0..=3)
+        .map(|level| {
+            merged(mesh_lod_layers(
+                &chunk,
+                LodLevel::new(level),
+                MeshStyle::Blocky,
+                false,
+            ))
+            .positions
+            .len()
+        })
+        .collect();
+    assert!(
+        counts.windows(2).all(|pair| pair[1] < pair[0]),
+        "vertex counts {counts:?}"
+    );
+}
+
+#[test]
+fn skirts_hang_below_the_surface_on_chunk_sides() {
+    let mut world = VoxelWorld::new();
+    for cz in 0..3 {
+        for cx in 0..3 {
+            world.insert_chunk(IVec3::new(cx, 0, cz), flat_chunk(9));
+        }
+    }
+    let neighborhood = ChunkNeighborhood::new(&world, IVec3::new(1, 0, 1), None);
+    let plain = merged(mesh_lod_layers(
+        &neighborhood,
+        LodLevel::FULL,
+        MeshStyle::Blocky,
+        false,
+    ));
+    let skirted = merged(mesh_lod_layers(
+        &neighborhood,
+        LodLevel::FULL,
+        MeshStyle::Blocky,
+        true,
+    ));
+
+    // Four sides, a row of faces along each, SKIRT_DEPTH faces deep.
+    let skirt_faces = 4 * CHUNK_SIZE * SKIRT_DEPTH as usize;
+    assert_eq!(
+        skirted.positions.len(),
+        plain.positions.len() + skirt_faces * 4
+    );
+    let sides: Vec<_> = skirted
+        .positions
+        .iter()
+        .zip(&skirted.normals)
+        .filter(|(_, normal)| normal[1] == 0.0)
+        .map(|(position, _)| position[1])
+        .collect();
+    assert_eq!(sides.len(), skirt_faces * 4);
+    let lowest = 10.0 - SKIRT_DEPTH as f32;
+    assert!(sides.iter().all(|&y| (lowest..=10.0).contains(&y)));
+
+    // The same skirts at a coarser level, in merged cells.
+    let coarse = merged(mesh_lod_layers(
+        &neighborhood,
+        LodLevel::new(1),
+        MeshStyle::Blocky,
+        true,
+    ));
+    let coarse_sides = coarse
+        .normals
+        .iter()
+        .filter(|normal| normal[1] == 0.0)
+        .count();
+    assert_eq!(
+        coarse_sides,
+        4 * (CHUNK_SIZE / 2) * SKIRT_DEPTH as usize * 4
+    );
+}
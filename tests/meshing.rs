@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec3, Vec3};
+use platz::meshing::{
+    mesh_smooth, mesh_smooth_layers, mesh_voxels, ChunkNeighborhood, MeshStyle, SurfaceMesh,
+};
+use platz::voxel::{BlockMaterial, Transparency, Voxel};
+use platz::world::{Chunk, VoxelWorld, CHUNK_SIZE};
+
+fn ball(center: Vec3, radius: f32) -> Chunk {
+    let mut chunk = Chunk::new();
+    let n = CHUNK_SIZE as u32;
+    for z in 0..n {
+        for y in 0..n {
+            for x in 0..n {
+                let cell = Vec3::new(x as f32, y as f32, z as f32) + Vec3::splat(0.5);
+                if cell.distance(center) <= radius {
+                    chunk.set(x, y, z, Voxel::GROUND);
+                }
+            }
+        }
+    }
+    chunk
+}
+
+fn vertex_key(position: [f32; 3]) -> [i32; 3] {
+    position.map(|v| (v * 64.0).round() as i32)
+}
+
+#[test]
+fn flat_ground_keeps_its_height_and_points_up() {
+    let mut chunk = Chunk::new();
+    for z in 0..CHUNK_SIZE as u32 {
+        for x in 0..CHUNK_SIZE as u32 {
+            for y in 0..10 {
+                chunk.set(x, y, z, Voxel::GROUND);
+            }
+        }
+    }
+    let mesh = mesh_smooth(&chunk);
+    let interior: Vec<usize> = (0..mesh.positions.len())
+        .filter(|&i| {
+            let [x, y, z] = mesh.positions[i];
+            y > 5.0 && (2.0..30.0).contains(&x) && (2.0..30.0).contains(&z)
+        })
+        .collect();
+    assert!(!interior.is_empty());
+    for i in interior {
+        assert_eq!(mesh.positions[i][1], 10.0);
+        assert_eq!(mesh.normals[i], [0.0, 1.0, 0.0]);
+    }
+}
+
+#[test]
+fn smooth_surface_is_closed_with_outward_normals() {
+    let center = Vec3::splat(16.0);
+    let mesh = mesh_smooth(&ball(center, 7.0));
+    assert!(!mesh.indices.is_empty());
+
+    // Every edge is shared by exactly two triangles.
+    let mut edges: HashMap<(u32, u32), u32> = HashMap::new();
+    for triangle in mesh.indices.chunks(3) {
+        for i in 0..3 {
+            let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+            *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    assert!(edges.values().all(|&count| count == 2));
+
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let normal = Vec3::from(*normal);
+        assert!((normal.length() - 1.0).abs() < 1e-4);
+        assert!(normal.dot(Vec3::from(*position) - center) > 0.0);
+    }
+}
+
+#[test]
+fn triangles_wind_like_blocky_faces() {
+    let mesh = mesh_smooth(&ball(Vec3::splat(16.0), 5.0));
+    for triangle in mesh.indices.chunks(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.positions[triangle[i] as usize]));
+        let normal = Vec3::from(mesh.normals[triangle[0] as usize]);
+        assert!((b - a).cross(c - a).dot(normal) < 0.0);
+    }
+}
+
+#[test]
+fn chunks_share_their_border_vertices() {
+    let mut world = VoxelWorld::new();
+    // A slope running across the border between two chunks.
+    for z in 0..32 {
+        for x in 0..64 {
+            for y in 0..=(4 + x / 3) {
+                world.set(IVec3::new(x, y, z), Voxel::GROUND);
+            }
+        }
+    }
+    let world_vertices = |coord: IVec3| -> Vec<([i32; 3], [i32; 3])> {
+        let neighborhood = ChunkNeighborhood::new(&world, coord, None);
+        let mesh: SurfaceMesh = mesh_smooth(&neighborhood);
+        let offset = (coord * CHUNK_SIZE as i32).as_vec3();
+        mesh.positions
+            .iter()
+            .zip(&mesh.normals)
+            .map(|(position, normal)| {
+                let world = Vec3::from(*position) + offset;
+                (vertex_key(world.to_array()), vertex_key(*normal))
+            })
+            .collect()
+    };
+    let left = world_vertices(IVec3::ZERO);
+    let right = world_vertices(IVec3::X);
+    let border: Vec<_> = right
+        .iter()
+        .filter(|(pos, _)| pos[0] < 32 * 64 + 32)
+        .collect();
+    assert!(!border.is_empty());
+    for vertex in border {
+        assert!(
+            left.contains(vertex),
+            "{vertex:?} missing from the left chunk"
+        );
+    }
+}
+
+#[test]
+fn see_through_blocks_stay_blocky() {
+    let mut chunk = ball(Vec3::splat(16.0), 4.0);
+    chunk.set(16, 22, 16, Voxel::WATER);
+    chunk.set(16, 23, 16, Voxel::LEAVES);
+    let layers = mesh_smooth_layers(&chunk);
+    let count = |material: BlockMaterial| {
+        layers
+            .iter()
+            .find(|(m, _)| *m == material)
+            .map_or(0, |(_, mesh)| mesh.indices.len() / 6)
+    };
+    assert!(count(BlockMaterial::Terrain) > 0);
+    assert_eq!(count(BlockMaterial::Water), 6);
+    assert_eq!(count(BlockMaterial::Leaves), 6);
+}
+
+#[test]
+fn mesh_style_from_args() {
+    let parse = |args: &[&str]| MeshStyle::from_args(args.iter().map(|arg| arg.to_string()));
+    assert_eq!(parse(&[]), Ok(MeshStyle::Blocky));
+    assert_eq!(parse(&["--mesh", "smooth"]), Ok(MeshStyle::Smooth));
+    assert_eq!(
+        parse(&["--seed", "3", "--mesh", "blocky"]),
+        Ok(MeshStyle::Blocky)
+    );
+    assert!(parse(&["--mesh", "wavy"]).is_err());
+    assert!(parse(&["--mesh"]).is_err());
+}
+
+fn faces_between(a: Voxel, b: Voxel) -> usize {
+    let mut chunk = Chunk::new();
+    chunk.set(4, 4, 4, a);
+    chunk.set(5, 4, 4, b);
+    mesh_voxels(&chunk).indices.len() / 6
+}
+
+#[test]
+fn culling_follows_transparency_classes() {
+    // Two cubes show 12 faces; hiding one shared face leaves 11, both 10.
+    assert_eq!(faces_between(Voxel::GROUND, Voxel::GROUND), 10);
+    assert_eq!(faces_between(Voxel::GLASS, Voxel::GLASS), 10);
+    assert_eq!(faces_between(Voxel::WATER, Voxel::WATER), 10);
+    assert_eq!(faces_between(Voxel::LEAVES, Voxel::LEAVES), 12);
+    // Ground hides the leaf face against it but still shows through leaves.
+    assert_eq!(faces_between(Voxel::GROUND, Voxel::LEAVES), 11);
+    assert_eq!(faces_between(Voxel::GROUND, Voxel::GLASS), 11);
+    assert_eq!(faces_between(Voxel::GLASS, Voxel::WATER), 12);
+}
+
+#[test]
+fn transparency_comes_from_the_material() {
+    for id in 0..=u8::MAX {
+        let voxel = Voxel::new(id, 0);
+        assert_eq!(voxel.transparency(), voxel.material().transparency());
+    }
+    assert_eq!(Voxel::AIR.transparency(), Transparency::Cutout);
+    assert!(!Voxel::AIR.is_opaque());
+    assert!(!BlockMaterial::ALL.contains(&Voxel::AIR.material()));
+    assert_eq!(Voxel::GLASS.transparency(), Transparency::Translucent);
+    assert_eq!(Voxel::LEAVES.transparency(), Transparency::Cutout);
+    assert_eq!(Voxel::GROUND.transparency(), Transparency::Opaque);
+    assert!(!Voxel::GLASS.is_opaque());
+}
+
+fn mesh_of(voxels: &[(IVec3, Voxel)]) -> SurfaceMesh {
+    let mut chunk = Chunk::new();
+    for &(pos, voxel) in voxels {
+        chunk.set(pos.x as u32, pos.y as u32, pos.z as u32, voxel);
+    }
+    mesh_voxels(&chunk)
+}
+
+fn quads(voxels: &[(IVec3, Voxel)]) -> usize {
+    mesh_of(voxels).indices.len() / 6
+}
+
+#[test]
+fn slabs_only_hide_the_face_they_cover() {
+    let slab = Voxel::COBBLESTONE_SLAB;
+    assert_eq!(quads(&[(IVec3::new(4, 4, 4), slab)]), 6);
+    // Ground beside a slab keeps its side face; the slab's side against the
+    // ground is hidden.
+    let beside = [
+        (IVec3::new(4, 4, 4), Voxel::GROUND),
+        (IVec3::new(5, 4, 4), slab),
+    ];
+    assert_eq!(quads(&beside), 11);
+    // Ground under a slab loses its top face, and the slab its bottom.
+    let under = [
+        (IVec3::new(4, 3, 4), Voxel::GROUND),
+        (IVec3::new(4, 4, 4), slab),
+    ];
+    assert_eq!(quads(&under), 10);
+
+    let top = mesh_of(&[(IVec3::new(4, 4, 4), slab)])
+        .positions
+        .iter()
+        .map(|position| position[1])
+        .fold(f32::MIN, f32::max);
+    assert_eq!(top, 4.5);
+}
+
+#[test]
+fn stairs_cover_their_back_side() {
+    let stairs = Voxel::brick_stairs(IVec3::X);
+    assert_eq!(Voxel::from_name("brick_stairs[facing=east]"), Some(stairs));
+    assert_eq!(quads(&[(IVec3::new(4, 4, 4), stairs)]), 11);
+    // Nothing is meshed where the step rests on the slab: the only quad at
+    // half height is the tread in front of the step.
+    let mesh = mesh_of(&[(IVec3::new(4, 4, 4), stairs)]);
+    let half_height: Vec<_> = mesh
+        .positions
+        .chunks(4)
+        .zip(mesh.normals.chunks(4))
+        .filter(|(quad, normal)| normal[0][1] != 0.0 && quad.iter().all(|p| p[1] == 4.5))
+        .collect();
+    assert_eq!(half_height.len(), 1);
+    let (tread, normal) = half_height[0];
+    assert_eq!(normal[0], [0.0, 1.0, 0.0]);
+    assert!(tread.iter().all(|p| (4.0..=4.5).contains(&p[0])));
+    let back = [
+        (IVec3::new(4, 4, 4), stairs),
+        (IVec3::new(5, 4, 4), Voxel::GROUND),
+    ];
+    let front = [
+        (IVec3::new(4, 4, 4), stairs),
+        (IVec3::new(3, 4, 4), Voxel::GROUND),
+    ];
+    // Against the back the ground face is hidden; the stairs' faces there
+    // are hidden by the ground either way.
+    assert_eq!(quads(&back), 11 + 5 - 2);
+    assert_eq!(quads(&front), 11 + 6 - 1);
+    assert!(!stairs.is_opaque());
+}
+
+#[test]
+fn fences_join_their_neighbours() {
+    let lone = [(IVec3::new(4, 4, 4), Voxel::FENCE)];
+    assert_eq!(quads(&lone), 6);
+    let pair = [
+        (IVec3::new(4, 4, 4), Voxel::FENCE),
+        (IVec3::new(5, 4, 4), Voxel::FENCE),
+    ];
+    // Each post grows two bars towards the other.
+    assert_eq!(quads(&pair), 2 * (6 + 2 * 6));
+    let mesh = mesh_of(&pair);
+    let reach = mesh.positions.iter().map(|p| p[0]).fold(f32::MIN, f32::max);
+    assert_eq!(reach, 5.625);
+}
+
+#[test]
+fn plants_are_crossed_quads() {
+    let mesh = mesh_of(&[(IVec3::new(4, 4, 4), Voxel::TALL_GRASS)]);
+    assert_eq!(mesh.indices.len() / 6, 2);
+    assert!(mesh.normals.iter().all(|normal| normal[1] == 0.0));
+    // Plants never hide the ground under them.
+    let on_ground = [
+        (IVec3::new(4, 3, 4), Voxel::GROUND),
+        (IVec3::new(4, 4, 4), Voxel::TALL_GRASS),
+    ];
+    assert_eq!(quads(&on_ground), 6 + 2);
+}
+
+/// Vertex ambient occlusion and triangle indices of the top face of `cell`,
+/// indices relative to the face's first vertex.
+fn top_face(mesh: &SurfaceMesh, cell: IVec3) -> ([f32; 4], Vec<u32>) {
+    let corner = (cell + IVec3::Y).as_vec3().to_array();
+    let base = (0..mesh.positions.len())
+        .step_by(4)
+        .find(|&i| mesh.positions[i] == corner && mesh.normals[i] == [0.0, 1.0, 0.0])
+        .expect("top face");
+    let ao = [0, 1, 2, 3].map(|k| mesh.ao[base + k]);
+    let quad = base as u32..base as u32 + 4;
+    let indices = mesh
+        .indices
+        .iter()
+        .filter(|index| quad.contains(index))
+        .map(|index| index - base as u32)
+        .collect();
+    (ao, indices)
+}
+
+#[test]
+fn ambient_occlusion_darkens_inner_corners() {
+    let floor = IVec3::new(5, 5, 5);
+    let mut voxels = vec![(floor, Voxel::GROUND)];
+    // Two walls meeting in an L above the floor's +x +z corner.
+    for i in 4..=6 {
+        voxels.push((IVec3::new(6, 6, i), Voxel::GROUND));
+        voxels.push((IVec3::new(i, 6, 6), Voxel::GROUND));
+    }
+    let (ao, _) = top_face(&mesh_of(&voxels), floor);
+    assert_eq!(ao, [1.0, 0.65, 0.5, 0.65]);
+
+    // Two side neighbours occlude the vertex fully without the corner.
+    let sides_only = [
+        (floor, Voxel::GROUND),
+        (IVec3::new(6, 6, 5), Voxel::GROUND),
+        (IVec3::new(5, 6, 6), Voxel::GROUND),
+    ];
+    let (ao, _) = top_face(&mesh_of(&sides_only), floor);
+    assert_eq!(ao, [1.0, 0.8, 0.5, 0.8]);
+}
+
+#[test]
+fn quads_split_along_the_brighter_diagonal() {
+    let floor = IVec3::new(5, 5, 5);
+    let (ao, indices) = top_face(&mesh_of(&[(floor, Voxel::GROUND)]), floor);
+    assert_eq!(ao, [1.0; 4]);
+    assert_eq!(indices, [0, 1, 2, 0, 2, 3]);
+
+    // A lone corner voxel darkens vertex 0, so the 0-2 diagonal is the
+    // darker one and the quad is split along 1-3 instead.
+    let voxels = [(floor, Voxel::GROUND), (IVec3::new(4, 6, 4), Voxel::GROUND)];
+    let (ao, indices) = top_face(&mesh_of(&voxels), floor);
+    assert_eq!(ao, [0.8, 1.0, 1.0, 1.0]);
+    assert!(ao[0] + ao[2] < ao[1] + ao[3]);
+    assert_eq!(indices, [1, 2, 3, 1, 3, 0]);
+}
+
+#[test]
+fn ambient_occlusion_reads_neighbouring_chunks() {
+    let edge = CHUNK_SIZE as i32 - 1;
+    let floor = IVec3::new(edge, 5, 5);
+    let mut world = VoxelWorld::new();
+    world.set(floor, Voxel::GROUND);
+    // The occluder sits just across the border, in the chunk at +x.
+    world.set(IVec3::new(edge + 1, 6, 5), Voxel::GROUND);
+
+    let neighborhood = ChunkNeighborhood::new(&world, IVec3::ZERO, None);
+    let (ao, _) = top_face(&mesh_voxels(&neighborhood), floor);
+    assert_eq!(ao, [1.0, 0.8, 0.8, 1.0]);
+
+    // Meshed on its own, the chunk cannot see the occluder.
+    let (ao, _) = top_face(&mesh_of(&[(floor, Voxel::GROUND)]), floor);
+    assert_eq!(ao, [1.0; 4]);
+}
@@ -0,0 +1,114 @@
+use bevy::math::{IVec2, IVec3, Vec2};
+use platz::minimap::{draw_marker, surface_color, MapView, WorldMap, UNMAPPED};
+use platz::voxel::Voxel;
+use platz::world::{HeightMap, VoxelWorld};
+
+/// Flat ground at y = 2 over the first chunk column, with a pond and a hill.
+fn world() -> VoxelWorld {
+    let mut world = VoxelWorld::new();
+    for z in 0..32 {
+        for x in 0..32 {
+            for y in 0..=2 {
+                world.set(IVec3::new(x, y, z), Voxel::GROUND);
+            }
+        }
+    }
+    world.set(IVec3::new(10, 2, 10), Voxel::WATER);
+    world.set(IVec3::new(11, 2, 10), Voxel::WATER);
+    world.set(IVec3::new(11, 1, 10), Voxel::WATER);
+    world.set(IVec3::new(20, 3, 20), Voxel::SAND);
+    world
+}
+
+fn mapped() -> (VoxelWorld, WorldMap) {
+    let world = world();
+    let mut map = WorldMap::new();
+    map.update_tile(&world, &HeightMap::build(&world), IVec2::ZERO);
+    (world, map)
+}
+
+#[test]
+fn tiles_shade_the_top_block_of_each_column() {
+    let (_, map) = mapped();
+    assert_eq!(map.tile_count(), 1);
+    assert_eq!(map.color(5, 5), Some(surface_color(Voxel::GROUND)));
+    assert_eq!(map.color(40, 5), None);
+    assert_eq!(map.color(-1, 0), None);
+
+    // Deeper water is darker.
+    let shallow = map.color(10, 10).unwrap();
+    let deep = map.color(11, 10).unwrap();
+    assert!(deep[2] < shallow[2]);
+    assert!(shallow[2] < surface_color(Voxel::WATER)[2]);
+    // Slopes facing the north-west light are brighter, the far side darker.
+    let hill = map.color(20, 20).unwrap();
+    assert!(hill[0] > surface_color(Voxel::SAND)[0]);
+    assert!(map.color(21, 21).unwrap()[1] < surface_color(Voxel::GROUND)[1]);
+}
+
+#[test]
+fn tiles_refresh_and_drop_independently() {
+    let (mut world, mut map) = mapped();
+    let revision = map.revision();
+    world.set(IVec3::new(5, 3, 5), Voxel::SNOW);
+    map.update_tile(&world, &HeightMap::build(&world), IVec2::ZERO);
+    assert!(map.revision() > revision);
+    // Snow on top, lit as a bump.
+    assert!(map.color(5, 5).unwrap()[2] >= surface_color(Voxel::SNOW)[2]);
+
+    // A column with nothing in it leaves no tile behind.
+    map.update_tile(&world, &HeightMap::build(&world), IVec2::new(3, 3));
+    assert_eq!(map.tile_count(), 1);
+    assert_eq!(map.bounds(), Some((IVec2::ZERO, IVec2::splat(32))));
+}
+
+#[test]
+fn views_map_pixels_to_world_columns() {
+    let (_, map) = mapped();
+    let view = MapView {
+        center: Vec2::new(16.0, 16.0),
+        cells_per_pixel: 2.0,
+    };
+    let half = Vec2::splat(8.0);
+    assert_eq!(view.to_world(half, half), view.center);
+    assert_eq!(
+        view.to_pixel(Vec2::new(20.0, 14.0), half),
+        Vec2::new(10.0, 7.0)
+    );
+
+    let pixels = map.render(view, 16, 16);
+    assert_eq!(pixels.len(), 16 * 16 * 4);
+    // Pixel (0, 0) covers world (0..2, 0..2); the whole view is mapped.
+    assert_eq!(pixels[..4], surface_color(Voxel::GROUND));
+    let far = map.render(
+        MapView {
+            center: Vec2::splat(100.0),
+            cells_per_pixel: 1.0,
+        },
+        4,
+        4,
+    );
+    assert!(far.chunks(4).all(|pixel| pixel == UNMAPPED));
+}
+
+#[test]
+fn markers_point_along_their_heading() {
+    let mut pixels = vec![0; 32 * 32 * 4];
+    draw_marker(&mut pixels, 32, 32, Vec2::splat(16.0), Vec2::X, [255; 4]);
+    let lit = |x: usize, y: usize| pixels[(x + y * 32) * 4] == 255;
+    assert!(lit(19, 16));
+    assert!(!lit(12, 16));
+    // Markers at the image edge are clipped rather than wrapped.
+    draw_marker(&mut pixels, 32, 32, Vec2::new(31.0, 0.0), Vec2::Y, [255; 4]);
+}
+
+#[test]
+fn exports_the_mapped_area_as_png() {
+    let (_, map) = mapped();
+    let path = std::env::temp_dir().join(format!("platz-map-{}.png", std::process::id()));
+    map.save_png(&path).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(&bytes[1..4], b"PNG");
+    assert!(WorldMap::new().save_png(&path).is_err());
+}
@@ -0,0 +1,173 @@
+use bevy::math::{IVec3, Vec3};
+use platz::pathfinding::{find_path, is_walkable, standing_cell, AgentProfile};
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+const STONE: Voxel = Voxel(1);
+
+/// Flat floor whose top surface is at `y = floor_y`, standing cells at `floor_y + 1`.
+fn floor(world: &mut VoxelWorld, min: IVec3, max: IVec3, floor_y: i32) {
+    for x in min.x..=max.x {
+        for z in min.z..=max.z {
+            world.set(IVec3::new(x, floor_y, z), STONE);
+        }
+    }
+}
+
+fn wall(world: &mut VoxelWorld, x: i32, z_range: std::ops::RangeInclusive<i32>, y0: i32, h: i32) {
+    for z in z_range {
+        for y in y0..y0 + h {
+            world.set(IVec3::new(x, y, z), STONE);
+        }
+    }
+}
+
+fn cell(path: &[Vec3], i: usize) -> IVec3 {
+    path[i].floor().as_ivec3()
+}
+
+#[test]
+fn straight_path_on_flat_floor() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 0, 0), IVec3::new(9, 0, 9), 0);
+    let agent = AgentProfile::default();
+
+    let path = find_path(&world, IVec3::new(1, 1, 1), IVec3::new(8, 1, 1), &agent).unwrap();
+    assert_eq!(path.len(), 8);
+    assert_eq!(path[0], Vec3::new(1.5, 1.0, 1.5));
+    assert_eq!(*path.last().unwrap(), Vec3::new(8.5, 1.0, 1.5));
+}
+
+#[test]
+fn path_crosses_chunk_boundaries_including_negative_coordinates() {
+    let mut world = VoxelWorld::new();
+    floor(
+        &mut world,
+        IVec3::new(-4, 31, -2),
+        IVec3::new(36, 31, 2),
+        31,
+    );
+    let agent = AgentProfile::default();
+
+    let start = IVec3::new(-3, 32, 0);
+    let goal = IVec3::new(35, 32, 0);
+    let path = find_path(&world, start, goal, &agent).unwrap();
+    assert_eq!(cell(&path, 0), start);
+    assert_eq!(cell(&path, path.len() - 1), goal);
+    assert!(path.iter().all(|p| p.y == 32.0));
+}
+
+#[test]
+fn path_detours_around_wall_gap() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 0, 0), IVec3::new(12, 0, 12), 0);
+    // Wall along x = 6 with a single gap at z = 10.
+    wall(&mut world, 6, 0..=9, 1, 3);
+    wall(&mut world, 6, 11..=12, 1, 3);
+    let agent = AgentProfile::default();
+
+    let path = find_path(&world, IVec3::new(2, 1, 2), IVec3::new(10, 1, 2), &agent).unwrap();
+    assert!(path.iter().any(|p| cell(&[*p], 0) == IVec3::new(6, 1, 10)));
+    assert!(path.iter().all(|p| !world.is_solid(p.floor().as_ivec3())));
+}
+
+#[test]
+fn sealed_wall_blocks_path() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 0, 0), IVec3::new(12, 0, 12), 0);
+    wall(&mut world, 6, 0..=12, 1, 2);
+    let agent = AgentProfile::default();
+
+    assert!(find_path(&world, IVec3::new(2, 1, 2), IVec3::new(10, 1, 2), &agent).is_none());
+}
+
+#[test]
+fn single_step_ledge_is_climbable_but_cliff_is_not() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 0, 0), IVec3::new(10, 0, 0), 0);
+    // One-voxel ledge, then a three-voxel cliff.
+    floor(&mut world, IVec3::new(4, 1, 0), IVec3::new(10, 1, 0), 1);
+    wall(&mut world, 8, 0..=0, 2, 2);
+    let agent = AgentProfile::default();
+
+    let up_ledge = find_path(&world, IVec3::new(1, 1, 0), IVec3::new(6, 2, 0), &agent).unwrap();
+    assert_eq!(cell(&up_ledge, up_ledge.len() - 1), IVec3::new(6, 2, 0));
+    assert!(find_path(&world, IVec3::new(1, 1, 0), IVec3::new(8, 4, 0), &agent).is_none());
+
+    // Dropping off the cliff is fine.
+    let down = find_path(&world, IVec3::new(8, 4, 0), IVec3::new(1, 1, 0), &agent).unwrap();
+    assert_eq!(cell(&down, down.len() - 1), IVec3::new(1, 1, 0));
+}
+
+#[test]
+fn clearance_prevents_walking_under_low_ceiling() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 0, 0), IVec3::new(8, 0, 0), 0);
+    // Ceiling at y = 2 over x = 4 leaves only one voxel of headroom.
+    world.set(IVec3::new(4, 2, 0), STONE);
+
+    let tall = AgentProfile::default();
+    assert!(find_path(&world, IVec3::new(1, 1, 0), IVec3::new(7, 1, 0), &tall).is_none());
+
+    let short = AgentProfile {
+        clearance: 1,
+        ..AgentProfile::default()
+    };
+    assert!(find_path(&world, IVec3::new(1, 1, 0), IVec3::new(7, 1, 0), &short).is_some());
+}
+
+#[test]
+fn max_slope_rejects_staircase_but_not_single_ledge() {
+    let mut world = VoxelWorld::new();
+    // Staircase rising one voxel per column along +x.
+    for x in 0..8 {
+        wall(&mut world, x, 0..=0, 0, x + 1);
+    }
+    let gentle = AgentProfile {
+        max_slope: 0.75,
+        ..AgentProfile::default()
+    };
+    assert!(!is_walkable(&world, IVec3::new(4, 5, 0), &gentle));
+    assert!(is_walkable(
+        &world,
+        IVec3::new(4, 5, 0),
+        &AgentProfile::default()
+    ));
+
+    let mut ledge = VoxelWorld::new();
+    floor(&mut ledge, IVec3::new(0, 0, 0), IVec3::new(8, 0, 0), 0);
+    floor(&mut ledge, IVec3::new(4, 1, 0), IVec3::new(8, 1, 0), 1);
+    assert!(is_walkable(&ledge, IVec3::new(4, 2, 0), &gentle));
+}
+
+#[test]
+fn diagonal_moves_do_not_cut_corners() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 0, 0), IVec3::new(4, 0, 4), 0);
+    wall(&mut world, 2, 1..=1, 1, 2);
+    let agent = AgentProfile::default();
+
+    let path = find_path(&world, IVec3::new(1, 1, 2), IVec3::new(2, 1, 0), &agent).unwrap();
+    for pair in path.windows(2) {
+        let a = pair[0].floor().as_ivec3();
+        let b = pair[1].floor().as_ivec3();
+        if a.x != b.x && a.z != b.z {
+            assert!(!world.is_solid(IVec3::new(b.x, a.y, a.z)));
+            assert!(!world.is_solid(IVec3::new(a.x, a.y, b.z)));
+        }
+    }
+}
+
+#[test]
+fn standing_cell_snaps_down_to_ground() {
+    let mut world = VoxelWorld::new();
+    floor(&mut world, IVec3::new(0, 4, 0), IVec3::new(2, 4, 2), 4);
+    let agent = AgentProfile::default();
+
+    let found = standing_cell(&world, Vec3::new(1.2, 9.7, 1.8), 16, &agent);
+    assert_eq!(found, Some(IVec3::new(1, 5, 1)));
+    assert_eq!(
+        standing_cell(&world, Vec3::new(1.2, 9.7, 1.8), 2, &agent),
+        None
+    );
+}
@@ -0,0 +1,162 @@
+use bevy::math::{IVec3, Vec3};
+use platz::structure::{Placement, StructureTemplate};
+use platz::voxel::Voxel;
+use platz::world::VoxelWorld;
+
+const RUIN: &str = include_str!("../assets/structures/ruin.txt");
+const FOUNTAIN: &str = include_str!("../assets/structures/fountain.txt");
+
+const STEP: &str = "
+// An L-shaped step with a marker on top.
+anchor 0 0 0
+block # brick
+block o lamp
+marker M flag
+layer
+##-
+#o.
+layer
+M--
+---
+";
+
+#[test]
+fn built_in_templates_parse_with_their_markers() {
+    let ruin = StructureTemplate::parse(RUIN).unwrap();
+    assert_eq!(ruin.size(), IVec3::new(13, 7, 13));
+    let markers = ruin.markers(&Placement::default());
+    assert_eq!(markers.len(), 1);
+    assert_eq!(markers[0].name, "explosive");
+
+    let fountain = StructureTemplate::parse(FOUNTAIN).unwrap();
+    let placement = Placement::at(IVec3::new(100, 10, 50));
+    let markers = fountain.markers(&placement);
+    assert_eq!(markers[0].name, "spray");
+    assert_eq!(markers[0].pos, IVec3::new(100, 12, 50));
+    let water = fountain
+        .blocks(&placement)
+        .iter()
+        .filter(|(_, voxel)| *voxel == Voxel::WATER)
+        .count();
+    assert_eq!(water, 21);
+}
+
+#[test]
+fn stamping_writes_palette_blocks_and_keeps_dashes() {
+    let template = StructureTemplate::parse(STEP).unwrap();
+    let mut world = VoxelWorld::new();
+    let origin = IVec3::new(4, 4, 4);
+    world.set(origin + IVec3::new(2, 0, 0), Voxel::SAND);
+    world.set(origin + IVec3::new(2, 0, 1), Voxel::SAND);
+    world.set(origin + IVec3::new(0, 1, 0), Voxel::SAND);
+
+    let markers = template.stamp(&mut world, &Placement::at(origin));
+    assert_eq!(world.get(origin), Voxel::BRICK);
+    assert_eq!(world.get(origin + IVec3::new(1, 0, 1)), Voxel::LAMP);
+    // '-' keeps, '.' clears, a marker cell is cleared.
+    assert_eq!(world.get(origin + IVec3::new(2, 0, 0)), Voxel::SAND);
+    assert_eq!(world.get(origin + IVec3::new(2, 0, 1)), Voxel::AIR);
+    assert_eq!(world.get(origin + IVec3::Y), Voxel::AIR);
+    assert_eq!(markers.len(), 1);
+    assert_eq!(markers[0].pos, origin + IVec3::Y);
+}
+
+#[test]
+fn quarter_turns_and_mirroring_move_cells_around_the_anchor() {
+    let template = StructureTemplate::parse(STEP).unwrap();
+    let lamp = |placement: Placement| {
+        template
+            .blocks(&placement)
+            .into_iter()
+            .find(|(_, voxel)| *voxel == Voxel::LAMP)
+            .unwrap()
+            .0
+    };
+    let origin = IVec3::new(10, 0, 10);
+    let mut placement = Placement::at(origin);
+    assert_eq!(lamp(placement), origin + IVec3::new(1, 0, 1));
+    placement.quarter_turns = 1;
+    assert_eq!(lamp(placement), origin + IVec3::new(1, 0, -1));
+    placement.quarter_turns = 2;
+    assert_eq!(lamp(placement), origin + IVec3::new(-1, 0, -1));
+    placement.quarter_turns = 4;
+    assert_eq!(lamp(placement), origin + IVec3::new(1, 0, 1));
+    placement.quarter_turns = 0;
+    placement.mirror = true;
+    assert_eq!(lamp(placement), origin + IVec3::new(-1, 0, 1));
+
+    // The quaternion agrees with the cell rotation.
+    placement.mirror = false;
+    placement.quarter_turns = 1;
+    let turned = placement.rotation() * Vec3::X;
+    assert!((turned - Vec3::NEG_Z).length() < 1e-5);
+}
+
+#[test]
+fn rotated_bounds_swap_width_and_depth() {
+    let ruin = StructureTemplate::parse(RUIN).unwrap();
+    let template = StructureTemplate::parse(STEP).unwrap();
+    let placement = Placement {
+        origin: IVec3::ZERO,
+        quarter_turns: 1,
+        mirror: true,
+    };
+    let (min, max) = template.bounds(&placement);
+    assert_eq!(max - min, IVec3::new(1, 1, 2));
+    let (min, max) = ruin.bounds(&placement);
+    assert_eq!(max - min + IVec3::ONE, IVec3::new(13, 7, 13));
+    assert_eq!(
+        ruin.blocks(&placement).len(),
+        ruin.blocks(&Placement::default()).len()
+    );
+}
+
+#[test]
+fn malformed_templates_are_rejected() {
+    assert!(StructureTemplate::parse("block # marble\nlayer\n#").is_err());
+    assert!(StructureTemplate::parse("layer\n##\n#").is_err());
+    assert!(StructureTemplate::parse("layer\n#?").is_err());
+    assert!(StructureTemplate::parse("##").is_err());
+    assert!(StructureTemplate::parse("anchor 1 2\nlayer\n.").is_err());
+    assert_eq!(Voxel::from_name("brick"), Some(Voxel::BRICK));
+    assert_eq!(Voxel::from_name("water"), Some(Voxel::WATER));
+}
+
+#[test]
+fn connectors_face_out_of_their_side_and_turn_with_the_template() {
+    let source = "block # brick\nconnector D door\nlayer\n#D#\n#.#\n###";
+    let template = StructureTemplate::parse(source).unwrap();
+    let connectors = template.connectors(&Placement::default());
+    assert_eq!(connectors.len(), 1);
+    assert_eq!(connectors[0].tag, "door");
+    assert_eq!(connectors[0].pos, IVec3::new(1, 0, 0));
+    assert_eq!(connectors[0].facing, IVec3::NEG_Z);
+
+    let turned = Placement {
+        quarter_turns: 1,
+        ..Placement::default()
+    };
+    assert_eq!(template.connectors(&turned)[0].facing, IVec3::NEG_X);
+    assert_eq!(template.connectors(&turned)[0].pos, IVec3::new(0, 0, -1));
+
+    // A corner cell sits on two sides, so its facing is ambiguous.
+    assert!(StructureTemplate::parse("connector D door\nlayer\nD#\n##").is_err());
+}
+
+#[test]
+fn facing_blocks_turn_with_the_template() {
+    let source = "block s brick_stairs[facing=east]\nlayer\ns";
+    let template = StructureTemplate::parse(source).unwrap();
+    let facing = |placement: Placement| template.blocks(&placement)[0].1.facing();
+    assert_eq!(facing(Placement::default()), Some(IVec3::X));
+    let turned = Placement {
+        quarter_turns: 1,
+        ..Placement::default()
+    };
+    assert_eq!(facing(turned), Some(IVec3::NEG_Z));
+    let mirrored = Placement {
+        mirror: true,
+        ..Placement::default()
+    };
+    assert_eq!(facing(mirrored), Some(IVec3::NEG_X));
+}
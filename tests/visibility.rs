@@ -0,0 +1,66 @@
+use bevy::math::IVec3;
+use platz::visibility::{visible_chunks, FaceConnections};
+use platz::voxel::Voxel;
+use platz::world::{Chunk, CHUNK_SIZE};
+
+const N: u32 = CHUNK_SIZE as u32;
+
+#[test]
+fn uniform_chunks_connect_all_or_nothing() {
+    assert_eq!(FaceConnections::of(&Chunk::new()), FaceConnections::ALL);
+    assert_eq!(
+        FaceConnections::of(&Chunk::filled(Voxel::GROUND)),
+        FaceConnections::NONE
+    );
+    // Glass lets the camera see through.
+    assert_eq!(
+        FaceConnections::of(&Chunk::filled(Voxel::GLASS)),
+        FaceConnections::ALL
+    );
+}
+
+#[test]
+fn a_tunnel_only_connects_its_two_ends() {
+    let mut chunk = Chunk::filled(Voxel::GROUND);
+    for x in 0..N {
+        chunk.set(x, 10, 10, Voxel::AIR);
+    }
+    // A sealed cave touching no side connects nothing.
+    chunk.set(20, 20, 20, Voxel::AIR);
+    let connections = FaceConnections::of(&chunk);
+    assert!(connections.connects(IVec3::X, IVec3::NEG_X));
+    assert!(connections.connects(IVec3::NEG_X, IVec3::X));
+    assert!(!connections.connects(IVec3::X, IVec3::Y));
+    assert!(!connections.connects(IVec3::Z, IVec3::NEG_Z));
+    assert!(!connections.connects(IVec3::Y, IVec3::Y));
+}
+
+#[test]
+fn solid_ground_hides_the_chunks_below_it() {
+    // A 3x3x3 world: open sky on top, a solid layer, then a buried cave layer.
+    let bounds = (IVec3::ZERO, IVec3::splat(2));
+    let connections = |coord: IVec3| match coord.y {
+        1 => FaceConnections::NONE,
+        _ => FaceConnections::ALL,
+    };
+    let visible = visible_chunks(IVec3::new(1, 2, 1), bounds, connections, |_| true);
+    for x in 0..3 {
+        for z in 0..3 {
+            assert!(visible.contains(&IVec3::new(x, 2, z)));
+            // The surface chunks themselves are seen; nothing behind them is.
+            assert!(visible.contains(&IVec3::new(x, 1, z)));
+            assert!(!visible.contains(&IVec3::new(x, 0, z)));
+        }
+    }
+}
+
+#[test]
+fn walks_respect_view_and_bounds() {
+    let bounds = (IVec3::ZERO, IVec3::new(4, 0, 0));
+    let all = |_| FaceConnections::ALL;
+    let visible = visible_chunks(IVec3::new(-3, 0, 0), bounds, all, |_| true);
+    assert_eq!(visible.len(), 5);
+    // Chunks outside the view stop the walk.
+    let visible = visible_chunks(IVec3::ZERO, bounds, all, |coord| coord.x < 2);
+    assert_eq!(visible.len(), 2);
+}
@@ -0,0 +1,99 @@
+use bevy::math::IVec3;
+use platz::voxel::{BlockShape, Property, Voxel};
+use platz::world::{Chunk, ChunkStorage};
+
+#[test]
+fn state_bits_round_trip_per_property() {
+    let water = Voxel::water(5, true);
+    assert_eq!(water.id(), Voxel::WATER.id());
+    assert_eq!(water.get(Property::Level), Some(5));
+    assert_eq!(water.get(Property::Falling), Some(1));
+    assert_eq!(water.with(Property::Level, 2).water_level(), Some(2));
+    assert!(water.with(Property::Level, 2).is_falling_water());
+
+    // Properties a block does not have read as nothing and do not write.
+    assert_eq!(Voxel::GROUND.get(Property::Facing), None);
+    assert_eq!(Voxel::GROUND.with(Property::Level, 3), Voxel::GROUND);
+
+    for facing in [IVec3::X, IVec3::Z, IVec3::NEG_X, IVec3::NEG_Z] {
+        let stairs = Voxel::brick_stairs(facing);
+        assert_eq!(stairs.facing(), Some(facing));
+        assert_eq!(stairs.shape(), BlockShape::Stairs(facing));
+        assert_eq!(stairs.info().name, "brick_stairs");
+    }
+    let top = Voxel::COBBLESTONE_SLAB.with(Property::Half, 1);
+    assert_eq!(top.shape(), BlockShape::Slab { top: true });
+    assert!(top.shape().covers(IVec3::Y));
+    assert!(!top.shape().covers(IVec3::NEG_Y));
+}
+
+#[test]
+fn names_carry_optional_state() {
+    assert_eq!(Voxel::from_name("glass"), Some(Voxel::GLASS));
+    assert_eq!(
+        Voxel::from_name("water[level=3, falling=true]"),
+        Some(Voxel::water(3, true))
+    );
+    assert_eq!(
+        Voxel::from_name("brick_stairs[facing=north]"),
+        Some(Voxel::brick_stairs(IVec3::NEG_Z))
+    );
+    assert_eq!(
+        Voxel::from_name("cobblestone_slab[half=top]").map(Voxel::shape),
+        Some(BlockShape::Slab { top: true })
+    );
+    assert_eq!(Voxel::from_name("water[level=8]"), None);
+    assert_eq!(Voxel::from_name("brick[facing=east]"), None);
+    assert_eq!(Voxel::from_name("brick_stairs[facing=up]"), None);
+    assert_eq!(Voxel::from_name("brick_stairs[facing=east"), None);
+}
+
+#[test]
+fn chunk_storage_widens_only_as_needed() {
+    let mut chunk = Chunk::new();
+    assert_eq!(chunk.storage(), ChunkStorage::Uniform);
+    chunk.set(1, 2, 3, Voxel::AIR);
+    assert_eq!(chunk.storage(), ChunkStorage::Uniform);
+
+    chunk.set(1, 2, 3, Voxel::GROUND);
+    assert_eq!(chunk.storage(), ChunkStorage::Plain);
+    assert_eq!(chunk.get(1, 2, 3), Voxel::GROUND);
+    assert_eq!(chunk.get(3, 2, 1), Voxel::AIR);
+
+    let stairs = Voxel::brick_stairs(IVec3::NEG_X);
+    chunk.set(4, 4, 4, stairs);
+    assert_eq!(chunk.storage(), ChunkStorage::Full);
+    assert_eq!(chunk.get(4, 4, 4), stairs);
+    assert_eq!(chunk.get(1, 2, 3), Voxel::GROUND);
+
+    chunk.set(4, 4, 4, Voxel::AIR);
+    chunk.compact();
+    assert_eq!(chunk.storage(), ChunkStorage::Plain);
+    chunk.set(1, 2, 3, Voxel::AIR);
+    chunk.compact();
+    assert_eq!(chunk.storage(), ChunkStorage::Uniform);
+    assert!(chunk.is_empty());
+
+    let mut water = Chunk::filled(Voxel::water(2, false));
+    assert!(!water.is_empty());
+    water.set(0, 0, 0, Voxel::GROUND);
+    assert_eq!(water.storage(), ChunkStorage::Full);
+    assert_eq!(water.get(0, 1, 0).water_level(), Some(2));
+}
+
+#[test]
+fn names_round_trip_through_display() {
+    for voxel in [
+        Voxel::GROUND,
+        Voxel::water(6, true),
+        Voxel::brick_stairs(IVec3::Z),
+        Voxel::COBBLESTONE_SLAB.with(Property::Half, 1),
+    ] {
+        assert_eq!(Voxel::from_name(&voxel.to_string()), Some(voxel));
+    }
+    assert_eq!(
+        Voxel::brick_stairs(IVec3::NEG_X).to_string(),
+        "brick_stairs[facing=west]"
+    );
+    assert_eq!(Voxel::LAMP.to_string(), "lamp");
+}
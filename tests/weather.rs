@@ -0,0 +1,181 @@
+use bevy::math::{IVec3, Vec3};
+use platz::voxel::Voxel;
+use platz::weather::{is_sheltered, snow_layer_cell, Weather, WeatherKind};
+use platz::world::{HeightMap, VoxelWorld};
+
+fn flat_world() -> VoxelWorld {
+    let mut world = VoxelWorld::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..=3 {
+                world.set(IVec3::new(x, y, z), Voxel::GROUND);
+            }
+        }
+    }
+    world
+}
+
+#[test]
+fn weather_only_moves_along_allowed_transitions() {
+    let mut weather = Weather::new(WeatherKind::Clear, 7);
+    let mut visited = vec![weather.current()];
+    for _ in 0..20_000 {
+        let before = weather.current();
+        weather.advance(1.0);
+        if let Some(next) = weather.next() {
+            assert!(
+                before.transitions().iter().any(|&(kind, _)| kind == next),
+                "{before:?} -> {next:?}"
+            );
+        }
+        if weather.current() != before && !visited.contains(&weather.current()) {
+            visited.push(weather.current());
+        }
+    }
+    assert_eq!(visited.len(), WeatherKind::ALL.len());
+}
+
+#[test]
+fn same_seed_gives_same_weather_sequence() {
+    let mut a = Weather::new(WeatherKind::Overcast, 42);
+    let mut b = Weather::new(WeatherKind::Overcast, 42);
+    for _ in 0..5_000 {
+        a.advance(0.5);
+        b.advance(0.5);
+        assert_eq!(a.current(), b.current());
+        assert_eq!(a.next(), b.next());
+    }
+}
+
+#[test]
+fn transitions_blend_profiles() {
+    let mut weather = Weather::new(WeatherKind::Clear, 3);
+    weather.locked = true;
+    weather.transition_secs = 10.0;
+    weather.set(WeatherKind::Rain);
+
+    weather.advance(5.0);
+    let clear = WeatherKind::Clear.profile();
+    let rain = WeatherKind::Rain.profile();
+    let halfway = weather.profile();
+    assert!((halfway.rain - 0.5).abs() < 1e-4);
+    assert!(halfway.fog_density > clear.fog_density && halfway.fog_density < rain.fog_density);
+
+    weather.advance(5.0);
+    assert_eq!(weather.current(), WeatherKind::Rain);
+    assert_eq!(weather.profile(), rain);
+    // Locked weather stays put.
+    weather.advance(10_000.0);
+    assert_eq!(weather.current(), WeatherKind::Rain);
+    assert_eq!(weather.next(), None);
+}
+
+#[test]
+fn changing_weather_mid_transition_blends_from_the_current_mix() {
+    let mut weather = Weather::new(WeatherKind::Clear, 3);
+    weather.locked = true;
+    weather.transition_secs = 10.0;
+    weather.set(WeatherKind::Fog);
+    weather.advance(5.0);
+    let shown = weather.profile();
+
+    // Switching target keeps what is on screen, then heads for rain.
+    weather.set(WeatherKind::Rain);
+    assert_eq!(weather.profile(), shown);
+    weather.advance(5.0);
+    let rain = WeatherKind::Rain.profile();
+    assert_eq!(weather.profile(), shown.lerp(rain, 0.5));
+    weather.advance(5.0);
+    assert_eq!(weather.current(), WeatherKind::Rain);
+    assert_eq!(weather.profile(), rain);
+
+    // Going back to the current state mid-transition fades back smoothly.
+    weather.set(WeatherKind::Overcast);
+    weather.advance(2.0);
+    let shown = weather.profile();
+    weather.set(WeatherKind::Rain);
+    assert_eq!(weather.next(), Some(WeatherKind::Rain));
+    assert_eq!(weather.profile(), shown);
+    weather.advance(10.0);
+    assert_eq!(weather.current(), WeatherKind::Rain);
+    assert_eq!(weather.next(), None);
+    assert_eq!(weather.profile(), rain);
+}
+
+#[test]
+fn command_line_selects_weather() {
+    let args = ["--weather", "Snow", "--lock-weather", "--snow-cover"].map(String::from);
+    let weather = Weather::from_args(args).unwrap();
+    assert_eq!(weather.current(), WeatherKind::Snow);
+    assert!(weather.locked);
+    assert!(weather.accumulate_snow);
+    assert!(Weather::from_args(["--weather", "hail"].map(String::from)).is_err());
+}
+
+#[test]
+fn heightmap_tracks_edits() {
+    let mut world = flat_world();
+    let mut heights = HeightMap::build(&world);
+    assert_eq!(heights.top(5, 5), Some(3));
+    assert_eq!(heights.top(40, 5), None);
+
+    let roof = IVec3::new(5, 40, 5);
+    world.set(roof, Voxel::GROUND);
+    heights.update(&world, roof);
+    assert_eq!(heights.top(5, 5), Some(40));
+
+    world.set(roof, Voxel::AIR);
+    heights.update(&world, roof);
+    assert_eq!(heights.top(5, 5), Some(3));
+
+    for y in 0..=3 {
+        let pos = IVec3::new(6, y, 6);
+        world.set(pos, Voxel::AIR);
+        heights.update(&world, pos);
+    }
+    assert_eq!(heights.top(6, 6), None);
+
+    // Clearing a span refreshes the column in one go.
+    for y in 2..=12 {
+        world.set(IVec3::new(7, y, 7), Voxel::GROUND);
+    }
+    heights.update_span(&world, 7, 7, 2, 12);
+    assert_eq!(heights.top(7, 7), Some(12));
+    for y in 2..=12 {
+        world.set(IVec3::new(7, y, 7), Voxel::AIR);
+    }
+    heights.update_span(&world, 7, 7, 2, 12);
+    assert_eq!(heights.top(7, 7), Some(1));
+}
+
+#[test]
+fn overhangs_shelter_from_precipitation() {
+    let mut world = flat_world();
+    for x in 4..8 {
+        world.set(IVec3::new(x, 8, 4), Voxel::GROUND);
+    }
+    let heights = HeightMap::build(&world);
+    assert!(is_sheltered(&heights, Vec3::new(5.5, 5.0, 4.5)));
+    assert!(!is_sheltered(&heights, Vec3::new(5.5, 9.2, 4.5)));
+    assert!(!is_sheltered(&heights, Vec3::new(5.5, 5.0, 6.5)));
+}
+
+#[test]
+fn snow_settles_once_on_exposed_tops() {
+    let mut world = flat_world();
+    world.set(IVec3::new(2, 9, 2), Voxel::GROUND);
+    let mut heights = HeightMap::build(&world);
+
+    let cell = snow_layer_cell(&world, &heights, 5, 5).unwrap();
+    assert_eq!(cell, IVec3::new(5, 4, 5));
+    world.set(cell, Voxel::SNOW);
+    heights.update(&world, cell);
+    assert_eq!(snow_layer_cell(&world, &heights, 5, 5), None);
+
+    // Snow lands on the roof, not under it.
+    assert_eq!(
+        snow_layer_cell(&world, &heights, 2, 2),
+        Some(IVec3::new(2, 10, 2))
+    );
+    assert_eq!(snow_layer_cell(&world, &heights, 40, 40), None);
+}
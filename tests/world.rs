@@ -0,0 +1,47 @@
+use bevy::math::IVec3;
+use platz::voxel::Voxel;
+use platz::world::{ChunkStorage, VoxelWorld};
+
+#[test]
+fn world_files_round_trip() {
+    let mut world = VoxelWorld::new();
+    for x in -40..40 {
+        world.set(IVec3::new(x, 0, 3), Voxel::GROUND);
+    }
+    world.set(IVec3::new(5, 1, 3), Voxel::brick_stairs(IVec3::NEG_Z));
+    world.set(IVec3::new(-7, 33, 70), Voxel::water(3, true));
+
+    let mut bytes = Vec::new();
+    world.write_to(&mut bytes).unwrap();
+    let loaded = VoxelWorld::read_from(&mut bytes.as_slice()).unwrap();
+    assert_eq!(loaded.chunk_count(), world.chunk_count());
+    for (coord, chunk) in world.chunks() {
+        let other = loaded.chunk(coord).unwrap();
+        assert!(chunk.voxels().eq(other.voxels()), "chunk {coord}");
+    }
+    // Loaded chunks come back in their smallest storage.
+    let plain = loaded.chunk(IVec3::new(-2, 0, 0)).unwrap();
+    assert_eq!(plain.storage(), ChunkStorage::Plain);
+    assert_eq!(
+        loaded.get(IVec3::new(5, 1, 3)),
+        Voxel::brick_stairs(IVec3::NEG_Z)
+    );
+}
+
+#[test]
+fn damaged_world_files_are_rejected() {
+    let mut world = VoxelWorld::new();
+    world.set(IVec3::new(1, 2, 3), Voxel::LAMP);
+    let mut bytes = Vec::new();
+    world.write_to(&mut bytes).unwrap();
+
+    assert!(VoxelWorld::read_from(&mut &bytes[..bytes.len() - 1]).is_err());
+    let mut wrong_magic = bytes.clone();
+    wrong_magic[0] = b'X';
+    assert!(VoxelWorld::read_from(&mut wrong_magic.as_slice()).is_err());
+    // A run reaching past the end of the chunk.
+    let mut long_run = bytes.clone();
+    long_run[21] = 0xff;
+    long_run[22] = 0xff;
+    assert!(VoxelWorld::read_from(&mut long_run.as_slice()).is_err());
+}
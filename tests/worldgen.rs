@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+
+use bevy::math::{IVec2, IVec3, UVec3, Vec2};
+use platz::structure::Connector;
+use platz::voxel::Voxel;
+use platz::world::{chunk_coord, local_coord, Chunk, CHUNK_SIZE};
+use platz::worldgen::{Biome, DecorationKind, OreVein, TerrainGenerator, Village, VILLAGE_REGION};
+
+const WORLD: i32 = 9 * CHUNK_SIZE as i32;
+
+fn column_top(generator: &TerrainGenerator, x: i32, z: i32) -> i32 {
+    generator.surface_height(x as f32, z as f32).floor() as i32
+}
+
+fn generate_chunk(generator: &TerrainGenerator, coord: IVec3) -> Chunk {
+    let mut chunk = Chunk::new();
+    generator.populate_chunk(coord, &mut chunk);
+    generator.populate_underground(coord, &mut chunk);
+    let decorations = generator.column_decorations(IVec2::new(coord.x, coord.z));
+    generator.decorate_chunk(coord, &mut chunk, &decorations);
+    chunk
+}
+
+#[test]
+fn everything_below_sea_level_is_water_or_ground() {
+    let generator = TerrainGenerator::default();
+    for x in (0..WORLD).step_by(7) {
+        for z in (0..WORLD).step_by(7) {
+            for y in 0..=generator.sea_level {
+                let voxel = generator.voxel_at(IVec3::new(x, y, z));
+                assert!(!voxel.is_empty(), "air below sea level at {x}, {y}, {z}");
+            }
+            let above = generator.voxel_at(IVec3::new(x, generator.sea_level + 1, z));
+            assert!(!above.is_water());
+        }
+    }
+}
+
+#[test]
+fn shorelines_are_sand() {
+    let generator = TerrainGenerator::default();
+    let mut shore_columns = 0;
+    for x in 0..WORLD {
+        for z in 0..WORLD {
+            let top = column_top(&generator, x, z);
+            let voxel = generator.voxel_at(IVec3::new(x, top, z));
+            if top <= generator.sea_level + generator.beach_height {
+                shore_columns += 1;
+                assert_eq!(voxel, Voxel::SAND, "column {x}, {z}");
+            } else {
+                assert_eq!(voxel, Voxel::GROUND, "column {x}, {z}");
+            }
+        }
+    }
+    assert!(shore_columns > 0);
+}
+
+#[test]
+fn default_world_has_some_water_but_is_mostly_land() {
+    let generator = TerrainGenerator::default();
+    let mut wet = 0;
+    for x in 0..WORLD {
+        for z in 0..WORLD {
+            if column_top(&generator, x, z) < generator.sea_level {
+                wet += 1;
+            }
+        }
+    }
+    let fraction = wet as f32 / (WORLD * WORLD) as f32;
+    assert!(fraction > 0.05 && fraction < 0.4, "water covers {fraction}");
+}
+
+#[test]
+fn chunks_match_point_queries_and_seed_changes_the_map() {
+    let generator = TerrainGenerator::default();
+    let coord = IVec3::new(2, 0, 3);
+    let mut chunk = Chunk::new();
+    generator.populate_chunk(coord, &mut chunk);
+    for (x, y, z) in [(0, 0, 0), (5, 9, 17), (31, 12, 31), (16, 20, 4)] {
+        let world = coord * CHUNK_SIZE as i32 + IVec3::new(x, y, z);
+        assert_eq!(
+            chunk.get(x as u32, y as u32, z as u32),
+            generator.voxel_at(world)
+        );
+    }
+
+    let other = TerrainGenerator {
+        seed: 99,
+        ..TerrainGenerator::default()
+    };
+    let differs = (0..WORLD).any(|x| {
+        (0..WORLD)
+            .step_by(5)
+            .any(|z| column_top(&generator, x, z) != column_top(&other, x, z))
+    });
+    assert!(differs);
+}
+
+#[test]
+fn raising_sea_level_floods_more_columns() {
+    let low = TerrainGenerator::default();
+    let high = TerrainGenerator {
+        sea_level: low.sea_level + 4,
+        ..TerrainGenerator::default()
+    };
+    let count = |generator: &TerrainGenerator| {
+        (0..WORLD)
+            .step_by(3)
+            .flat_map(|x| (0..WORLD).step_by(3).map(move |z| (x, z)))
+            .filter(|&(x, z)| {
+                generator
+                    .voxel_at(IVec3::new(x, generator.sea_level, z))
+                    .is_water()
+            })
+            .count()
+    };
+    assert!(count(&high) > count(&low));
+}
+
+#[test]
+fn trees_crossing_chunk_borders_are_written_on_both_sides() {
+    let generator = TerrainGenerator::default();
+    let mut crossing = 0;
+    for cx in 0..4 {
+        for cz in 0..4 {
+            let column = IVec2::new(cx, cz);
+            for tree in generator
+                .column_decorations(column)
+                .into_iter()
+                .filter(|decoration| decoration.kind == DecorationKind::Tree)
+            {
+                let base_column = chunk_coord(tree.base);
+                if IVec2::new(base_column.x, base_column.z) != column {
+                    continue;
+                }
+                let voxels = tree.voxels();
+                if voxels
+                    .iter()
+                    .all(|(pos, _)| chunk_coord(*pos) == base_column)
+                {
+                    continue;
+                }
+                crossing += 1;
+                // Every chunk is generated on its own, so each one must find
+                // the tree by itself.
+                for (pos, voxel) in voxels {
+                    let chunk = generate_chunk(&generator, chunk_coord(pos));
+                    let local = local_coord(pos);
+                    let found = chunk.get(local.x, local.y, local.z);
+                    match voxel {
+                        Voxel::LOG => assert_eq!(found, Voxel::LOG, "{pos}"),
+                        _ => assert!(found == Voxel::LEAVES || found == Voxel::LOG, "{pos}"),
+                    }
+                }
+            }
+        }
+    }
+    assert!(crossing > 0, "no tree crosses a chunk border");
+}
+
+#[test]
+fn decorations_are_spaced_and_grow_on_gentle_ground() {
+    let generator = TerrainGenerator::default();
+    let decorations = generator.column_decorations(IVec2::new(4, 2));
+    assert!(!decorations.is_empty());
+    for (i, a) in decorations.iter().enumerate() {
+        assert_ne!(a.biome, Biome::Shore);
+        let ground = a.base - IVec3::Y;
+        assert_eq!(generator.voxel_at(ground), Voxel::GROUND, "{ground}");
+        assert!(generator.voxel_at(a.base).is_empty());
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let neighbour = column_top(&generator, ground.x + dx, ground.z + dz);
+            assert!((neighbour - ground.y).abs() <= a.kind.max_slope());
+        }
+        for b in &decorations[i + 1..] {
+            if a.kind == b.kind {
+                let delta = a.base - b.base;
+                let distance = delta.x * delta.x + delta.z * delta.z;
+                assert!(distance >= a.kind.spacing().pow(2), "{a:?} {b:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn clearings_stay_free_of_decorations() {
+    let centre = Vec2::new(80.0, 80.0);
+    let generator = TerrainGenerator {
+        clearings: vec![(centre, 20.0)],
+        ..TerrainGenerator::default()
+    };
+    let open = TerrainGenerator::default();
+    let near = |generator: &TerrainGenerator| {
+        generator
+            .column_decorations(IVec2::new(2, 2))
+            .into_iter()
+            .filter(|decoration| {
+                let base = decoration.base;
+                Vec2::new(base.x as f32 + 0.5, base.z as f32 + 0.5).distance(centre) < 20.0
+            })
+            .count()
+    };
+    assert!(near(&open) > 0);
+    assert_eq!(near(&generator), 0);
+}
+
+fn underground_chunk(generator: &TerrainGenerator, coord: IVec3) -> Chunk {
+    let mut chunk = Chunk::new();
+    generator.populate_chunk(coord, &mut chunk);
+    generator.populate_underground(coord, &mut chunk);
+    chunk
+}
+
+#[test]
+fn ores_replace_ground_within_their_depth_range() {
+    let generator = TerrainGenerator {
+        dungeon_chance: 0.0,
+        ..TerrainGenerator::default()
+    };
+    let mut found = [0; 3];
+    for (cx, cz) in [(1, 1), (4, 6), (7, 2)] {
+        let coord = IVec3::new(cx, 0, cz);
+        let chunk = underground_chunk(&generator, coord);
+        for y in 0..CHUNK_SIZE as u32 {
+            for z in 0..CHUNK_SIZE as u32 {
+                for x in 0..CHUNK_SIZE as u32 {
+                    let voxel = chunk.get(x, y, z);
+                    let Some(index) = generator.ores.iter().position(|ore| ore.voxel == voxel)
+                    else {
+                        continue;
+                    };
+                    found[index] += 1;
+                    let ore = generator.ores[index];
+                    let pos = coord * CHUNK_SIZE as i32 + UVec3::new(x, y, z).as_ivec3();
+                    assert_eq!(generator.voxel_at(pos), Voxel::GROUND, "{pos}");
+                    let reach = ore.size as i32;
+                    assert!(pos.y >= ore.min_y - reach && pos.y <= ore.max_y + reach);
+                }
+            }
+        }
+    }
+    assert!(found.iter().all(|&count| count > 0), "{found:?}");
+}
+
+#[test]
+fn ore_table_drives_what_is_scattered() {
+    let plain = TerrainGenerator {
+        ores: Vec::new(),
+        dungeon_chance: 0.0,
+        ..TerrainGenerator::default()
+    };
+    let crystal_only = TerrainGenerator {
+        ores: vec![OreVein {
+            voxel: Voxel::CRYSTAL,
+            min_y: 0,
+            max_y: 20,
+            frequency: 12.0,
+            size: 8,
+        }],
+        ..plain.clone()
+    };
+    let coord = IVec3::new(3, 0, 3);
+    let mut heightfield = Chunk::new();
+    plain.populate_chunk(coord, &mut heightfield);
+    let without = underground_chunk(&plain, coord);
+    let with = underground_chunk(&crystal_only, coord);
+
+    let mut crystals = 0;
+    for y in 0..CHUNK_SIZE as u32 {
+        for z in 0..CHUNK_SIZE as u32 {
+            for x in 0..CHUNK_SIZE as u32 {
+                assert_eq!(without.get(x, y, z), heightfield.get(x, y, z));
+                let voxel = with.get(x, y, z);
+                assert_ne!(voxel, Voxel::COAL_ORE);
+                crystals += usize::from(voxel == Voxel::CRYSTAL);
+            }
+        }
+    }
+    assert!(crystals > 0);
+}
+
+#[test]
+fn dungeons_are_buried_and_match_across_chunks() {
+    let generator = TerrainGenerator::default();
+    let dungeons: Vec<_> = (0..6)
+        .flat_map(|x| (0..6).map(move |z| IVec2::new(x, z)))
+        .filter_map(|region| generator.dungeon_in_region(region))
+        .collect();
+    assert!(!dungeons.is_empty());
+
+    for dungeon in &dungeons {
+        let max = dungeon.max();
+        for x in dungeon.min.x..=max.x {
+            for z in dungeon.min.z..=max.z {
+                for y in max.y + 1..=max.y + 3 {
+                    assert!(!generator.voxel_at(IVec3::new(x, y, z)).is_empty());
+                }
+            }
+        }
+    }
+
+    // Each chunk the room overlaps is generated on its own and must carry
+    // its share of the walls, air and lamp.
+    let dungeon = dungeons
+        .iter()
+        .find(|dungeon| chunk_coord(dungeon.min) != chunk_coord(dungeon.max()))
+        .expect("no dungeon crosses a chunk border");
+    let mut lamps = 0;
+    for x in dungeon.min.x..=dungeon.max().x {
+        for y in dungeon.min.y..=dungeon.max().y {
+            for z in dungeon.min.z..=dungeon.max().z {
+                let pos = IVec3::new(x, y, z);
+                let chunk = underground_chunk(&generator, chunk_coord(pos));
+                let local = local_coord(pos);
+                let voxel = chunk.get(local.x, local.y, local.z);
+                assert_eq!(Some(voxel), dungeon.voxel_at(pos), "{pos}");
+                lamps += usize::from(voxel == Voxel::LAMP);
+            }
+        }
+    }
+    assert_eq!(lamps, 1);
+}
+
+fn villages(generator: &TerrainGenerator) -> Vec<Village> {
+    (0..3)
+        .flat_map(|x| (0..3).map(move |z| IVec2::new(x, z)))
+        .filter_map(|region| generator.village_in_region(region))
+        .collect()
+}
+
+#[test]
+fn villages_are_deterministic_and_stay_inside_their_region() {
+    let generator = TerrainGenerator::default();
+    let first = villages(&generator);
+    assert!(!first.is_empty());
+    assert_eq!(first, villages(&generator));
+
+    for village in &first {
+        assert_eq!(village.pieces[0].piece, generator.village_pieces.start);
+        let bounds: Vec<_> = village
+            .pieces
+            .iter()
+            .map(|placed| {
+                generator.village_pieces.pieces[placed.piece]
+                    .template
+                    .bounds(&placed.placement)
+            })
+            .collect();
+        let region_min = village.region * VILLAGE_REGION;
+        for (i, (min, max)) in bounds.iter().enumerate() {
+            assert!(min.x >= region_min.x && max.x < region_min.x + VILLAGE_REGION);
+            assert!(min.z >= region_min.y && max.z < region_min.y + VILLAGE_REGION);
+            for (other_min, other_max) in &bounds[i + 1..] {
+                let apart = max.x < other_min.x
+                    || min.x > other_max.x
+                    || max.z < other_min.z
+                    || min.z > other_max.z;
+                assert!(apart, "pieces overlap in {:?}", village.region);
+            }
+        }
+    }
+}
+
+#[test]
+fn village_pieces_meet_door_to_door() {
+    let generator = TerrainGenerator::default();
+    for village in villages(&generator) {
+        let connectors: Vec<Vec<Connector>> = village
+            .pieces
+            .iter()
+            .map(|placed| {
+                generator.village_pieces.pieces[placed.piece]
+                    .template
+                    .connectors(&placed.placement)
+            })
+            .collect();
+        for (i, own) in connectors.iter().enumerate().skip(1) {
+            let joined = own.iter().any(|door| {
+                connectors[..i].iter().flatten().any(|other| {
+                    other.pos + other.facing == door.pos && other.facing == -door.facing
+                })
+            });
+            assert!(joined, "piece {i} of {:?} is not attached", village.region);
+        }
+    }
+}
+
+#[test]
+fn village_chunks_agree_and_floors_stand_on_foundations() {
+    let generator = TerrainGenerator::default();
+    let villages = villages(&generator);
+    let village = villages
+        .iter()
+        .max_by_key(|village| village.pieces.len())
+        .unwrap();
+    let mut chunks = HashMap::new();
+    let mut foundations = 0;
+    for placed in &village.pieces {
+        let template = &generator.village_pieces.pieces[placed.piece].template;
+        let floor = template.bounds(&placed.placement).0.y;
+        for (pos, voxel) in template.blocks(&placed.placement) {
+            let mut voxel_at = |pos: IVec3| {
+                let chunk = chunks.entry(chunk_coord(pos)).or_insert_with(|| {
+                    let mut chunk = Chunk::new();
+                    generator.populate_chunk(chunk_coord(pos), &mut chunk);
+                    generator.build_villages(chunk_coord(pos), &mut chunk, &villages);
+                    chunk
+                });
+                let local = local_coord(pos);
+                chunk.get(local.x, local.y, local.z)
+            };
+            assert_eq!(voxel_at(pos), voxel, "{pos}");
+            if pos.y == floor && !voxel.is_empty() {
+                let top = column_top(&generator, pos.x, pos.z);
+                for y in top + 1..floor {
+                    assert_eq!(voxel_at(IVec3::new(pos.x, y, pos.z)), Voxel::COBBLESTONE);
+                    foundations += 1;
+                }
+            }
+        }
+    }
+    assert!(foundations > 0);
+}
+
+#[test]
+fn ore_table_is_read_from_data() {
+    let shipped = OreVein::parse(include_str!("../assets/ores.txt")).unwrap();
+    assert_eq!(shipped, OreVein::table());
+    assert_eq!(shipped.len(), 3);
+    assert_eq!(shipped[1].voxel, Voxel::IRON_ORE);
+    assert_eq!((shipped[1].min_y, shipped[1].max_y), (0, 14));
+
+    let ores = OreVein::parse("// rare\nore crystal 2 9 0.5 3\n").unwrap();
+    assert_eq!(
+        ores,
+        [OreVein {
+            voxel: Voxel::CRYSTAL,
+            min_y: 2,
+            max_y: 9,
+            frequency: 0.5,
+            size: 3,
+        }]
+    );
+    assert!(OreVein::parse("ore mithril 0 4 1 2").is_err());
+    assert!(OreVein::parse("ore coal_ore 9 4 1 2").is_err());
+    assert!(OreVein::parse("ore coal_ore 0 4 often 2")
+        .unwrap_err()
+        .starts_with("line 1"));
+}